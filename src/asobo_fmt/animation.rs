@@ -0,0 +1,390 @@
+use std::fs::File;
+use std::path::Path;
+
+use binwrite::BinWrite;
+use nom_derive::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::asobo_fmt::common::{
+    FixedVec, HasReferences, ObjectFormat, ObjectFormatTrait, PascalArray, ResourceObjectZ, Vec3i32,
+};
+use crate::error::DpcError;
+use crate::glb::{GlbBuilder, GlbChannel, GlbJoint, GlbKeyframes};
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct AnimationZUnknown0 {
+    data: FixedVec<u8, 40>,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct AnimationZUnknown {
+    unknown0: u32,
+    unknown1: u32,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct AnimationZUnknown2 {
+    unknowns: FixedVec<AnimationZUnknown, 3>,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct AnimationZUnknown1 {
+    unknown0: u32,
+    unknown1: u32,
+    unknown2: u32,
+    unknown3: u32,
+    unknown4: u32,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct AnimationZUnknown4 {
+    unknown0: u32,
+    unknown1s: PascalArray<AnimationZUnknown1>,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct AnimationZUnknown5 {
+    unknown0: u32,
+    unknown1: u32,
+    unknown2: u32,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct AnimationZUnknown12 {
+    unknown0: u32,
+    unknown1: u32,
+    unknown2: u32,
+    unknown3: u32,
+    unknown4: u32,
+    unknown5: u32,
+    unknown6: u32,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+#[nom(Exact)]
+pub struct AnimationZ {
+    a: u32,
+    b: u32,
+    c: u16,
+    d: u16,
+    vectors: PascalArray<Vec3i32>,
+    unknown0s: PascalArray<AnimationZUnknown0>,
+    unknown2flag: u16,
+    unknown2s: PascalArray<AnimationZUnknown2>,
+    unknown3flag: u16,
+    unknown3s: PascalArray<AnimationZUnknown2>,
+    unknown4s: PascalArray<AnimationZUnknown4>,
+    unknown5flag: u16,
+    unknown5s: PascalArray<AnimationZUnknown5>,
+    unknown6flag: u16,
+    unknown6s: PascalArray<AnimationZUnknown5>,
+    unknown7flag: u16,
+    unknown7s: PascalArray<AnimationZUnknown2>,
+    unknown8flag: u16,
+    unknown8s: PascalArray<AnimationZUnknown2>,
+    unknown9flag: u16,
+    unknown9s: PascalArray<AnimationZUnknown5>,
+    unknown10flag: u16,
+    unknown10s: PascalArray<AnimationZUnknown5>,
+    unknown11flag: u16,
+    unknown11s: PascalArray<AnimationZUnknown5>,
+    unknown12s: PascalArray<AnimationZUnknown12>,
+    unknown13s: PascalArray<AnimationZUnknown12>,
+    unknown14s: PascalArray<AnimationZUnknown5>,
+    unknown15s: PascalArray<AnimationZUnknown5>,
+}
+
+impl HasReferences for AnimationZ {
+    fn hard_links(&self) -> Vec<u32> {
+        vec![]
+    }
+
+    fn soft_links(&self) -> Vec<u32> {
+        vec![]
+    }
+}
+
+// The flag and array pairs are keyframers. An AnimationZUnknown2 keyframe is a time followed by
+// a vector, an AnimationZUnknown5 one a time followed by a rotation compressed to four i16
+impl AnimationZUnknown2 {
+    fn time(&self) -> f32 {
+        f32::from_bits(self.unknowns.data[0].unknown0)
+    }
+
+    fn vector(&self) -> [f32; 3] {
+        [
+            f32::from_bits(self.unknowns.data[0].unknown1),
+            f32::from_bits(self.unknowns.data[1].unknown0),
+            f32::from_bits(self.unknowns.data[1].unknown1),
+        ]
+    }
+}
+
+impl AnimationZUnknown5 {
+    fn time(&self) -> f32 {
+        f32::from_bits(self.unknown0)
+    }
+
+    fn rotation(&self) -> [f32; 4] {
+        let components = [
+            self.unknown1 as u16 as i16,
+            (self.unknown1 >> 16) as u16 as i16,
+            self.unknown2 as u16 as i16,
+            (self.unknown2 >> 16) as u16 as i16,
+        ];
+        let rotation = components.map(|x| x as f32 / i16::MAX as f32);
+        let length = rotation.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if length == 0.0 {
+            [0.0, 0.0, 0.0, 1.0]
+        } else {
+            rotation.map(|x| x / length)
+        }
+    }
+}
+
+// glTF needs strictly increasing times and finite values, keyframers that don't have them are
+// left out
+fn glb_channel(joint: usize, times: Vec<f32>, keyframes: GlbKeyframes) -> Option<GlbChannel> {
+    let values: Vec<f32> = match &keyframes {
+        GlbKeyframes::Translation(values) | GlbKeyframes::Scale(values) => {
+            values.iter().flatten().copied().collect()
+        }
+        GlbKeyframes::Rotation(values) => values.iter().flatten().copied().collect(),
+    };
+    if times.is_empty()
+        || times.iter().chain(values.iter()).any(|x| !x.is_finite())
+        || times.windows(2).any(|x| x[0] >= x[1])
+    {
+        return None;
+    }
+
+    Some(GlbChannel {
+        joint,
+        times,
+        keyframes,
+    })
+}
+
+impl AnimationZ {
+    // The first vector keyframers move and scale the animated joint and the first rotation
+    // keyframer turns it. What the others animate is not known
+    fn glb_channels(&self, joint: usize) -> Vec<GlbChannel> {
+        let translations = &self.unknown2s.data;
+        let scales = &self.unknown3s.data;
+        let rotations = &self.unknown5s.data;
+
+        vec![
+            glb_channel(
+                joint,
+                translations.iter().map(|x| x.time()).collect(),
+                GlbKeyframes::Translation(translations.iter().map(|x| x.vector()).collect()),
+            ),
+            glb_channel(
+                joint,
+                scales.iter().map(|x| x.time()).collect(),
+                GlbKeyframes::Scale(scales.iter().map(|x| x.vector()).collect()),
+            ),
+            glb_channel(
+                joint,
+                rotations.iter().map(|x| x.time()).collect(),
+                GlbKeyframes::Rotation(rotations.iter().map(|x| x.rotation()).collect()),
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    // Animation_Z doesn't say which Skel_Z it plays on, so its keyframers drive the root joint of
+    // a skin of its own
+    fn glb_animation(&self, builder: &mut GlbBuilder) {
+        let mut identity = [0.0; 16];
+        identity[0] = 1.0;
+        identity[5] = 1.0;
+        identity[10] = 1.0;
+        identity[15] = 1.0;
+        let root = GlbJoint {
+            name: String::from("root"),
+            parent: None,
+            translation: [0.0; 3],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0; 3],
+            model_transform: identity,
+        };
+
+        let skin = builder.add_skin("skeleton", &[root]);
+        let channels = self.glb_channels(0);
+        if !channels.is_empty() {
+            builder.add_animation("animation", skin, &channels);
+        }
+    }
+}
+
+pub struct AnimationObjectFormat;
+
+impl AnimationObjectFormat {
+    pub fn new<'a>() -> &'a Self {
+        &Self {}
+    }
+}
+
+impl ObjectFormatTrait for AnimationObjectFormat {
+    fn pack(
+        &self,
+        input_path: &Path,
+        header: &mut Vec<u8>,
+        body: &mut Vec<u8>,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        ObjectFormat::<ResourceObjectZ, AnimationZ>::new().pack(input_path, header, body)
+    }
+
+    fn unpack(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        let references =
+            ObjectFormat::<ResourceObjectZ, AnimationZ>::new().unpack(header, body, output_path)?;
+
+        let animation = match AnimationZ::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
+
+        let mut builder = GlbBuilder::new();
+        animation.glb_animation(&mut builder);
+
+        let mut glb_file = File::create(output_path.join("data.glb"))?;
+        builder.write(&mut glb_file)?;
+
+        Ok(references)
+    }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        ObjectFormat::<ResourceObjectZ, AnimationZ>::new().to_json(header, body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use gltf::animation::util::ReadOutputs;
+
+    use super::*;
+
+    fn animation_z(translations: &[(f32, [f32; 3])], rotations: &[(f32, [i16; 4])]) -> AnimationZ {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&1f32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        // vectors, unknown0s
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&(translations.len() as u32).to_le_bytes());
+        for (time, vector) in translations {
+            data.extend_from_slice(&time.to_le_bytes());
+            for x in vector {
+                data.extend_from_slice(&x.to_le_bytes());
+            }
+            data.extend_from_slice(&[0; 8]);
+        }
+        // unknown3s, unknown4s
+        data.extend_from_slice(&[0; 10]);
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&(rotations.len() as u32).to_le_bytes());
+        for (time, rotation) in rotations {
+            data.extend_from_slice(&time.to_le_bytes());
+            for x in rotation {
+                data.extend_from_slice(&x.to_le_bytes());
+            }
+        }
+        // unknown6s to unknown11s, unknown12s to unknown15s
+        data.extend_from_slice(&[0; 6 * 6 + 4 * 4]);
+        AnimationZ::parse(&data[..]).unwrap().1
+    }
+
+    #[test]
+    fn test_animation_channels() {
+        let animation = animation_z(
+            &[(0.0, [0.0, 1.0, 2.0]), (0.5, [3.0, 4.0, 5.0])],
+            &[(0.0, [0, 0, 0, i16::MAX]), (1.0, [i16::MAX, 0, 0, 0])],
+        );
+        let channels = animation.glb_channels(0);
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].times, vec![0.0, 0.5]);
+        assert!(matches!(
+            &channels[0].keyframes,
+            GlbKeyframes::Translation(values) if values[1] == [3.0, 4.0, 5.0]
+        ));
+        assert!(matches!(
+            &channels[1].keyframes,
+            GlbKeyframes::Rotation(values) if values == &[[0.0, 0.0, 0.0, 1.0], [1.0, 0.0, 0.0, 0.0]]
+        ));
+
+        // Out of order times can't be played back
+        let animation = animation_z(&[(1.0, [0.0; 3]), (0.0, [0.0; 3])], &[]);
+        assert!(animation.glb_channels(0).is_empty());
+    }
+
+    #[test]
+    fn test_animation_glb_roundtrip() {
+        let animation = animation_z(
+            &[(0.0, [0.0, 1.0, 2.0]), (0.5, [3.0, 4.0, 5.0])],
+            &[(0.0, [0, 0, 0, i16::MAX]), (1.0, [i16::MAX, 0, 0, 0])],
+        );
+        let mut builder = GlbBuilder::new();
+        animation.glb_animation(&mut builder);
+        let mut glb = Vec::new();
+        builder.write(&mut glb).unwrap();
+
+        let (document, buffers, _) = gltf::import_slice(&glb).unwrap();
+        let joint = document.skins().next().unwrap().joints().next().unwrap();
+        assert_eq!(joint.name(), Some("root"));
+
+        let gltf_animation = document.animations().next().unwrap();
+        let mut translations = None;
+        let mut rotations = None;
+        for channel in gltf_animation.channels() {
+            assert_eq!(channel.target().node().index(), joint.index());
+            let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+            let times: Vec<f32> = reader.read_inputs().unwrap().collect();
+            match reader.read_outputs().unwrap() {
+                ReadOutputs::Translations(values) => {
+                    translations = Some((times, values.collect::<Vec<_>>()))
+                }
+                ReadOutputs::Rotations(values) => {
+                    rotations = Some((times, values.into_f32().collect::<Vec<_>>()))
+                }
+                _ => panic!("unexpected channel"),
+            }
+        }
+        assert_eq!(
+            translations,
+            Some((vec![0.0, 0.5], vec![[0.0, 1.0, 2.0], [3.0, 4.0, 5.0]]))
+        );
+        assert_eq!(
+            rotations,
+            Some((
+                vec![0.0, 1.0],
+                vec![[0.0, 0.0, 0.0, 1.0], [1.0, 0.0, 0.0, 0.0]]
+            ))
+        );
+    }
+}
@@ -1,12 +1,14 @@
 use std::fs::File;
-use std::io::{Error, ErrorKind, Read, Write};
+use std::io::{Read, Write};
 use std::path::Path;
 
 use binwrite::BinWrite;
 use nom_derive::Parse;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use crate::fuel_fmt::common::{FUELObjectFormatTrait, HasReferences, ResourceObjectZ};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormatTrait, ResourceObjectZ};
+use crate::error::DpcError;
 use std::fs;
 use zerocopy::AsBytes;
 
@@ -18,13 +20,13 @@ impl BinaryObjectFormat {
     }
 }
 
-impl FUELObjectFormatTrait for BinaryObjectFormat {
+impl ObjectFormatTrait for BinaryObjectFormat {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
@@ -51,11 +53,11 @@ impl FUELObjectFormatTrait for BinaryObjectFormat {
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
@@ -64,7 +66,7 @@ impl FUELObjectFormatTrait for BinaryObjectFormat {
 
         let resource_object = match ResourceObjectZ::parse(&header) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
         };
 
         #[derive(Serialize)]
@@ -83,4 +85,13 @@ impl FUELObjectFormatTrait for BinaryObjectFormat {
             object.resource_object.soft_links(),
         ))
     }
+
+    fn to_json(&self, header: &[u8], _body: &[u8]) -> Result<Value, DpcError> {
+        let resource_object = match ResourceObjectZ::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        Ok(json!({ "resource_object": serde_json::to_value(resource_object)? }))
+    }
 }
@@ -0,0 +1,47 @@
+use binwrite::BinWrite;
+use nom_derive::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, ObjectZ};
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+#[nom(Exact)]
+pub struct CameraZ {
+    angle_of_view: f32,
+    zero: f32,
+    node_crc32: u32,
+}
+
+impl HasReferences for CameraZ {
+    fn hard_links(&self) -> Vec<u32> {
+        vec![]
+    }
+
+    fn soft_links(&self) -> Vec<u32> {
+        let mut v = Vec::new();
+        if self.node_crc32 != 0 {
+            v.push(self.node_crc32)
+        }
+        v
+    }
+}
+
+pub type CameraObjectFormat = ObjectFormat<ObjectZ, CameraZ>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_camera_links() {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&60f32.to_le_bytes());
+        data.extend_from_slice(&0f32.to_le_bytes());
+        data.extend_from_slice(&3213513545u32.to_le_bytes());
+        let (_, camera) = CameraZ::parse(&data[..]).unwrap();
+        assert_eq!(camera.hard_links(), Vec::<u32>::new());
+        assert_eq!(camera.soft_links(), vec![3213513545]);
+    }
+}
@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, FixedVec, HasReferences, Mat4f, ObjectZ};
+use crate::asobo_fmt::common::{FixedVec, HasReferences, Mat4f, ObjectFormat, ObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -27,4 +27,4 @@ impl HasReferences for CollisionVolZ {
     }
 }
 
-pub type CollisionVolObjectType = FUELObjectFormat<ObjectZ, CollisionVolZ>;
+pub type CollisionVolObjectType = ObjectFormat<ObjectZ, CollisionVolZ>;
@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::{Error, ErrorKind, Write};
+use std::io::{Error, Write};
 use std::marker::PhantomData;
 use std::path::Path;
 use std::vec::Vec;
@@ -12,6 +12,9 @@ use binwrite::{BinWrite, WriterOption};
 pub use nom_derive::*;
 use num_traits::{cast, NumCast};
 pub use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::error::DpcError;
 
 pub trait HasReferences {
     fn hard_links(&self) -> Vec<u32>;
@@ -76,7 +79,10 @@ where
             data: Vec::deserialize(deserializer)?,
         };
         if fv.data.len() != U {
-            panic!("FixedVec size does not match");
+            return Err(serde::de::Error::invalid_length(
+                fv.data.len(),
+                &format!("{} elements", U).as_str(),
+            ));
         }
         Ok(fv)
     }
@@ -181,7 +187,7 @@ where
 }
 
 impl<T> PascalArray<T> {
-    pub fn len(self: &Self) -> usize {
+    pub fn len(&self) -> usize {
         self.data.len()
     }
 }
@@ -287,13 +293,22 @@ impl<'de> Deserialize<'de> for PascalStringNULL {
 
 #[derive(NomLE)]
 pub struct FixedStringNULL<const U: usize> {
-    #[nom(
-        Map = "|x: &[u8]| String::from_utf8_lossy(x.split_at(x.iter().position(|&r| r == 0u8).unwrap()).0).to_string()",
-        Take = "U"
-    )]
+    #[nom(Parse = "{ |i| parse_fixed_string_null(i, U) }")]
     data: String,
 }
 
+// A name without its NUL terminator is corrupt, so the parse fails rather than guessing its end
+fn parse_fixed_string_null(i: &[u8], size: usize) -> ::nom::IResult<&[u8], String> {
+    let (i, data) = ::nom::bytes::complete::take(size)(i)?;
+    match data.iter().position(|&r| r == 0u8) {
+        Some(end) => Ok((i, String::from_utf8_lossy(&data[..end]).to_string())),
+        None => Err(::nom::Err::Error(::nom::error::Error::new(
+            data,
+            ::nom::error::ErrorKind::Verify,
+        ))),
+    }
+}
+
 impl<const U: usize> BinWrite for FixedStringNULL<U> {
     fn write_options<W: Write>(&self, writer: &mut W, options: &WriterOption) -> Result<(), Error> {
         BinWrite::write_options(&self.data, writer, options)?;
@@ -409,37 +424,34 @@ impl HasReferences for ObjectZ {
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        if let Some(crc32s) = &self.crc32s {
+        let mut v = if let Some(crc32s) = &self.crc32s {
             crc32s.clone()
         } else if self.data_crc32 != 0 {
             vec![self.data_crc32]
         } else {
             vec![]
+        };
+        if self.link_crc32 != 0 {
+            v.push(self.link_crc32)
         }
+        v
     }
 }
 
-pub trait FUELObjectFormatTrait {
-    fn pack(
-        self: &Self,
-        input_path: &Path,
-        header: &mut Vec<u8>,
-        body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error>;
-    fn unpack(
-        self: &Self,
-        header: &[u8],
-        body: &[u8],
-        output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error>;
-}
+pub use crate::base_dpc::ObjectFormatTrait;
 
-pub struct FUELObjectFormat<T, U> {
+pub struct ObjectFormat<T, U> {
     x: PhantomData<T>,
     y: PhantomData<U>,
 }
 
-impl<T, U> FUELObjectFormat<T, U> {
+#[derive(Serialize, Deserialize)]
+struct Object<T, U> {
+    header: T,
+    body: U,
+}
+
+impl<T, U> ObjectFormat<T, U> {
     pub fn new<'a>() -> &'a Self {
         &Self {
             x: PhantomData,
@@ -448,26 +460,40 @@ impl<T, U> FUELObjectFormat<T, U> {
     }
 }
 
-impl<T, U> FUELObjectFormatTrait for FUELObjectFormat<T, U>
+impl<T, U> ObjectFormat<T, U>
+where
+    for<'a> T: Parse<&'a [u8]>,
+    for<'a> U: Parse<&'a [u8]>,
+{
+    fn parse_object(header: &[u8], body: &[u8]) -> Result<Object<T, U>, DpcError> {
+        let header = match T::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        let body = match U::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
+
+        Ok(Object { header, body })
+    }
+}
+
+impl<T, U> ObjectFormatTrait for ObjectFormat<T, U>
 where
     for<'a> T: Parse<&'a [u8]> + Serialize + Deserialize<'a> + BinWrite + HasReferences,
     for<'a> U: Parse<&'a [u8]> + Serialize + Deserialize<'a> + BinWrite + HasReferences,
 {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
-        #[derive(Serialize, Deserialize)]
-        struct Object<T, U> {
-            header: T,
-            body: U,
-        }
-
         let object: Object<T, U> = serde_json::from_reader(json_file)?;
 
         object.header.write(header)?;
@@ -488,31 +514,15 @@ where
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
-        let header = match T::parse(&header) {
-            Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
-        };
-
-        let body = match U::parse(&body) {
-            Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
-        };
-
-        #[derive(Serialize, Deserialize)]
-        struct Object<T, U> {
-            header: T,
-            body: U,
-        }
-
-        let object = Object { header, body };
+        let object = Self::parse_object(header, body)?;
 
         output_file.write(serde_json::to_string_pretty(&object)?.as_bytes())?;
 
@@ -529,6 +539,10 @@ where
 
         Ok((hard_links, soft_links))
     }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        Ok(serde_json::to_value(Self::parse_object(header, body)?)?)
+    }
 }
 
 #[derive(BinWrite)]
@@ -548,3 +562,16 @@ pub struct DynBox {
     flags: u32,
     dyn_box_name: u32,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fixed_string_null() {
+        let (i, name) = FixedStringNULL::<4>::parse(&b"ab\0\0cd"[..]).unwrap();
+        assert_eq!(name.data, "ab");
+        assert_eq!(i, b"cd");
+        assert!(FixedStringNULL::<4>::parse(&b"abcd"[..]).is_err());
+    }
+}
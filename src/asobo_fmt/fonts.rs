@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, PascalArray, ResourceObjectZ};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, PascalArray, ResourceObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -38,4 +38,4 @@ impl HasReferences for FontsZ {
     }
 }
 
-pub type FontsObjectFormat = FUELObjectFormat<ResourceObjectZ, FontsZ>;
+pub type FontsObjectFormat = ObjectFormat<ResourceObjectZ, FontsZ>;
@@ -2,9 +2,9 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::walle_fmt::common::{
-    FixedStringNULL, FixedVec, HasReferences, Mat4f, ObjectZ, PascalArray, PascalStringNULL, Quat,
-    Vec2f, Vec3f, WALLEObjectFormat,
+use crate::asobo_fmt::common::{
+    FixedStringNULL, FixedVec, HasReferences, Mat4f, ObjectFormat, ObjectZ, PascalArray,
+    PascalStringNULL, Quat, Vec2f, Vec3f,
 };
 
 #[derive(BinWrite)]
@@ -103,8 +103,13 @@ impl HasReferences for GenWorldZ {
         v.append(&mut self.binary_crc32s.data.clone());
         v.append(&mut self.bitmap_crc32s.data.clone());
         v.append(&mut self.material_crc32s.data.clone());
+        for category in self.categories.data.iter() {
+            for node_crc32s in category.node_crc32s_arrays.data.iter() {
+                v.append(&mut node_crc32s.data.clone());
+            }
+        }
         v
     }
 }
 
-pub type GenWorldObjectFormat = WALLEObjectFormat<ObjectZ, GenWorldZ>;
+pub type GenWorldObjectFormat = ObjectFormat<ObjectZ, GenWorldZ>;
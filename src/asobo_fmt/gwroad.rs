@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, ResourceObjectZ, Vec2f};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, ResourceObjectZ, Vec2f};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -100,4 +100,4 @@ impl HasReferences for GwRoadZ {
     }
 }
 
-pub type GwRoadObjectFormat = FUELObjectFormat<ResourceObjectZ, GwRoadZ>;
+pub type GwRoadObjectFormat = ObjectFormat<ResourceObjectZ, GwRoadZ>;
@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, ResourceObjectZ, Vec3f, Vec3i32};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, ResourceObjectZ, Vec3f, Vec3i32};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -27,4 +27,4 @@ impl HasReferences for LightDataZ {
     }
 }
 
-pub type LightDataObjectFormat = FUELObjectFormat<ResourceObjectZ, LightDataZ>;
+pub type LightDataObjectFormat = ObjectFormat<ResourceObjectZ, LightDataZ>;
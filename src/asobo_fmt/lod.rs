@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    write_option, DynBox, DynSphere, FUELObjectFormat, HasReferences, ObjectZ, PascalArray,
+use crate::asobo_fmt::common::{
+    write_option, DynBox, DynSphere, HasReferences, ObjectFormat, ObjectZ, PascalArray,
 };
 
 #[derive(BinWrite)]
@@ -174,7 +174,12 @@ impl HasReferences for LodZAlt {
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        vec![]
+        let mut v = Vec::new();
+        v.append(&mut self.skin_crc32s.data.clone());
+        if let Some(sound_entries) = &self.sound_entries {
+            v.append(&mut sound_entries.data.iter().map(|x| x.sound_crc32).collect());
+        }
+        v
     }
 }
 
@@ -184,10 +189,15 @@ impl HasReferences for LodZAltAlt {
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        vec![]
+        let mut v = Vec::new();
+        v.append(&mut self.skin_crc32s.data.clone());
+        if let Some(sound_entries) = &self.sound_entries {
+            v.append(&mut sound_entries.data.iter().map(|x| x.sound_crc32).collect());
+        }
+        v
     }
 }
 
-pub type LodObjectFormat = FUELObjectFormat<ObjectZ, LodZ>;
-pub type LodObjectFormatAlt = FUELObjectFormat<ObjectZ, LodZAlt>;
-pub type LodObjectFormatAltAlt = FUELObjectFormat<ObjectZ, LodZAltAlt>;
+pub type LodObjectFormat = ObjectFormat<ObjectZ, LodZ>;
+pub type LodObjectFormatAlt = ObjectFormat<ObjectZ, LodZAlt>;
+pub type LodObjectFormatAltAlt = ObjectFormat<ObjectZ, LodZAltAlt>;
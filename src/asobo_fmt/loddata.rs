@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    write_option, FUELObjectFormat, FixedVec, HasReferences, PascalArray, ResourceObjectZ,
+use crate::asobo_fmt::common::{
+    write_option, FixedVec, HasReferences, ObjectFormat, PascalArray, ResourceObjectZ,
 };
 
 #[derive(BinWrite)]
@@ -103,8 +103,25 @@ impl HasReferences for LodDataZ {
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        vec![]
+        self.crc32s.data.clone()
     }
 }
 
-pub type LodDataObjectFormat = FUELObjectFormat<ResourceObjectZ, LodDataZ>;
+pub type LodDataObjectFormat = ObjectFormat<ResourceObjectZ, LodDataZ>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lod_data_links() {
+        let mut data: Vec<u8> = vec![0u8; 4];
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&[0u8; 4 + 1]);
+        let (_, lod_data) = LodDataZ::parse(&data[..]).unwrap();
+        assert_eq!(lod_data.hard_links(), Vec::<u32>::new());
+        assert_eq!(lod_data.soft_links(), vec![1, 2]);
+    }
+}
@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, PascalArray, ResourceObjectZ};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, PascalArray, ResourceObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -89,7 +89,11 @@ pub struct MaterialAnimZ {
 
 impl HasReferences for MaterialAnimZ {
     fn hard_links(&self) -> Vec<u32> {
-        vec![]
+        let mut v = Vec::new();
+        if self.material_crc32 != 0 {
+            v.push(self.material_crc32)
+        }
+        v
     }
 
     fn soft_links(&self) -> Vec<u32> {
@@ -97,4 +101,20 @@ impl HasReferences for MaterialAnimZ {
     }
 }
 
-pub type MaterialAnimObjectFormat = FUELObjectFormat<ResourceObjectZ, MaterialAnimZ>;
+pub type MaterialAnimObjectFormat = ObjectFormat<ResourceObjectZ, MaterialAnimZ>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_material_anim_links() {
+        // 11 empty arrays and 8 flags
+        let mut data: Vec<u8> = vec![0u8; 11 * 4 + 8 * 2];
+        data.extend_from_slice(&3517318496u32.to_le_bytes());
+        data.extend_from_slice(&[0u8; 4 + 1]);
+        let (_, material_anim) = MaterialAnimZ::parse(&data[..]).unwrap();
+        assert_eq!(material_anim.hard_links(), vec![3517318496]);
+        assert_eq!(material_anim.soft_links(), Vec::<u32>::new());
+    }
+}
@@ -0,0 +1,58 @@
+use binwrite::BinWrite;
+use nom_derive::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, PascalArray, ResourceObjectZ};
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct MaterialObjZEntry {
+    array_name_crc32: u32,
+    material_anim_crc32s: PascalArray<u32>,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+#[nom(Exact)]
+pub struct MaterialObjZ {
+    entries: PascalArray<MaterialObjZEntry>,
+}
+
+impl HasReferences for MaterialObjZ {
+    fn hard_links(&self) -> Vec<u32> {
+        vec![]
+    }
+
+    fn soft_links(&self) -> Vec<u32> {
+        let mut v = Vec::new();
+        for entry in self.entries.data.iter() {
+            v.append(&mut entry.material_anim_crc32s.data.clone());
+        }
+        v
+    }
+}
+
+pub type MaterialObjObjectFormat = ObjectFormat<ResourceObjectZ, MaterialObjZ>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_material_obj_links() {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for crc32s in [&[1u32, 2][..], &[3u32][..]] {
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data.extend_from_slice(&(crc32s.len() as u32).to_le_bytes());
+            for crc32 in crc32s {
+                data.extend_from_slice(&crc32.to_le_bytes());
+            }
+        }
+        let (_, material_obj) = MaterialObjZ::parse(&data[..]).unwrap();
+        assert_eq!(material_obj.hard_links(), Vec::<u32>::new());
+        assert_eq!(material_obj.soft_links(), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::asobo_fmt::animation::AnimationObjectFormat;
+use crate::asobo_fmt::binary::BinaryObjectFormat;
+use crate::asobo_fmt::camera::CameraObjectFormat;
+use crate::asobo_fmt::collisionvol::CollisionVolObjectType;
+use crate::asobo_fmt::common::ObjectFormatTrait;
+use crate::asobo_fmt::fonts::FontsObjectFormat;
+use crate::asobo_fmt::genworld::GenWorldObjectFormat;
+use crate::asobo_fmt::gwroad::GwRoadObjectFormat;
+use crate::asobo_fmt::lightdata::LightDataObjectFormat;
+use crate::asobo_fmt::loddata::LodDataObjectFormat;
+use crate::asobo_fmt::materialanim::MaterialAnimObjectFormat;
+use crate::asobo_fmt::materialobj::MaterialObjObjectFormat;
+use crate::asobo_fmt::omni::OmniObjectFormat;
+use crate::asobo_fmt::particlesdata::ParticlesDataObjectFormat;
+use crate::asobo_fmt::rotshape::RotShapeObjectFormat;
+use crate::asobo_fmt::rotshapedata::RotShapeDataObjectFormat;
+use crate::asobo_fmt::rtc::RtcObjectFormat;
+use crate::asobo_fmt::spline::SplineObjectFormat;
+use crate::asobo_fmt::splinegraph::SplineGraphObjectFormat;
+use crate::asobo_fmt::surface::SurfaceObjectFormat;
+use crate::asobo_fmt::surfacedatas::SurfaceDatasObjectFormat;
+use crate::asobo_fmt::userdefine::UserDefineObjectFormat;
+use crate::asobo_fmt::warp::WarpObjectFormat;
+use crate::asobo_fmt::world::WorldObjectFormat;
+use crate::asobo_fmt::worldref::WorldRefObjectFormat;
+
+pub(crate) mod animation;
+pub(crate) mod binary;
+pub(crate) mod camera;
+pub(crate) mod collisionvol;
+pub(crate) mod common;
+pub(crate) mod fonts;
+pub(crate) mod genworld;
+pub(crate) mod gwroad;
+pub(crate) mod lightdata;
+pub(crate) mod lod;
+pub(crate) mod loddata;
+pub(crate) mod materialanim;
+pub(crate) mod materialobj;
+pub(crate) mod omni;
+pub(crate) mod particles;
+pub(crate) mod particlesdata;
+pub(crate) mod rotshape;
+pub(crate) mod rotshapedata;
+pub(crate) mod rtc;
+pub(crate) mod sound;
+pub(crate) mod spline;
+pub(crate) mod splinegraph;
+pub(crate) mod surface;
+pub(crate) mod surfacedatas;
+pub(crate) mod userdefine;
+pub(crate) mod warp;
+pub(crate) mod world;
+pub(crate) mod worldref;
+
+// Formats every game and version lays out the same way. The per-game and per-version ones are
+// added on top by the game profile
+pub fn get_formats<'a>() -> HashMap<u32, &'a dyn ObjectFormatTrait> {
+    let mut formats: HashMap<u32, &'a dyn ObjectFormatTrait> = HashMap::new();
+
+    formats.insert(1175485833, AnimationObjectFormat::new());
+    formats.insert(4240844041, CameraObjectFormat::new());
+    formats.insert(2398393906, CollisionVolObjectType::new());
+    formats.insert(1536002910, FontsObjectFormat::new());
+    formats.insert(838505646, GenWorldObjectFormat::new());
+    formats.insert(3845834591, GwRoadObjectFormat::new());
+    formats.insert(848525546, LightDataObjectFormat::new());
+    formats.insert(3412401859, LodDataObjectFormat::new());
+    formats.insert(3834418854, MaterialAnimObjectFormat::new());
+    formats.insert(849861735, MaterialObjObjectFormat::new());
+    formats.insert(549480509, OmniObjectFormat::new());
+    formats.insert(954499543, ParticlesDataObjectFormat::new());
+    formats.insert(866453734, RotShapeObjectFormat::new());
+    formats.insert(1625945536, RotShapeDataObjectFormat::new());
+    formats.insert(705810152, RtcObjectFormat::new());
+    formats.insert(1135194223, SplineObjectFormat::new());
+    formats.insert(1910554652, SplineGraphObjectFormat::new());
+    formats.insert(1706265229, SurfaceObjectFormat::new());
+    formats.insert(3747817665, SurfaceDatasObjectFormat::new());
+    formats.insert(1391959958, UserDefineObjectFormat::new());
+    formats.insert(1114947943, WarpObjectFormat::new());
+    formats.insert(968261323, WorldObjectFormat::new());
+    formats.insert(2906362741, WorldRefObjectFormat::new());
+    formats.insert(2259852416, BinaryObjectFormat::new());
+
+    formats
+}
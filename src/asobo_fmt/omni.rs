@@ -0,0 +1,46 @@
+use binwrite::BinWrite;
+use nom_derive::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asobo_fmt::common::{FixedVec, HasReferences, ObjectFormat, ObjectZ};
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+#[nom(Exact)]
+pub struct OmniZ {
+    data: FixedVec<u32, 48>,
+    crc32s: FixedVec<u32, 2>,
+}
+
+impl HasReferences for OmniZ {
+    fn hard_links(&self) -> Vec<u32> {
+        vec![]
+    }
+
+    fn soft_links(&self) -> Vec<u32> {
+        self.crc32s
+            .data
+            .iter()
+            .copied()
+            .filter(|x| *x != 0)
+            .collect()
+    }
+}
+
+pub type OmniObjectFormat = ObjectFormat<ObjectZ, OmniZ>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_omni_links() {
+        let mut data: Vec<u8> = vec![0u8; 48 * 4];
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&848525546u32.to_le_bytes());
+        let (_, omni) = OmniZ::parse(&data[..]).unwrap();
+        assert_eq!(omni.hard_links(), Vec::<u32>::new());
+        assert_eq!(omni.soft_links(), vec![848525546]);
+    }
+}
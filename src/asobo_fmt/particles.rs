@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    FUELObjectFormat, FixedVec, HasReferences, Mat4f, ObjectZ, PascalArray,
+use crate::asobo_fmt::common::{
+    FixedVec, HasReferences, Mat4f, ObjectFormat, ObjectZ, PascalArray,
 };
 
 #[derive(BinWrite)]
@@ -130,5 +130,5 @@ impl HasReferences for ParticlesZAlt {
     }
 }
 
-pub type ParticlesObjectFormat = FUELObjectFormat<ObjectZ, ParticlesZ>;
-pub type ParticlesObjectFormatAlt = FUELObjectFormat<ObjectZ, ParticlesZAlt>;
+pub type ParticlesObjectFormat = ObjectFormat<ObjectZ, ParticlesZ>;
+pub type ParticlesObjectFormatAlt = ObjectFormat<ObjectZ, ParticlesZAlt>;
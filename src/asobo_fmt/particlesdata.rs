@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, PascalArray, ResourceObjectZ};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, PascalArray, ResourceObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -30,4 +30,4 @@ impl HasReferences for ParticlesDataZ {
     }
 }
 
-pub type ParticlesDataObjectFormat = FUELObjectFormat<ResourceObjectZ, ParticlesDataZ>;
+pub type ParticlesDataObjectFormat = ObjectFormat<ResourceObjectZ, ParticlesDataZ>;
@@ -0,0 +1,55 @@
+use binwrite::BinWrite;
+use nom_derive::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, ObjectZ, PascalArray, Vec2f, Vec3f};
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+#[nom(Exact)]
+pub struct RotShapeZ {
+    vertices: PascalArray<Vec3f>,
+    unknown1: f32,
+    ints: PascalArray<u32>,
+    sizes: PascalArray<Vec3f>,
+    texcoords: PascalArray<Vec2f>,
+    material_crc32s: PascalArray<u32>,
+    scale: f32,
+    billboard_mode: u16,
+}
+
+impl HasReferences for RotShapeZ {
+    fn hard_links(&self) -> Vec<u32> {
+        self.material_crc32s.data.clone()
+    }
+
+    fn soft_links(&self) -> Vec<u32> {
+        vec![]
+    }
+}
+
+pub type RotShapeObjectFormat = ObjectFormat<ObjectZ, RotShapeZ>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rot_shape_links() {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&1f32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&2204276779u32.to_le_bytes());
+        data.extend_from_slice(&849861735u32.to_le_bytes());
+        data.extend_from_slice(&1f32.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        let (_, rot_shape) = RotShapeZ::parse(&data[..]).unwrap();
+        assert_eq!(rot_shape.hard_links(), vec![2204276779, 849861735]);
+        assert_eq!(rot_shape.soft_links(), Vec::<u32>::new());
+    }
+}
@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, PascalArray, ResourceObjectZ};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, PascalArray, ResourceObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -25,4 +25,4 @@ impl HasReferences for RotShapeDataZ {
     }
 }
 
-pub type RotShapeDataObjectFormat = FUELObjectFormat<ResourceObjectZ, RotShapeDataZ>;
+pub type RotShapeDataObjectFormat = ObjectFormat<ResourceObjectZ, RotShapeDataZ>;
@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::walle_fmt::common::{
-    FixedVec, HasReferences, PascalArray, ResourceObjectZ, WALLEObjectFormat,
+use crate::asobo_fmt::common::{
+    FixedVec, HasReferences, ObjectFormat, PascalArray, ResourceObjectZ,
 };
 
 #[derive(BinWrite)]
@@ -203,8 +203,34 @@ impl HasReferences for RtcZ {
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        vec![]
+        self.unknown1s
+            .data
+            .iter()
+            .map(|x| x.unknown_node_crc32)
+            .filter(|x| *x != 0)
+            .collect()
     }
 }
 
-pub type RtcObjectFormat = WALLEObjectFormat<ResourceObjectZ, RtcZ>;
+pub type RtcObjectFormat = ObjectFormat<ResourceObjectZ, RtcZ>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rtc_links() {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&0f32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for crc32 in [848525546u32, 0] {
+            data.extend_from_slice(&crc32.to_le_bytes());
+            // unknown1, then 4 empty arrays and 2 flags
+            data.extend_from_slice(&[0u8; 2 + 4 + 2 + 4 + 2 + 4 + 4]);
+        }
+        data.extend_from_slice(&[0u8; 8 * 4]);
+        let (_, rtc) = RtcZ::parse(&data[..]).unwrap();
+        assert_eq!(rtc.hard_links(), Vec::<u32>::new());
+        assert_eq!(rtc.soft_links(), vec![848525546]);
+    }
+}
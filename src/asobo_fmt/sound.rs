@@ -1,14 +1,15 @@
 use std::fs::File;
-use std::io::{Cursor, ErrorKind};
-use std::io::{Error, Write};
+use std::io::{Cursor, Write};
 use std::path::Path;
 
 use binwrite::BinWrite;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use crate::walle_fmt::common::{write_option, HasReferences, WALLEObjectFormatTrait};
+use crate::asobo_fmt::common::{write_option, HasReferences, ObjectFormatTrait};
+use crate::error::DpcError;
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -42,26 +43,27 @@ impl HasReferences for SoundZHeader {
     }
 }
 
-pub struct SoundObjectFormat;
+// DEFAULT_SAMPLE_RATE is used for sounds whose header has no sample rate
+pub struct SoundObjectFormat<const DEFAULT_SAMPLE_RATE: u32>;
 
-impl SoundObjectFormat {
+impl<const DEFAULT_SAMPLE_RATE: u32> SoundObjectFormat<DEFAULT_SAMPLE_RATE> {
     pub fn new<'a>() -> &'a Self {
         &Self {}
     }
 }
 
-impl WALLEObjectFormatTrait for SoundObjectFormat {
+impl<const DEFAULT_SAMPLE_RATE: u32> ObjectFormatTrait for SoundObjectFormat<DEFAULT_SAMPLE_RATE> {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
         let wav_path = input_path.join("data.wav");
-        let mut reader = hound::WavReader::open(wav_path).unwrap();
+        let mut reader = hound::WavReader::open(wav_path)?;
 
         #[derive(Deserialize)]
         struct Object {
@@ -73,7 +75,7 @@ impl WALLEObjectFormatTrait for SoundObjectFormat {
         object.sound_header.write(header)?;
 
         for sample in reader.samples::<i16>() {
-            body.write_i16::<LittleEndian>(sample.unwrap())?;
+            body.write_i16::<LittleEndian>(sample?)?;
         }
 
         Ok((
@@ -83,11 +85,11 @@ impl WALLEObjectFormatTrait for SoundObjectFormat {
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
@@ -95,7 +97,7 @@ impl WALLEObjectFormatTrait for SoundObjectFormat {
 
         let sound_header = match SoundZHeader::parse(&header) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
         };
 
         let spec = hound::WavSpec {
@@ -103,7 +105,7 @@ impl WALLEObjectFormatTrait for SoundObjectFormat {
             sample_rate: if sound_header.sample_rate != 0 {
                 sound_header.sample_rate
             } else {
-                22050
+                DEFAULT_SAMPLE_RATE
             },
             bits_per_sample: 16,
             sample_format: hound::SampleFormat::Int,
@@ -111,7 +113,7 @@ impl WALLEObjectFormatTrait for SoundObjectFormat {
 
         let number_of_samples = body.len() as u32 / (spec.bits_per_sample / 8) as u32;
 
-        let mut parent_writer = hound::WavWriter::create(wav_path, spec).unwrap();
+        let mut parent_writer = hound::WavWriter::create(wav_path, spec)?;
         let mut writer = parent_writer.get_i16_writer(number_of_samples);
 
         let mut data_cursor = Cursor::new(&body);
@@ -119,8 +121,8 @@ impl WALLEObjectFormatTrait for SoundObjectFormat {
         for _ in 0..number_of_samples {
             writer.write_sample(data_cursor.read_i16::<LittleEndian>()?);
         }
-        writer.flush().unwrap();
-        parent_writer.finalize().unwrap();
+        writer.flush()?;
+        parent_writer.finalize()?;
 
         #[derive(Serialize)]
         struct Object {
@@ -136,4 +138,13 @@ impl WALLEObjectFormatTrait for SoundObjectFormat {
             object.sound_header.soft_links(),
         ))
     }
+
+    fn to_json(&self, header: &[u8], _body: &[u8]) -> Result<Value, DpcError> {
+        let sound_header = match SoundZHeader::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        Ok(json!({ "sound_header": serde_json::to_value(sound_header)? }))
+    }
 }
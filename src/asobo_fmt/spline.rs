@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    FUELObjectFormat, FixedVec, HasReferences, ObjectZ, PascalArray, Vec3f,
+use crate::asobo_fmt::common::{
+    FixedVec, HasReferences, ObjectFormat, ObjectZ, PascalArray, Vec3f,
 };
 
 #[derive(BinWrite)]
@@ -52,4 +52,4 @@ impl HasReferences for SplineZ {
     }
 }
 
-pub type SplineObjectFormat = FUELObjectFormat<ObjectZ, SplineZ>;
+pub type SplineObjectFormat = ObjectFormat<ObjectZ, SplineZ>;
@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    FUELObjectFormat, FixedVec, HasReferences, ObjectZ, PascalArray, Vec3f,
+use crate::asobo_fmt::common::{
+    FixedVec, HasReferences, ObjectFormat, ObjectZ, PascalArray, Vec3f,
 };
 
 #[derive(BinWrite)]
@@ -47,4 +47,4 @@ impl HasReferences for SplineGraphZ {
     }
 }
 
-pub type SplineGraphObjectFormat = FUELObjectFormat<ObjectZ, SplineGraphZ>;
+pub type SplineGraphObjectFormat = ObjectFormat<ObjectZ, SplineGraphZ>;
@@ -2,9 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    write_option, FUELObjectFormat, FixedVec, HasReferences, ObjectZ, PascalArray, Quat, Vec2f,
-    Vec3f,
+use crate::asobo_fmt::common::{
+    write_option, FixedVec, HasReferences, ObjectFormat, ObjectZ, PascalArray, Quat, Vec2f, Vec3f,
 };
 
 #[derive(BinWrite)]
@@ -97,4 +96,4 @@ impl HasReferences for SurfaceZ {
     }
 }
 
-pub type SurfaceObjectFormat = FUELObjectFormat<ObjectZ, SurfaceZ>;
+pub type SurfaceObjectFormat = ObjectFormat<ObjectZ, SurfaceZ>;
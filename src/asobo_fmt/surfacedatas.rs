@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, ResourceObjectZ};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, ResourceObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -22,4 +22,4 @@ impl HasReferences for SurfaceDatasZ {
     }
 }
 
-pub type SurfaceDatasObjectFormat = FUELObjectFormat<ResourceObjectZ, SurfaceDatasZ>;
+pub type SurfaceDatasObjectFormat = ObjectFormat<ResourceObjectZ, SurfaceDatasZ>;
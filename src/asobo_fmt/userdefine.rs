@@ -1,14 +1,14 @@
 use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use crate::walle_fmt::common::{
-    HasReferences, PascalString, ResourceObjectZ, WALLEObjectFormatTrait,
-};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormatTrait, PascalString, ResourceObjectZ};
+use crate::error::DpcError;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::fs;
 use std::fs::File;
-use std::io::{Cursor, Error, ErrorKind, Read, Write};
+use std::io::{Cursor, Read, Write};
 use std::path::Path;
 use zerocopy::AsBytes;
 
@@ -28,13 +28,13 @@ impl UserDefineObjectFormat {
     }
 }
 
-impl WALLEObjectFormatTrait for UserDefineObjectFormat {
+impl ObjectFormatTrait for UserDefineObjectFormat {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
@@ -52,7 +52,7 @@ impl WALLEObjectFormatTrait for UserDefineObjectFormat {
         let metadata = fs::metadata(&txt_path)?;
         let mut body_cursor = Cursor::new(body);
         body_cursor.write_u32::<LittleEndian>(metadata.len() as u32)?;
-        body_cursor.write(fs::read(txt_path).unwrap().as_bytes())?;
+        body_cursor.write_all(&fs::read(txt_path)?)?;
 
         Ok((
             object.resource_object.hard_links(),
@@ -61,11 +61,11 @@ impl WALLEObjectFormatTrait for UserDefineObjectFormat {
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
@@ -74,7 +74,7 @@ impl WALLEObjectFormatTrait for UserDefineObjectFormat {
 
         let resource_object = match ResourceObjectZ::parse(&header) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
         };
 
         #[derive(Serialize)]
@@ -98,4 +98,13 @@ impl WALLEObjectFormatTrait for UserDefineObjectFormat {
             object.resource_object.soft_links(),
         ))
     }
+
+    fn to_json(&self, header: &[u8], _body: &[u8]) -> Result<Value, DpcError> {
+        let resource_object = match ResourceObjectZ::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        Ok(json!({ "resource_object": serde_json::to_value(resource_object)? }))
+    }
 }
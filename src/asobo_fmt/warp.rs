@@ -0,0 +1,76 @@
+use binwrite::BinWrite;
+use nom_derive::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, ResourceObjectZ, Vec2f, Vec3f};
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+#[nom(Exact)]
+pub struct WarpZ {
+    material_crc32: u32,
+    #[nom(Count(8))]
+    vertices: Vec<Vec3f>,
+    u24: f32,
+    u25: f32,
+    radius: f32,
+    #[nom(Count(4))]
+    texcoords: Vec<Vec2f>,
+}
+
+impl HasReferences for WarpZ {
+    fn hard_links(&self) -> Vec<u32> {
+        let mut v = Vec::new();
+        if self.material_crc32 != 0 {
+            v.push(self.material_crc32)
+        }
+        v
+    }
+
+    fn soft_links(&self) -> Vec<u32> {
+        vec![]
+    }
+}
+
+pub type WarpObjectFormat = ObjectFormat<ResourceObjectZ, WarpZ>;
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use serde_json::Value;
+    use tempdir::TempDir;
+
+    use super::*;
+    use crate::asobo_fmt::common::ObjectFormatTrait;
+
+    fn warp_data() -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&1471281566u32.to_le_bytes());
+        data.extend_from_slice(&[0u8; 8 * 12 + 12 + 4 * 8]);
+        data
+    }
+
+    #[test]
+    fn test_warp_links() {
+        let data = warp_data();
+        let (_, warp) = WarpZ::parse(&data[..]).unwrap();
+        assert_eq!(warp.hard_links(), vec![1471281566]);
+        assert_eq!(warp.soft_links(), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_warp_json() {
+        let header = 1u32.to_le_bytes();
+        let data = warp_data();
+        let dir = TempDir::new("dpc-warp").unwrap();
+        let format = WarpObjectFormat::new();
+        format.unpack(&header, &data, dir.path()).unwrap();
+
+        let written: Value =
+            serde_json::from_slice(&fs::read(dir.path().join("object.json")).unwrap()).unwrap();
+        assert_eq!(format.to_json(&header, &data).unwrap(), written);
+        assert!(format.to_json(&header, &data[..4]).is_err());
+    }
+}
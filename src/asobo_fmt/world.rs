@@ -0,0 +1,115 @@
+use binwrite::BinWrite;
+use nom_derive::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asobo_fmt::common::{HasReferences, Mat4f, ObjectFormat, PascalArray, ResourceObjectZ};
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+struct WorldZUnknown2 {
+    placeholder0: u32,
+    placeholder1: u32,
+    index: u32,
+    placeholder2: u32,
+    unknown4: u32,
+    zero: u32,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+#[derive(Serialize, Deserialize, NomLE)]
+#[nom(Exact)]
+pub struct WorldZ {
+    node_crc32: u32,
+    warp_crc32: u32,
+    game_obj_crc32: u32,
+    unused14: u32,
+    gen_world_crc32: u32,
+    node_crc321: u32,
+    unused17s: PascalArray<u32>,
+    unuseds: PascalArray<u8>,
+    unknown0: Mat4f,
+    indices0: PascalArray<u32>,
+    unknown2s: PascalArray<WorldZUnknown2>,
+    unknown3: Mat4f,
+    indices1: PascalArray<u32>,
+    unknown5s: PascalArray<WorldZUnknown2>,
+    unused6s: PascalArray<u32>,
+    unused7s: PascalArray<u32>,
+    unused8s: PascalArray<u32>,
+    unused9s: PascalArray<u32>,
+    unused10s: PascalArray<u32>,
+    spline_graph_crc32: PascalArray<u32>,
+    unused12s: PascalArray<u32>,
+    material_anim_crc32: PascalArray<u32>,
+}
+
+impl HasReferences for WorldZ {
+    fn hard_links(&self) -> Vec<u32> {
+        vec![]
+    }
+
+    fn soft_links(&self) -> Vec<u32> {
+        let mut v = Vec::new();
+        if self.node_crc32 != 0 {
+            v.push(self.node_crc32)
+        }
+        if self.warp_crc32 != 0 {
+            v.push(self.warp_crc32)
+        }
+        if self.game_obj_crc32 != 0 {
+            v.push(self.game_obj_crc32)
+        }
+        if self.unused14 != 0 {
+            v.push(self.unused14)
+        }
+        if self.gen_world_crc32 != 0 {
+            v.push(self.gen_world_crc32)
+        }
+        if self.node_crc321 != 0 {
+            v.push(self.node_crc321)
+        }
+        v.append(&mut self.spline_graph_crc32.data.clone());
+        v.append(&mut self.material_anim_crc32.data.clone());
+        v
+    }
+}
+
+pub type WorldObjectFormat = ObjectFormat<ResourceObjectZ, WorldZ>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_world_links() {
+        let world: WorldZ = serde_json::from_value(serde_json::json!({
+            "node_crc32": 1,
+            "warp_crc32": 2,
+            "game_obj_crc32": 3,
+            "unused14": 0,
+            "gen_world_crc32": 4,
+            "node_crc321": 5,
+            "unused17s": [],
+            "unuseds": [],
+            "unknown0": vec![0f32; 16],
+            "indices0": [],
+            "unknown2s": [],
+            "unknown3": vec![0f32; 16],
+            "indices1": [],
+            "unknown5s": [],
+            "unused6s": [],
+            "unused7s": [],
+            "unused8s": [],
+            "unused9s": [],
+            "unused10s": [],
+            "spline_graph_crc32": [6],
+            "unused12s": [],
+            "material_anim_crc32": [7, 8],
+        }))
+        .unwrap();
+        assert_eq!(world.hard_links(), Vec::<u32>::new());
+        assert_eq!(world.soft_links(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    FUELObjectFormat, HasReferences, Mat4f, ObjectZ, PascalArray, PascalStringNULL, Vec3f,
+use crate::asobo_fmt::common::{
+    HasReferences, Mat4f, ObjectFormat, ObjectZ, PascalArray, PascalStringNULL, Vec3f,
 };
 
 #[derive(BinWrite)]
@@ -66,4 +66,4 @@ impl HasReferences for WorldRefZ {
     }
 }
 
-pub type WorldRefObjectFormat = FUELObjectFormat<ObjectZ, WorldRefZ>;
+pub type WorldRefObjectFormat = ObjectFormat<ObjectZ, WorldRefZ>;
@@ -1,38 +1,204 @@
-use std::convert::From;
+use std::convert::TryFrom;
 use std::ffi::OsStr;
-use std::io::Result;
-use std::path::Path;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
 
 use clap::ArgMatches;
+use serde_json::Value;
+
+use crate::error::{DpcError, Result};
+use crate::lz::CompressionLevel;
+use crate::names::NameTable;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub enum OverwritePolicy {
+    #[default]
+    Error,
+    Skip,
+    Overwrite,
+    // The library never reads stdin, the caller asks whether to overwrite the existing output
+    // given its path and a description of it
+    Prompt(fn(&Path, &str) -> Result<bool>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputStatus {
+    Written,
+    Skipped(PathBuf),
+}
 
 #[derive(Clone, Copy)]
 pub struct Options {
     pub is_quiet: bool,
-    pub is_force: bool,
+    pub overwrite_policy: OverwritePolicy,
     pub is_unsafe: bool,
     pub is_lz: bool,
     pub is_optimization: bool,
+    pub compression_level: CompressionLevel,
     pub is_recursive: bool,
+    // 0 uses every available core
+    pub threads: usize,
 }
 
-impl From<&ArgMatches<'_>> for Options {
-    fn from(arg_matches: &ArgMatches) -> Self {
-        Options {
+impl Options {
+    pub fn thread_count(&self) -> usize {
+        match self.threads {
+            0 => thread::available_parallelism().map_or(1, |x| x.get()),
+            threads => threads,
+        }
+    }
+}
+
+impl TryFrom<&ArgMatches<'_>> for Options {
+    type Error = DpcError;
+
+    fn try_from(arg_matches: &ArgMatches) -> Result<Self> {
+        let compression_level = match arg_matches.value_of("COMPRESSION_LEVEL") {
+            Some(name) => CompressionLevel::from_name(name).ok_or_else(|| {
+                DpcError::Format(format!(
+                    "unknown compression level {}, expected fast, balanced or max",
+                    name
+                ))
+            })?,
+            None => match arg_matches.is_present("OPTIMIZATION") {
+                true => CompressionLevel::Max,
+                false => CompressionLevel::default(),
+            },
+        };
+
+        let threads = match arg_matches.value_of("THREADS") {
+            Some(threads) => threads.parse().map_err(|_| {
+                DpcError::Format(format!(
+                    "invalid thread count {}, expected a number or 0 for every core",
+                    threads
+                ))
+            })?,
+            None => 0,
+        };
+
+        Ok(Options {
             is_quiet: arg_matches.is_present("QUIET"),
-            is_force: arg_matches.is_present("FORCE"),
+            overwrite_policy: match arg_matches.is_present("FORCE") {
+                true => OverwritePolicy::Overwrite,
+                false => OverwritePolicy::Error,
+            },
             is_unsafe: arg_matches.is_present("UNSAFE"),
             is_lz: arg_matches.is_present("LZ"),
             is_optimization: arg_matches.is_present("OPTIMIZATION"),
+            compression_level,
             is_recursive: arg_matches.is_present("RECURSIVE"),
+            threads,
+        })
+    }
+}
+
+pub fn should_write<P: AsRef<Path>>(
+    overwrite_policy: OverwritePolicy,
+    output_path: &P,
+    description: &str,
+) -> Result<bool> {
+    if !output_path.as_ref().exists() {
+        return Ok(true);
+    }
+
+    match overwrite_policy {
+        OverwritePolicy::Overwrite => Ok(true),
+        OverwritePolicy::Skip => Ok(false),
+        OverwritePolicy::Error => Err(DpcError::OutputExists(output_path.as_ref().to_path_buf())),
+        OverwritePolicy::Prompt(prompt) => prompt(output_path.as_ref(), description),
+    }
+}
+
+// Writes to a file next to output_path and renames it over output_path only once write succeeds,
+// so a failure never leaves a partial file where the previous output was
+pub fn write_output<P: AsRef<Path>, T>(
+    output_path: &P,
+    write: impl FnOnce(&mut File) -> Result<T>,
+) -> Result<T> {
+    let output_path = output_path.as_ref();
+    let mut temp_name = output_path
+        .file_name()
+        .map(OsStr::to_os_string)
+        .unwrap_or_default();
+    temp_name.push(".tmp");
+    let temp_path = output_path.with_file_name(temp_name);
+
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&temp_path)?;
+    let result = write(&mut file);
+    drop(file);
+
+    match result {
+        Ok(x) => {
+            fs::rename(&temp_path, output_path)?;
+            Ok(x)
+        }
+        Err(error) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(error)
         }
     }
 }
 
+#[derive(Clone, Copy, Default)]
+pub struct FormatOptions {
+    pub png_mips: bool,
+}
+
+pub trait ObjectFormatTrait {
+    fn pack(
+        &self,
+        input_path: &Path,
+        header: &mut Vec<u8>,
+        body: &mut Vec<u8>,
+    ) -> Result<(Vec<u32>, Vec<u32>)>;
+    fn unpack(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+    ) -> Result<(Vec<u32>, Vec<u32>)>;
+    // The object.json unpack would write, without touching the file system
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value>;
+    // Formats writing crc32s to files other than object.json, which is annotated afterwards,
+    // look their names up here
+    fn unpack_named(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+        _names: &NameTable,
+    ) -> Result<(Vec<u32>, Vec<u32>)> {
+        self.unpack(header, body, output_path)
+    }
+}
+
 pub trait DPC {
-    fn new(options: &Options, custom_args: &Vec<&OsStr>) -> Self;
-    fn extract<P: AsRef<Path>>(&mut self, input_path: &P, output_path: &P) -> Result<()>;
-    fn create<P: AsRef<Path>>(&mut self, input_path: &P, output_path: &P) -> Result<()>;
-    fn validate<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()>;
+    fn new(options: &Options, custom_args: &[&OsStr]) -> Result<Self>
+    where
+        Self: Sized;
+    fn extract<P: AsRef<Path>>(&mut self, input_path: &P, output_path: &P) -> Result<OutputStatus>;
+    fn create<P: AsRef<Path>>(&mut self, input_path: &P, output_path: &P) -> Result<OutputStatus>;
+    // objects_path is an object file, a `.d` directory or a directory holding several of them
+    fn patch<P: AsRef<Path>>(
+        &mut self,
+        input_path: &P,
+        objects_path: &P,
+        output_path: &P,
+    ) -> Result<OutputStatus>;
+    // Writes the objects added, removed, changed and moved between two BigFiles as json
+    fn diff<P: AsRef<Path>>(
+        &self,
+        old_path: &P,
+        new_path: &P,
+        output_path: &P,
+    ) -> Result<OutputStatus>;
+    fn validate<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<OutputStatus>;
     fn compress_object<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()>;
     fn decompress_object<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()>;
     fn split_object<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()>;
@@ -43,3 +209,116 @@ pub trait DPC {
     ) -> Result<(Vec<u32>, Vec<u32>)>;
     fn fmt_create<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()>;
 }
+
+#[cfg(test)]
+mod test {
+    use clap::{App, Arg};
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_should_write() {
+        let dir = TempDir::new("dpc-base").unwrap();
+        let missing = dir.path().join("missing.DPC");
+        for policy in [
+            OverwritePolicy::Error,
+            OverwritePolicy::Skip,
+            OverwritePolicy::Overwrite,
+        ] {
+            assert!(should_write(policy, &missing, "DPC").unwrap());
+        }
+
+        let existing = dir.path();
+        assert!(should_write(OverwritePolicy::Overwrite, &existing, "directory").unwrap());
+        assert!(!should_write(OverwritePolicy::Skip, &existing, "directory").unwrap());
+        assert!(matches!(
+            should_write(OverwritePolicy::Error, &existing, "directory"),
+            Err(DpcError::OutputExists(_))
+        ));
+
+        fn skip_directories(_: &Path, description: &str) -> Result<bool> {
+            Ok(description != "directory")
+        }
+        fn abort(_: &Path, _: &str) -> Result<bool> {
+            Err(DpcError::Aborted)
+        }
+        let prompt = OverwritePolicy::Prompt(skip_directories);
+        assert!(!should_write(prompt, &existing, "directory").unwrap());
+        assert!(should_write(prompt, &existing, "json").unwrap());
+        assert!(matches!(
+            should_write(OverwritePolicy::Prompt(abort), &existing, "json"),
+            Err(DpcError::Aborted)
+        ));
+    }
+
+    #[test]
+    fn test_write_output() {
+        use std::io::Write;
+
+        let dir = TempDir::new("dpc-base").unwrap();
+        let output = dir.path().join("output.DPC");
+        fs::write(&output, b"old").unwrap();
+
+        let result: Result<()> = write_output(&output, |file| {
+            file.write_all(b"partial")?;
+            Err(DpcError::Aborted)
+        });
+        assert!(matches!(result, Err(DpcError::Aborted)));
+        assert_eq!(fs::read(&output).unwrap(), b"old");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        write_output(&output, |file| Ok(file.write_all(b"new")?)).unwrap();
+        assert_eq!(fs::read(&output).unwrap(), b"new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_options_overwrite_policy() {
+        let app = App::new("dpc").arg(Arg::with_name("FORCE").short("f"));
+        let options = Options::try_from(&app.clone().get_matches_from(vec!["dpc"])).unwrap();
+        assert!(matches!(options.overwrite_policy, OverwritePolicy::Error));
+        let options = Options::try_from(&app.get_matches_from(vec!["dpc", "-f"])).unwrap();
+        assert!(matches!(
+            options.overwrite_policy,
+            OverwritePolicy::Overwrite
+        ));
+    }
+
+    #[test]
+    fn test_options_compression_level() {
+        let app = App::new("dpc")
+            .arg(Arg::with_name("OPTIMIZATION").short("O"))
+            .arg(
+                Arg::with_name("COMPRESSION_LEVEL")
+                    .long("level")
+                    .takes_value(true),
+            );
+        let options = |args: Vec<&str>| Options::try_from(&app.clone().get_matches_from(args));
+
+        let level = |args| options(args).unwrap().compression_level;
+        assert_eq!(level(vec!["dpc"]), CompressionLevel::Fast);
+        assert_eq!(level(vec!["dpc", "-O"]), CompressionLevel::Max);
+        assert_eq!(
+            level(vec!["dpc", "-O", "--level", "balanced"]),
+            CompressionLevel::Balanced
+        );
+        assert!(matches!(
+            options(vec!["dpc", "--level", "maximum"]),
+            Err(DpcError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn test_options_threads() {
+        let app = App::new("dpc").arg(Arg::with_name("THREADS").long("threads").takes_value(true));
+        let options = |args: Vec<&str>| Options::try_from(&app.clone().get_matches_from(args));
+
+        assert_eq!(options(vec!["dpc"]).unwrap().threads, 0);
+        assert_eq!(options(vec!["dpc", "--threads", "3"]).unwrap().threads, 3);
+        assert!(matches!(
+            options(vec!["dpc", "--threads", "foo"]),
+            Err(DpcError::Format(_))
+        ));
+    }
+}
@@ -0,0 +1,1640 @@
+use std::cmp::max;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::str::from_utf8;
+use std::time::{Duration, Instant};
+
+use ::nom::bytes::streaming::take;
+use ::nom::multi::length_count;
+use ::nom::number::complete::*;
+use binwrite::BinWrite;
+use itertools::Itertools;
+use nom::combinator::map_res;
+use nom::error::Error as NomError;
+use nom::IResult;
+use nom_derive::*;
+use serde::Serialize;
+
+use crate::error::{DpcError, Result};
+use crate::lz::{CompressionLevel, LzrsDecoder, LzrsEncoder};
+use crate::parallel::parallel_map;
+
+pub fn calculate_padded_size(unpadded_size: u32) -> u32 {
+    (unpadded_size + 0x7ff) & 0xfffff800
+}
+
+pub fn calculate_padding_size(unpadded_size: u32) -> u32 {
+    calculate_padded_size(unpadded_size) - unpadded_size
+}
+
+#[derive(Serialize, NomLE, BinWrite, Clone, Copy, Debug, PartialEq, Eq)]
+#[binwrite(little)]
+pub struct ObjectHeader {
+    pub data_size: u32,
+    #[nom(Verify = "*class_object_size <= data_size")]
+    pub class_object_size: u32,
+    pub decompressed_size: u32,
+    pub compressed_size: u32,
+    pub class_crc32: u32,
+    pub crc32: u32,
+}
+
+impl ObjectHeader {
+    // Size of the data following the class object
+    pub fn body_size(&self) -> Result<u32> {
+        self.data_size
+            .checked_sub(self.class_object_size)
+            .ok_or_else(|| {
+                DpcError::Format(format!(
+                    "object {} has a class object size of {} but a data size of {}",
+                    self.crc32, self.class_object_size, self.data_size
+                ))
+            })
+    }
+}
+
+#[derive(Serialize, NomLE, BinWrite, Clone, Copy, Debug, PartialEq, Eq)]
+#[binwrite(little)]
+pub struct PoolManifestHeader {
+    pub equals524288: u32,
+    pub equals2048: u32,
+    pub objects_crc32_count_sum: u32,
+}
+
+#[derive(Serialize, NomLE, BinWrite, Clone, Copy, Debug, PartialEq, Eq)]
+#[binwrite(little)]
+pub struct ReferenceRecord {
+    pub start_chunk_index: u32,
+    pub end_chunk_index: u32,
+    pub objects_crc32_starting_index: u32,
+    pub placeholder_dpc_index: u16,
+    pub objects_crc32_count: u16,
+    pub placeholder_times_referenced: u32,
+    pub placeholder_current_references_shared: u32,
+    pub placeholder_current_references_weak: u32,
+}
+
+#[derive(Serialize, NomLE, Clone, Debug, PartialEq, Eq)]
+pub struct PoolManifest {
+    #[nom(Parse = "PoolManifestHeader::parse")]
+    pub header: PoolManifestHeader,
+    #[nom(Parse = "{ |i| length_count(le_u32, le_u32)(i) }")]
+    pub objects_crc32s: Vec<u32>,
+    #[nom(Parse = "{ |i| length_count(le_u32, le_u32)(i) }")]
+    pub crc32s: Vec<u32>,
+    #[nom(Parse = "{ |i| length_count(le_u32, le_u32)(i) }")]
+    pub reference_counts: Vec<u32>,
+    #[nom(Parse = "{ |i| length_count(le_u32, le_u32)(i) }")]
+    pub object_padded_size: Vec<u32>,
+    #[nom(Parse = "{ |i| length_count(le_u32, le_u32)(i) }")]
+    pub reference_records_indices: Vec<u32>,
+    #[nom(Parse = "{ |i| length_count(le_u32, ReferenceRecord::parse)(i) }")]
+    pub reference_records: Vec<ReferenceRecord>,
+}
+
+#[derive(Serialize, NomLE, BinWrite, Clone, Copy, Debug, PartialEq, Eq)]
+#[binwrite(little)]
+pub struct BlockDescription {
+    pub block_type: u32,
+    pub object_count: u32,
+    pub padded_size: u32,
+    pub data_size: u32,
+    pub working_buffer_offset: u32,
+    pub crc32: u32,
+}
+
+// named_args!(take_c_string_as_str(size: usize)<&str>, do_parse!(
+//     s: take_str!(size) >>
+//     (s.trim_end_matches('\0'))
+// ));
+fn take_c_string_as_str(i: &[u8], size: usize) -> IResult<&[u8], &str, nom::error::Error<&[u8]>> {
+    let map = map_res(take::<usize, &[u8], NomError<&[u8]>>(size), from_utf8)(i);
+    match map {
+        Ok(v) => {
+            let string = v.1.trim_end_matches('\0');
+            Ok((v.0, string))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// named!(take_nothing_as_str<&str>, do_parse!(("")));
+fn take_nothing_as_str(i: &[u8]) -> IResult<&[u8], &str, nom::error::Error<&[u8]>> {
+    // let l = (i, "");
+    Ok((i, ""))
+}
+
+#[derive(Serialize, NomLE, Clone, Debug, PartialEq, Eq)]
+pub struct PrimaryHeader {
+    #[nom(
+        Map = "|x: &str| x.to_string()",
+        Parse = "{ |i| take_c_string_as_str(i, 256) }"
+    )]
+    pub version_string: String,
+    pub is_not_rtc: u32,
+    #[nom(Verify = "*block_count <= 64")]
+    pub block_count: u32,
+    pub block_working_buffer_capacity_even: u32,
+    pub block_working_buffer_capacity_odd: u32,
+    pub padded_size: u32,
+    pub version_patch: u32,
+    pub version_minor: u32,
+    #[nom(Count = "block_count", Parse = "BlockDescription::parse")]
+    pub block_descriptions: Vec<BlockDescription>,
+    #[nom(MoveAbs(0x720))]
+    #[nom(Map = "|x| x * 2048")]
+    pub pool_manifest_padded_size: u32,
+    #[nom(Map = "|x| x * 2048")]
+    pub pool_manifest_offset: u32,
+    pub pool_manifest_unused0: u32,
+    pub pool_manifest_unused1: u32,
+    pub pool_object_decompression_buffer_capacity: u32,
+    pub block_sector_padding_size: u32,
+    pub pool_sector_padding_size: u32,
+    pub file_size: u32,
+    #[nom(
+        Map = "|x: &str| x.to_string()",
+        Parse = "{ |i| { if file_size != 0xFFFFFFFF { take_c_string_as_str(i, 128) } else { take_nothing_as_str(i) } } }"
+    )]
+    pub incredi_builder_string: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigFileObject {
+    pub header: ObjectHeader,
+    pub class_object: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+impl BigFileObject {
+    pub fn is_compressed(&self) -> bool {
+        self.header.compressed_size != 0
+    }
+
+    pub fn decompressed_data(&self) -> Result<Vec<u8>> {
+        if !self.is_compressed() {
+            return Ok(self.data.clone());
+        }
+
+        let mut decompressed_buffer = Vec::with_capacity(self.header.decompressed_size as usize);
+        LzrsDecoder::new(&self.data[..])?.read_to_end(&mut decompressed_buffer)?;
+
+        Ok(decompressed_buffer)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigFileBlock {
+    pub description: BlockDescription,
+    pub object_headers: Vec<ObjectHeader>,
+}
+
+pub struct BigFile {
+    pub header: PrimaryHeader,
+    pub blocks: Vec<BigFileBlock>,
+    pub pool_manifest: Option<PoolManifest>,
+    objects: HashMap<u32, BigFileObject>,
+    pooled_crc32s: HashSet<u32>,
+}
+
+pub(crate) fn read_primary_header<R: Read + Seek>(reader: &mut R) -> Result<PrimaryHeader> {
+    reader.seek(SeekFrom::Start(0))?;
+
+    let mut buffer = [0; 2048];
+    reader.read_exact(&mut buffer)?;
+    match PrimaryHeader::parse(&buffer) {
+        Ok((_, h)) => Ok(h),
+        Err(error) => Err(DpcError::parse("primary header", 0, &buffer, error)),
+    }
+}
+
+// Leaves the reader at the first pool object
+pub(crate) fn read_pool_manifest<R: Read + Seek>(
+    reader: &mut R,
+    header: &PrimaryHeader,
+) -> Result<PoolManifest> {
+    reader.seek(SeekFrom::Start(header.pool_manifest_offset as u64))?;
+
+    let mut buf: Vec<u8> = vec![0; header.pool_manifest_padded_size as usize];
+    reader.read_exact(&mut buf)?;
+
+    match PoolManifest::parse(&buf) {
+        Ok((_, h)) => Ok(h),
+        Err(error) => Err(DpcError::parse(
+            "pool manifest",
+            header.pool_manifest_offset as usize,
+            &buf,
+            error,
+        )),
+    }
+}
+
+pub(crate) fn read_object_header<R: Read + Seek>(reader: &mut R) -> Result<ObjectHeader> {
+    let offset = reader.stream_position()? as usize;
+    let mut buffer = [0; 24];
+    reader.read_exact(&mut buffer)?;
+    match ObjectHeader::parse(&buffer) {
+        Ok((_, h)) => Ok(h),
+        Err(error) => Err(DpcError::parse("object header", offset, &buffer, error)),
+    }
+}
+
+// Checks the reader holds size more bytes before allocating them, so a corrupt size in a header
+// fails instead of allocating it
+pub(crate) fn read_sized<R: Read + Seek>(reader: &mut R, size: u32) -> Result<Vec<u8>> {
+    let position = reader.stream_position()?;
+    let end = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(position))?;
+    if position + size as u64 > end {
+        return Err(DpcError::Format(format!(
+            "{} bytes at {} run past the end of the file at {}",
+            size, position, end
+        )));
+    }
+
+    let mut buffer = vec![0; size as usize];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+// data_size of an object whose body is in the pool
+fn pooled_data_size(header: &ObjectHeader, pool_object_header: &ObjectHeader) -> Result<u32> {
+    header
+        .class_object_size
+        .checked_add(pool_object_header.data_size)
+        .ok_or_else(|| {
+            DpcError::Format(format!(
+                "pool object {} is too large",
+                pool_object_header.crc32
+            ))
+        })
+}
+
+impl BigFile {
+    pub fn read<R: Read + Seek>(reader: &mut R) -> Result<BigFile> {
+        let header = read_primary_header(reader)?;
+
+        let mut blocks = vec![];
+        let mut objects: HashMap<u32, BigFileObject> = HashMap::new();
+
+        for block_description in header.block_descriptions.iter() {
+            let block_start = reader.stream_position()?;
+
+            let mut object_headers = vec![];
+            for _ in 0..block_description.object_count {
+                let object_header = read_object_header(reader)?;
+
+                let class_object = read_sized(reader, object_header.class_object_size)?;
+                let data = read_sized(reader, object_header.body_size()?)?;
+
+                objects.entry(object_header.crc32).or_insert(BigFileObject {
+                    header: object_header,
+                    class_object,
+                    data,
+                });
+
+                object_headers.push(object_header);
+            }
+
+            reader.seek(SeekFrom::Start(
+                block_start + block_description.padded_size as u64,
+            ))?;
+
+            blocks.push(BigFileBlock {
+                description: *block_description,
+                object_headers,
+            });
+        }
+
+        let mut pool_manifest = None;
+        let mut pooled_crc32s = HashSet::new();
+
+        if header.pool_manifest_offset != 0 {
+            let manifest = read_pool_manifest(reader, &header)?;
+
+            for _ in 0..manifest.objects_crc32s.len() {
+                let pool_object_header = read_object_header(reader)?;
+
+                let data = read_sized(reader, pool_object_header.data_size)?;
+                let size = pool_object_header.data_size as u64 + 24;
+                reader.seek(SeekFrom::Current((size.wrapping_neg() & 0x7ff) as i64))?;
+
+                if pooled_crc32s.contains(&pool_object_header.crc32) {
+                    continue;
+                }
+
+                let object = match objects.get_mut(&pool_object_header.crc32) {
+                    Some(object) => object,
+                    None => return Err(DpcError::MissingObject(pool_object_header.crc32)),
+                };
+
+                object.header.compressed_size = pool_object_header.compressed_size;
+                object.header.decompressed_size = pool_object_header.decompressed_size;
+                object.header.data_size = pooled_data_size(&object.header, &pool_object_header)?;
+                object.data = data;
+
+                pooled_crc32s.insert(pool_object_header.crc32);
+            }
+
+            pool_manifest = Some(manifest);
+        }
+
+        Ok(BigFile {
+            header,
+            blocks,
+            pool_manifest,
+            objects,
+            pooled_crc32s,
+        })
+    }
+
+    pub fn object(&self, crc32: u32) -> Option<&BigFileObject> {
+        self.objects.get(&crc32)
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = &BigFileObject> {
+        self.objects.values()
+    }
+
+    pub fn crc32s(&self) -> impl Iterator<Item = &u32> {
+        self.objects.keys()
+    }
+
+    pub fn contains(&self, crc32: u32) -> bool {
+        self.objects.contains_key(&crc32)
+    }
+
+    pub fn is_pooled(&self, crc32: u32) -> bool {
+        self.pooled_crc32s.contains(&crc32)
+    }
+
+    pub fn class_object(&self, crc32: u32) -> Option<&[u8]> {
+        self.objects.get(&crc32).map(|x| &x.class_object[..])
+    }
+
+    pub fn data(&self, crc32: u32) -> Option<&[u8]> {
+        self.objects.get(&crc32).map(|x| &x.data[..])
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ObjectLocation {
+    pub block_index: usize,
+    pub offset: u64,
+    pub header: ObjectHeader,
+    pub pool_offset: Option<u64>,
+}
+
+pub struct BigFileIndex<R: Read + Seek> {
+    reader: R,
+    pub header: PrimaryHeader,
+    pub blocks: Vec<BigFileBlock>,
+    pub pool_manifest: Option<PoolManifest>,
+    locations: HashMap<u32, ObjectLocation>,
+}
+
+impl<R: Read + Seek> BigFileIndex<R> {
+    pub fn new(mut reader: R) -> Result<BigFileIndex<R>> {
+        let header = read_primary_header(&mut reader)?;
+
+        let mut locations: HashMap<u32, ObjectLocation> = HashMap::new();
+        let mut blocks = vec![];
+
+        let mut block_start: u64 = 2048;
+        for (block_index, block_description) in header.block_descriptions.iter().enumerate() {
+            reader.seek(SeekFrom::Start(block_start))?;
+
+            let mut object_headers = vec![];
+            for _ in 0..block_description.object_count {
+                let offset = reader.stream_position()?;
+                let object_header = read_object_header(&mut reader)?;
+                reader.seek(SeekFrom::Current(object_header.data_size as i64))?;
+
+                locations
+                    .entry(object_header.crc32)
+                    .or_insert(ObjectLocation {
+                        block_index,
+                        offset,
+                        header: object_header,
+                        pool_offset: None,
+                    });
+
+                object_headers.push(object_header);
+            }
+
+            blocks.push(BigFileBlock {
+                description: *block_description,
+                object_headers,
+            });
+
+            block_start += block_description.padded_size as u64;
+        }
+
+        let mut pool_manifest = None;
+
+        if header.pool_manifest_offset != 0 {
+            let manifest = read_pool_manifest(&mut reader, &header)?;
+
+            let mut pool_object_offset =
+                header.pool_manifest_offset as u64 + header.pool_manifest_padded_size as u64;
+            for object_entry_index in manifest.objects_crc32s.iter() {
+                let crc32 = *manifest
+                    .crc32s
+                    .get(*object_entry_index as usize)
+                    .ok_or_else(|| {
+                        DpcError::Format(format!(
+                            "pool object entry index {} is out of range",
+                            object_entry_index
+                        ))
+                    })?;
+
+                if let Some(location) = locations.get_mut(&crc32) {
+                    if location.pool_offset.is_none() {
+                        location.pool_offset = Some(pool_object_offset);
+                    }
+                } else {
+                    return Err(DpcError::MissingObject(crc32));
+                }
+
+                let padded_size = manifest
+                    .object_padded_size
+                    .get(*object_entry_index as usize)
+                    .ok_or_else(|| {
+                        DpcError::Format(format!(
+                            "pool object entry index {} has no padded size",
+                            object_entry_index
+                        ))
+                    })?;
+                pool_object_offset += *padded_size as u64 * 2048;
+            }
+
+            pool_manifest = Some(manifest);
+        }
+
+        Ok(BigFileIndex {
+            reader,
+            header,
+            blocks,
+            pool_manifest,
+            locations,
+        })
+    }
+
+    pub fn location(&self, crc32: u32) -> Option<&ObjectLocation> {
+        self.locations.get(&crc32)
+    }
+
+    pub fn locations(&self) -> impl Iterator<Item = &ObjectLocation> {
+        self.locations.values()
+    }
+
+    pub fn contains(&self, crc32: u32) -> bool {
+        self.locations.contains_key(&crc32)
+    }
+
+    pub fn open_object(&mut self, crc32: u32) -> Result<BigFileObject> {
+        let mut object = self.read_object(crc32)?;
+
+        if object.is_compressed() {
+            object.data = object.decompressed_data()?;
+            object.header.compressed_size = 0;
+            object.header.data_size =
+                object.header.class_object_size + object.header.decompressed_size;
+        }
+
+        Ok(object)
+    }
+
+    // The object as it is stored, still compressed if it was, with its body from the pool
+    pub fn read_object(&mut self, crc32: u32) -> Result<BigFileObject> {
+        let location = match self.locations.get(&crc32) {
+            Some(location) => *location,
+            None => return Err(DpcError::MissingObject(crc32)),
+        };
+
+        self.reader.seek(SeekFrom::Start(location.offset + 24))?;
+
+        let mut oh = location.header;
+
+        let class_object = read_sized(&mut self.reader, oh.class_object_size)?;
+        let mut data = read_sized(&mut self.reader, oh.body_size()?)?;
+
+        if let Some(pool_offset) = location.pool_offset {
+            self.reader.seek(SeekFrom::Start(pool_offset))?;
+            let pool_object_header = read_object_header(&mut self.reader)?;
+
+            data = read_sized(&mut self.reader, pool_object_header.data_size)?;
+
+            oh.compressed_size = pool_object_header.compressed_size;
+            oh.decompressed_size = pool_object_header.decompressed_size;
+            oh.data_size = pooled_data_size(&oh, &pool_object_header)?;
+        }
+
+        Ok(BigFileObject {
+            header: oh,
+            class_object,
+            data,
+        })
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl BigFileObject {
+    pub fn new(
+        crc32: u32,
+        class_crc32: u32,
+        class_object: Vec<u8>,
+        data: Vec<u8>,
+    ) -> BigFileObject {
+        BigFileObject {
+            header: ObjectHeader {
+                data_size: (class_object.len() + data.len()) as u32,
+                class_object_size: class_object.len() as u32,
+                decompressed_size: data.len() as u32,
+                compressed_size: 0,
+                class_crc32,
+                crc32,
+            },
+            class_object,
+            data,
+        }
+    }
+
+    // Reads an object as written to objects/ by extract: header, class object, data
+    pub fn read<R: Read + Seek>(reader: &mut R) -> Result<BigFileObject> {
+        let header = read_object_header(reader)?;
+        let class_object = read_sized(reader, header.class_object_size)?;
+        let data = read_sized(reader, header.body_size()?)?;
+        Ok(BigFileObject {
+            header,
+            class_object,
+            data,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.header.write(writer)?;
+        writer.write_all(&self.class_object)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+
+    pub fn compressed(&self, compression_level: CompressionLevel) -> Result<BigFileObject> {
+        if self.is_compressed() {
+            return Err(DpcError::AlreadyCompressed(self.header.crc32));
+        }
+
+        let mut encoder = LzrsEncoder::new(vec![]).compression_level(compression_level);
+        encoder.write_all(&self.data)?;
+        let data = encoder.finish()?;
+
+        let mut header = self.header;
+        header.decompressed_size = self.data.len() as u32;
+        header.compressed_size = data.len() as u32;
+        header.data_size = header.class_object_size + header.compressed_size;
+
+        Ok(BigFileObject {
+            header,
+            class_object: self.class_object.clone(),
+            data,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PoolEntry {
+    pub crc32: u32,
+    // 1-based index into reference_records
+    pub reference_record_index: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PoolReferenceRecord {
+    pub object_entries_starting_index: u32,
+    pub object_entries_count: u16,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolLayout {
+    pub object_entry_indices: Vec<u32>,
+    pub object_entries: Vec<PoolEntry>,
+    pub reference_records: Vec<PoolReferenceRecord>,
+}
+
+impl PoolLayout {
+    pub fn from_manifest(manifest: &PoolManifest) -> PoolLayout {
+        PoolLayout {
+            object_entry_indices: manifest.objects_crc32s.clone(),
+            object_entries: manifest
+                .crc32s
+                .iter()
+                .zip(manifest.reference_records_indices.iter())
+                .map(|(crc32, reference_record_index)| PoolEntry {
+                    crc32: *crc32,
+                    reference_record_index: *reference_record_index,
+                })
+                .collect(),
+            reference_records: manifest
+                .reference_records
+                .iter()
+                .map(|record| PoolReferenceRecord {
+                    object_entries_starting_index: record.objects_crc32_starting_index,
+                    object_entries_count: record.objects_crc32_count,
+                })
+                .collect(),
+        }
+    }
+
+    // One reference record per block, listing the pooled objects in block order
+    fn from_blocks(blocks: &[WriterBlock], pooled_crc32s: &HashSet<u32>) -> PoolLayout {
+        let mut layout = PoolLayout::default();
+        let mut entry_indices: HashMap<u32, u32> = HashMap::new();
+        for block in blocks.iter() {
+            let starting_index = layout.object_entry_indices.len();
+            for (crc32, _) in block.objects.iter() {
+                if !pooled_crc32s.contains(crc32) {
+                    continue;
+                }
+                let reference_record_index = layout.reference_records.len() as u32 + 1;
+                let object_entries = &mut layout.object_entries;
+                let entry_index = *entry_indices.entry(*crc32).or_insert_with(|| {
+                    object_entries.push(PoolEntry {
+                        crc32: *crc32,
+                        reference_record_index,
+                    });
+                    object_entries.len() as u32 - 1
+                });
+                layout.object_entry_indices.push(entry_index);
+            }
+            let count = layout.object_entry_indices.len() - starting_index;
+            if count != 0 {
+                layout.reference_records.push(PoolReferenceRecord {
+                    object_entries_starting_index: starting_index as u32,
+                    object_entries_count: count as u16,
+                });
+            }
+        }
+        layout
+    }
+
+    // Merges duplicate reference records
+    pub fn optimize(&mut self) -> Result<()> {
+        let reference_records: Vec<PoolReferenceRecord> = self
+            .reference_records
+            .clone()
+            .into_iter()
+            .unique()
+            .collect();
+
+        let old_reference_records = &self.reference_records;
+        for entry in self.object_entries.iter_mut() {
+            let record = entry
+                .reference_record_index
+                .checked_sub(1)
+                .and_then(|index| old_reference_records.get(index as usize))
+                .ok_or_else(|| {
+                    DpcError::Format(format!(
+                        "pool entry {} has reference record index {} out of range",
+                        entry.crc32, entry.reference_record_index
+                    ))
+                })?;
+            let index = reference_records
+                .iter()
+                .position(|r| r == record)
+                .ok_or_else(|| {
+                    DpcError::Format(format!(
+                        "pool entry {} lost its reference record",
+                        entry.crc32
+                    ))
+                })?;
+            entry.reference_record_index = index as u32 + 1;
+        }
+
+        self.reference_records = reference_records;
+        Ok(())
+    }
+}
+
+struct WriterBlock {
+    working_buffer_offset: u32,
+    // Defaults to the writer's block type for the first block and 0 after it
+    block_type: Option<u32>,
+    // (crc32, compress)
+    objects: Vec<(u32, bool)>,
+    // Copied as is from this offset in the source given to finish_copying instead of writing the
+    // objects, which then only feed the pool
+    raw: Option<(BlockDescription, u64)>,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+struct PascalArrayU32 {
+    len: u32,
+    data: Vec<u32>,
+}
+
+impl PascalArrayU32 {
+    fn new(data: Vec<u32>) -> PascalArrayU32 {
+        PascalArrayU32 {
+            len: data.len() as u32,
+            data,
+        }
+    }
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+struct PascalArrayReferenceRecord {
+    len: u32,
+    data: Vec<ReferenceRecord>,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+struct PrimaryHeaderPartA {
+    is_not_rtc: u32,
+    block_count: u32,
+    block_working_buffer_capacity_even: u32,
+    block_working_buffer_capacity_odd: u32,
+    padded_size: u32,
+    version_patch: u32,
+    version_minor: u32,
+}
+
+#[derive(BinWrite)]
+#[binwrite(little)]
+struct PrimaryHeaderPartB {
+    pool_manifest_padded_size: u32,
+    pool_manifest_offset: u32,
+    pool_manifest_unused0: u32,
+    pool_manifest_unused1: u32,
+    pool_object_decompression_buffer_capacity: u32,
+    block_sector_padding_size: u32,
+    pool_sector_padding_size: u32,
+    file_size: u32,
+}
+
+fn write_padding<W: Write + Seek>(writer: &mut W, value: u8) -> Result<u32> {
+    let pos = writer.stream_position()? as u32;
+    let padding = vec![value; calculate_padding_size(pos) as usize];
+    writer.write_all(&padding)?;
+    Ok(padding.len() as u32)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompressionTotals {
+    pub object_count: usize,
+    pub decompressed_size: u64,
+    pub compressed_size: u64,
+    // Summed across threads, so it can exceed the wall time
+    pub duration: Duration,
+}
+
+// Keyed by class crc32
+pub type CompressionReport = BTreeMap<u32, CompressionTotals>;
+
+/// Builds a BigFile without an extracted directory: add blocks, push objects into them, mark
+/// objects as pooled and `finish` into any `Write + Seek`
+pub struct BigFileWriter {
+    version_string: String,
+    version_patch: u32,
+    version_minor: u32,
+    block_type: u32,
+    is_rtc: bool,
+    pool_manifest_unused: u32,
+    incredi_builder_string: String,
+    is_lz: bool,
+    compression_level: CompressionLevel,
+    threads: usize,
+    blocks: Vec<WriterBlock>,
+    objects: HashMap<u32, BigFileObject>,
+    compressed_objects: HashMap<u32, BigFileObject>,
+    pooled_crc32s: HashSet<u32>,
+    pool_layout: Option<PoolLayout>,
+}
+
+impl BigFileWriter {
+    pub fn new(
+        version_string: &str,
+        version_patch: u32,
+        version_minor: u32,
+        block_type: u32,
+    ) -> BigFileWriter {
+        BigFileWriter {
+            version_string: version_string.to_string(),
+            version_patch,
+            version_minor,
+            block_type,
+            is_rtc: false,
+            pool_manifest_unused: 0,
+            incredi_builder_string: String::new(),
+            is_lz: true,
+            compression_level: CompressionLevel::default(),
+            threads: 1,
+            blocks: Vec::new(),
+            objects: HashMap::new(),
+            compressed_objects: HashMap::new(),
+            pooled_crc32s: HashSet::new(),
+            pool_layout: None,
+        }
+    }
+
+    pub fn rtc(mut self, is_rtc: bool) -> BigFileWriter {
+        self.is_rtc = is_rtc;
+        self
+    }
+
+    pub fn pool_manifest_unused(mut self, pool_manifest_unused: u32) -> BigFileWriter {
+        self.pool_manifest_unused = pool_manifest_unused;
+        self
+    }
+
+    // An empty string leaves the IncrediBuilder fields as 0xFFFFFFFF
+    pub fn incredi_builder_string(mut self, incredi_builder_string: &str) -> BigFileWriter {
+        self.incredi_builder_string = incredi_builder_string.to_string();
+        self
+    }
+
+    // Without lz, objects marked compressed are written as they were pushed
+    pub fn lz(mut self, is_lz: bool) -> BigFileWriter {
+        self.is_lz = is_lz;
+        self
+    }
+
+    pub fn compression_level(mut self, compression_level: CompressionLevel) -> BigFileWriter {
+        self.compression_level = compression_level;
+        self
+    }
+
+    // Objects are compressed on this many threads during finish
+    pub fn threads(mut self, threads: usize) -> BigFileWriter {
+        self.threads = threads;
+        self
+    }
+
+    pub fn add_block(&mut self, working_buffer_offset: u32) -> usize {
+        self.blocks.push(WriterBlock {
+            working_buffer_offset,
+            block_type: None,
+            objects: Vec::new(),
+            raw: None,
+        });
+        self.blocks.len() - 1
+    }
+
+    // Copies a block from the BigFile passed to finish_copying, padding included, starting at
+    // offset. Its pooled objects still have to be pushed so they end up in the pool
+    pub fn add_copied_block(&mut self, description: BlockDescription, offset: u64) -> usize {
+        self.blocks.push(WriterBlock {
+            working_buffer_offset: description.working_buffer_offset,
+            block_type: Some(description.block_type),
+            objects: Vec::new(),
+            raw: Some((description, offset)),
+        });
+        self.blocks.len() - 1
+    }
+
+    pub fn set_block_type(&mut self, block_index: usize, block_type: u32) -> Result<()> {
+        self.block_mut(block_index)?.block_type = Some(block_type);
+        Ok(())
+    }
+
+    fn block_mut(&mut self, block_index: usize) -> Result<&mut WriterBlock> {
+        self.blocks
+            .get_mut(block_index)
+            .ok_or_else(|| DpcError::Format(format!("block {} does not exist", block_index)))
+    }
+
+    pub fn contains(&self, crc32: u32) -> bool {
+        self.objects.contains_key(&crc32)
+    }
+
+    // The first object pushed for a crc32 is the one written for every occurrence
+    pub fn push(
+        &mut self,
+        block_index: usize,
+        object: BigFileObject,
+        compress: bool,
+    ) -> Result<()> {
+        let crc32 = object.header.crc32;
+        self.objects.entry(crc32).or_insert(object);
+        self.push_existing(block_index, crc32, compress)
+    }
+
+    // Adds another occurrence of an already pushed object
+    pub fn push_existing(&mut self, block_index: usize, crc32: u32, compress: bool) -> Result<()> {
+        if !self.objects.contains_key(&crc32) {
+            return Err(DpcError::MissingObject(crc32));
+        }
+
+        self.block_mut(block_index)?.objects.push((crc32, compress));
+        Ok(())
+    }
+
+    pub fn pool(&mut self, crc32: u32) {
+        self.pooled_crc32s.insert(crc32);
+    }
+
+    // Replaces the layout derived from the blocks, every entry's object is pooled
+    pub fn set_pool_layout(&mut self, pool_layout: PoolLayout) {
+        for entry in pool_layout.object_entries.iter() {
+            self.pooled_crc32s.insert(entry.crc32);
+        }
+        self.pool_layout = Some(pool_layout);
+    }
+
+    // Compresses every object still waiting for it, finish does this itself when not called
+    pub fn compress(&mut self) -> Result<CompressionReport> {
+        let mut crc32s: Vec<u32> = vec![];
+        for block in self.blocks.iter() {
+            for (crc32, compress) in block.objects.iter() {
+                if *compress
+                    && !self.objects[crc32].is_compressed()
+                    && !self.compressed_objects.contains_key(crc32)
+                {
+                    crc32s.push(*crc32);
+                }
+            }
+        }
+        let crc32s: Vec<u32> = crc32s.into_iter().unique().collect();
+
+        let objects = &self.objects;
+        let compression_level = self.compression_level;
+        let compressed_objects = parallel_map(self.threads, &crc32s, |crc32| {
+            let start = Instant::now();
+            let object = objects[crc32].compressed(compression_level)?;
+            Ok((object, start.elapsed()))
+        })?;
+
+        let mut report = CompressionReport::new();
+        for (crc32, (object, duration)) in crc32s.into_iter().zip(compressed_objects) {
+            let totals = report.entry(object.header.class_crc32).or_default();
+            totals.object_count += 1;
+            totals.decompressed_size += object.header.decompressed_size as u64;
+            totals.compressed_size += object.header.compressed_size as u64;
+            totals.duration += duration;
+            self.compressed_objects.insert(crc32, object);
+        }
+        Ok(report)
+    }
+
+    fn object(&self, crc32: u32, compress: bool) -> Result<&BigFileObject> {
+        if compress && self.is_lz {
+            if let Some(object) = self.compressed_objects.get(&crc32) {
+                return Ok(object);
+            }
+        }
+        self.objects
+            .get(&crc32)
+            .ok_or(DpcError::MissingObject(crc32))
+    }
+
+    pub fn finish<W: Write + Seek>(self, writer: &mut W) -> Result<PrimaryHeader> {
+        self.finish_copying(&mut Cursor::new(&[][..]), writer)
+    }
+
+    // Like finish, streaming the blocks added with add_copied_block from source
+    pub fn finish_copying<R: Read + Seek, W: Write + Seek>(
+        mut self,
+        source: &mut R,
+        writer: &mut W,
+    ) -> Result<PrimaryHeader> {
+        if self.is_lz {
+            self.compress()?;
+        }
+
+        let pool_layout = match &self.pool_layout {
+            Some(pool_layout) => Some(pool_layout.clone()),
+            None if !self.pooled_crc32s.is_empty() => {
+                Some(PoolLayout::from_blocks(&self.blocks, &self.pooled_crc32s))
+            }
+            None => None,
+        };
+        let pooled_crc32s = &self.pooled_crc32s;
+
+        writer.seek(SeekFrom::Start(0))?;
+        writer.write_all(&[0; 2048])?;
+
+        //
+        // Blocks
+        //
+
+        let mut block_descriptions: Vec<BlockDescription> = Vec::new();
+        let mut block_sector_padding_size: u32 = 0;
+        let mut pool_object_compress_map: HashMap<u32, bool> = HashMap::new();
+        let mut object_padded_size_map: HashMap<u32, u32> = HashMap::new();
+        let mut block_type = self.block_type;
+
+        for block in self.blocks.iter() {
+            let start_pos = writer.stream_position()?;
+
+            for (crc32, compress) in block.objects.iter() {
+                let object = self.object(*crc32, *compress)?;
+                if !pooled_crc32s.contains(crc32) {
+                    if block.raw.is_none() {
+                        object.write(writer)?;
+                    }
+                    continue;
+                }
+
+                match pool_object_compress_map.get(crc32) {
+                    Some(x) if *x != *compress => {
+                        return Err(DpcError::InconsistentCompress(*crc32))
+                    }
+                    Some(_) => (),
+                    None => {
+                        pool_object_compress_map.insert(*crc32, *compress);
+                    }
+                }
+
+                if !object_padded_size_map.contains_key(crc32) {
+                    object_padded_size_map.insert(
+                        *crc32,
+                        calculate_padded_size(24 + object.data.len() as u32) >> 11,
+                    );
+                }
+
+                if block.raw.is_some() {
+                    continue;
+                }
+
+                let mut oh = object.header;
+                oh.class_object_size = object.class_object.len() as u32;
+                oh.data_size = oh.class_object_size;
+                oh.compressed_size = 0;
+                oh.decompressed_size = 0;
+                oh.write(writer)?;
+                writer.write_all(&object.class_object)?;
+            }
+
+            if let Some((description, offset)) = &block.raw {
+                source.seek(SeekFrom::Start(*offset))?;
+                let size = description.padded_size as u64;
+                if io::copy(&mut (&mut *source).take(size), writer)? != size {
+                    return Err(DpcError::Format(format!(
+                        "block at {} runs past the end of the file",
+                        offset
+                    )));
+                }
+                block_descriptions.push(*description);
+                block_type = 0;
+                block_sector_padding_size += description.padded_size - description.data_size;
+                continue;
+            }
+
+            let len = (writer.stream_position()? - start_pos) as u32;
+
+            block_descriptions.push(BlockDescription {
+                block_type: block.block_type.unwrap_or(block_type),
+                object_count: block.objects.len() as u32,
+                crc32: block.objects.first().map_or(0, |(crc32, _)| *crc32),
+                data_size: len,
+                padded_size: calculate_padded_size(len),
+                working_buffer_offset: block.working_buffer_offset,
+            });
+
+            block_type = 0;
+
+            block_sector_padding_size += write_padding(writer, 0x00)?;
+        }
+
+        let blocks_padded_size = writer.stream_position()? as u32 - 2048;
+
+        //
+        // Pool
+        //
+
+        let mut pool_manifest_offset: u32 = 0;
+        let mut pool_manifest_padded_size: u32 = 0;
+        let mut pool_sector_padding_size: u32 = 0;
+        let mut max_pool_decompressed_size = 0;
+
+        if let Some(pool) = &pool_layout {
+            pool_manifest_offset = writer.stream_position()? as u32;
+
+            let mut objects_crc32_count_sum: u32 = 0;
+            for record in pool.reference_records.iter() {
+                objects_crc32_count_sum += record.object_entries_count as u32;
+            }
+
+            PoolManifestHeader {
+                equals524288: 524288,
+                equals2048: 2048,
+                objects_crc32_count_sum,
+            }
+            .write(writer)?;
+
+            PascalArrayU32::new(pool.object_entry_indices.clone()).write(writer)?;
+
+            let mut reference_count_map: HashMap<u32, u32> = HashMap::new();
+            for i in pool.object_entry_indices.iter() {
+                let entry = pool
+                    .object_entries
+                    .get(*i as usize)
+                    .ok_or_else(|| DpcError::Format(format!("pool entry {} does not exist", i)))?;
+                *reference_count_map.entry(entry.crc32).or_insert(0) += 1;
+            }
+
+            let mut vec_crc32s = vec![];
+            let mut vec_reference_records_indices = vec![];
+            let mut vec_reference_count: Vec<u32> = vec![];
+            let mut vec_object_padded_size: Vec<u32> = vec![];
+            for entry in pool.object_entries.iter() {
+                vec_crc32s.push(entry.crc32);
+                vec_reference_records_indices.push(entry.reference_record_index);
+                vec_reference_count.push(*reference_count_map.get(&entry.crc32).unwrap_or(&0));
+                vec_object_padded_size.push(
+                    *object_padded_size_map
+                        .get(&entry.crc32)
+                        .ok_or(DpcError::MissingObject(entry.crc32))?,
+                );
+            }
+
+            PascalArrayU32::new(vec_crc32s).write(writer)?;
+            PascalArrayU32::new(vec_reference_count).write(writer)?;
+            PascalArrayU32::new(vec_object_padded_size).write(writer)?;
+            PascalArrayU32::new(vec_reference_records_indices).write(writer)?;
+
+            let pos = writer.stream_position()? as u32;
+            let end_of_pool_manifest =
+                calculate_padded_size(pos + 28 * pool.reference_records.len() as u32 + 28);
+
+            let padded_size = |i: u32| -> Result<u32> {
+                let crc32 = pool
+                    .object_entry_indices
+                    .get(i as usize)
+                    .and_then(|entry_index| pool.object_entries.get(*entry_index as usize))
+                    .ok_or_else(|| {
+                        DpcError::Format(format!("pool entry index {} does not exist", i))
+                    })?
+                    .crc32;
+                object_padded_size_map
+                    .get(&crc32)
+                    .copied()
+                    .ok_or(DpcError::MissingObject(crc32))
+            };
+
+            let mut vec_reference_records = vec![];
+            for record in pool.reference_records.iter() {
+                let mut start_chunk_index: u32 = end_of_pool_manifest / 2048;
+                for i in 0..record.object_entries_starting_index {
+                    start_chunk_index += padded_size(i)?;
+                }
+
+                let mut end_chunk_index = start_chunk_index;
+                for i in record.object_entries_starting_index
+                    ..record
+                        .object_entries_starting_index
+                        .saturating_add(record.object_entries_count as u32)
+                {
+                    end_chunk_index += padded_size(i)?;
+                }
+
+                vec_reference_records.push(ReferenceRecord {
+                    start_chunk_index,
+                    end_chunk_index,
+                    objects_crc32_starting_index: record.object_entries_starting_index,
+                    placeholder_dpc_index: 0,
+                    objects_crc32_count: record.object_entries_count,
+                    placeholder_times_referenced: 0xFFFFFFFF,
+                    placeholder_current_references_shared: 0xFFFFFFFF,
+                    placeholder_current_references_weak: 0xFFFFFFFF,
+                })
+            }
+
+            PascalArrayReferenceRecord {
+                len: vec_reference_records.len() as u32,
+                data: vec_reference_records,
+            }
+            .write(writer)?;
+
+            // terminal
+            ReferenceRecord {
+                start_chunk_index: 0,
+                end_chunk_index: 0,
+                objects_crc32_starting_index: 0,
+                placeholder_dpc_index: 0,
+                objects_crc32_count: 0,
+                placeholder_times_referenced: 0xFFFFFFFF,
+                placeholder_current_references_shared: 0xFFFFFFFF,
+                placeholder_current_references_weak: 0xFFFFFFFF,
+            }
+            .write(writer)?;
+
+            write_padding(writer, 0xff)?;
+
+            pool_manifest_padded_size = writer.stream_position()? as u32 - pool_manifest_offset;
+
+            for i in pool.object_entry_indices.iter() {
+                let crc32 = pool.object_entries[*i as usize].crc32;
+                let compress = *pool_object_compress_map
+                    .get(&crc32)
+                    .ok_or(DpcError::MissingObject(crc32))?;
+                let object = self.object(crc32, compress)?;
+
+                max_pool_decompressed_size = max(
+                    max_pool_decompressed_size,
+                    calculate_padded_size(object.header.decompressed_size) >> 11,
+                );
+
+                let mut oh = object.header;
+                oh.data_size = object.data.len() as u32;
+                oh.class_object_size = 0;
+                oh.write(writer)?;
+                writer.write_all(&object.data)?;
+
+                pool_sector_padding_size += write_padding(writer, 0xff)?;
+            }
+        }
+
+        let mut file_size = writer.stream_position()? as u32;
+
+        //
+        // Header
+        //
+
+        if self.incredi_builder_string.is_empty() {
+            block_sector_padding_size = 0xFFFFFFFF;
+            pool_sector_padding_size = 0xFFFFFFFF;
+            file_size = 0xFFFFFFFF;
+        }
+
+        let mut block_working_buffer_capacity_even = 0;
+        let mut block_working_buffer_capacity_odd = 0;
+        for (i, block_description) in block_descriptions.iter().enumerate() {
+            let block_working_buffer_capacity =
+                block_description.padded_size + block_description.working_buffer_offset;
+            if i % 2 == 0 {
+                block_working_buffer_capacity_even = max(
+                    block_working_buffer_capacity_even,
+                    block_working_buffer_capacity,
+                );
+            } else {
+                block_working_buffer_capacity_odd = max(
+                    block_working_buffer_capacity_odd,
+                    block_working_buffer_capacity,
+                );
+            }
+        }
+
+        let header = PrimaryHeader {
+            version_string: self.version_string.clone(),
+            is_not_rtc: !self.is_rtc as u32,
+            block_count: block_descriptions.len() as u32,
+            block_working_buffer_capacity_even,
+            block_working_buffer_capacity_odd,
+            padded_size: blocks_padded_size,
+            version_patch: self.version_patch,
+            version_minor: self.version_minor,
+            block_descriptions,
+            pool_manifest_padded_size: calculate_padded_size(pool_manifest_padded_size),
+            pool_manifest_offset: calculate_padded_size(pool_manifest_offset),
+            pool_manifest_unused0: self.pool_manifest_unused,
+            pool_manifest_unused1: self.pool_manifest_unused,
+            pool_object_decompression_buffer_capacity: max_pool_decompressed_size,
+            block_sector_padding_size,
+            pool_sector_padding_size,
+            file_size,
+            incredi_builder_string: self.incredi_builder_string.clone(),
+        };
+
+        let mut cursor = Cursor::new(vec![0u8; 2048]);
+
+        cursor.write_all(header.version_string.as_bytes())?;
+
+        cursor.seek(SeekFrom::Start(256))?;
+        PrimaryHeaderPartA {
+            is_not_rtc: header.is_not_rtc,
+            block_count: header.block_count,
+            block_working_buffer_capacity_even: header.block_working_buffer_capacity_even,
+            block_working_buffer_capacity_odd: header.block_working_buffer_capacity_odd,
+            padded_size: header.padded_size,
+            version_patch: header.version_patch,
+            version_minor: header.version_minor,
+        }
+        .write(&mut cursor)?;
+        for block_description in header.block_descriptions.iter() {
+            block_description.write(&mut cursor)?;
+        }
+
+        cursor.seek(SeekFrom::Start(0x720))?;
+        PrimaryHeaderPartB {
+            pool_manifest_padded_size: header.pool_manifest_padded_size / 2048,
+            pool_manifest_offset: header.pool_manifest_offset / 2048,
+            pool_manifest_unused0: header.pool_manifest_unused0,
+            pool_manifest_unused1: header.pool_manifest_unused1,
+            pool_object_decompression_buffer_capacity: header
+                .pool_object_decompression_buffer_capacity,
+            block_sector_padding_size: header.block_sector_padding_size,
+            pool_sector_padding_size: header.pool_sector_padding_size,
+            file_size: header.file_size,
+        }
+        .write(&mut cursor)?;
+
+        if !header.incredi_builder_string.is_empty() {
+            cursor.write_all(header.incredi_builder_string.as_bytes())?;
+        } else {
+            cursor.write_all(&[0xFF; 128])?;
+        }
+
+        cursor.seek(SeekFrom::Start(0x7c0))?;
+        cursor.write_all(&[0xff; 64])?;
+
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(0))?;
+        writer.write_all(&cursor.into_inner())?;
+        writer.seek(SeekFrom::Start(end))?;
+
+        Ok(header)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_writer_roundtrip() {
+        let data: Vec<u8> = (0..4096).map(|x| (x % 7) as u8).collect();
+
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253)
+            .incredi_builder_string("IncrediBuilder");
+        let first = writer.add_block(0);
+        let second = writer.add_block(0);
+        writer
+            .push(
+                first,
+                BigFileObject::new(1, 100, vec![1, 2, 3], data.clone()),
+                true,
+            )
+            .unwrap();
+        writer
+            .push(
+                first,
+                BigFileObject::new(2, 200, vec![4], vec![5, 6]),
+                false,
+            )
+            .unwrap();
+        writer.push_existing(second, 1, true).unwrap();
+        writer.pool(1);
+
+        let mut cursor = Cursor::new(Vec::new());
+        let header = writer.finish(&mut cursor).unwrap();
+
+        let bigfile = BigFile::read(&mut cursor).unwrap();
+        assert_eq!(bigfile.header, header);
+        assert_eq!(bigfile.blocks.len(), 2);
+        assert_eq!(bigfile.header.file_size as usize, cursor.get_ref().len());
+
+        let pooled = bigfile.object(1).unwrap();
+        assert!(bigfile.is_pooled(1));
+        assert!(pooled.is_compressed());
+        assert_eq!(pooled.class_object, vec![1, 2, 3]);
+        assert_eq!(pooled.decompressed_data().unwrap(), data);
+
+        let object = bigfile.object(2).unwrap();
+        assert!(!bigfile.is_pooled(2));
+        assert_eq!(object.header.class_crc32, 200);
+        assert_eq!(object.data, vec![5, 6]);
+
+        let pool_manifest = bigfile.pool_manifest.as_ref().unwrap();
+        assert_eq!(pool_manifest.crc32s, vec![1]);
+        assert_eq!(pool_manifest.objects_crc32s, vec![0, 0]);
+        assert_eq!(pool_manifest.reference_counts, vec![2]);
+    }
+
+    #[test]
+    fn test_index_open_object() {
+        let data: Vec<u8> = (0..4096).map(|x| (x % 11) as u8).collect();
+        let objects = [
+            // Pooled and compressed, pooled and stored, in a block only
+            (
+                BigFileObject::new(1, 100, vec![1, 2, 3], data.clone()),
+                true,
+            ),
+            (BigFileObject::new(2, 200, vec![4], vec![5; 3000]), false),
+            (
+                BigFileObject::new(3, 300, vec![], data[..100].to_vec()),
+                true,
+            ),
+            (BigFileObject::new(4, 400, vec![6, 7], vec![8, 9]), false),
+        ];
+
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        let first = writer.add_block(0);
+        let second = writer.add_block(0);
+        for (object, compress) in objects.iter() {
+            writer.push(first, object.clone(), *compress).unwrap();
+        }
+        writer.push_existing(second, 1, true).unwrap();
+        writer.push_existing(second, 4, false).unwrap();
+        writer.pool(1);
+        writer.pool(2);
+
+        let mut cursor = Cursor::new(Vec::new());
+        writer.finish(&mut cursor).unwrap();
+
+        let bigfile = BigFile::read(&mut cursor).unwrap();
+        let mut index = BigFileIndex::new(&mut cursor).unwrap();
+        assert_eq!(index.header, bigfile.header);
+        for (object, compress) in objects.iter() {
+            let crc32 = object.header.crc32;
+            let location = *index.location(crc32).unwrap();
+            assert_eq!(location.block_index, 0);
+            assert_eq!(location.pool_offset.is_some(), crc32 == 1 || crc32 == 2);
+            assert_eq!(bigfile.is_pooled(crc32), crc32 == 1 || crc32 == 2);
+            assert_eq!(bigfile.object(crc32).unwrap().is_compressed(), *compress);
+
+            let opened = index.open_object(crc32).unwrap();
+            assert!(!opened.is_compressed());
+            assert_eq!(opened.header.class_crc32, object.header.class_crc32);
+            assert_eq!(opened.class_object, object.class_object);
+            assert_eq!(opened.data, object.data);
+            assert_eq!(
+                opened.data,
+                bigfile.object(crc32).unwrap().decompressed_data().unwrap()
+            );
+        }
+        assert!(index.open_object(5).is_err());
+    }
+
+    #[test]
+    fn test_read() {
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        let first = writer.add_block(0);
+        let second = writer.add_block(0);
+        writer
+            .push(
+                first,
+                BigFileObject::new(1, 100, vec![1, 2], vec![3]),
+                false,
+            )
+            .unwrap();
+        writer
+            .push(
+                first,
+                BigFileObject::new(2, 200, vec![], vec![4; 3000]),
+                false,
+            )
+            .unwrap();
+        writer.push_existing(second, 1, false).unwrap();
+
+        let mut cursor = Cursor::new(Vec::new());
+        writer.finish(&mut cursor).unwrap();
+
+        let bigfile = BigFile::read(&mut cursor).unwrap();
+        assert!(bigfile.pool_manifest.is_none());
+        assert_eq!(bigfile.len(), 2);
+        assert!(!bigfile.is_empty());
+        assert_eq!(
+            bigfile.crc32s().copied().sorted().collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        let crc32s = |block: &BigFileBlock| {
+            block
+                .object_headers
+                .iter()
+                .map(|header| header.crc32)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(crc32s(&bigfile.blocks[0]), vec![1, 2]);
+        assert_eq!(crc32s(&bigfile.blocks[1]), vec![1]);
+        assert_eq!(bigfile.class_object(1), Some(&[1, 2][..]));
+        assert_eq!(bigfile.data(1), Some(&[3][..]));
+        assert_eq!(bigfile.data(2).unwrap().len(), 3000);
+        assert!(!bigfile.is_pooled(1));
+        assert!(!bigfile.contains(3));
+
+        // Cut into the second block
+        let mut truncated = cursor.into_inner();
+        truncated.truncate(2048 + bigfile.blocks[0].description.padded_size as usize + 8);
+        assert!(BigFile::read(&mut Cursor::new(truncated)).is_err());
+    }
+
+    #[test]
+    fn test_pool_layout_out_of_range() {
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        let block = writer.add_block(0);
+        writer
+            .push(block, BigFileObject::new(1, 100, vec![], vec![2]), false)
+            .unwrap();
+        writer.set_pool_layout(PoolLayout {
+            object_entry_indices: vec![0],
+            object_entries: vec![PoolEntry {
+                crc32: 1,
+                reference_record_index: 1,
+            }],
+            // Past the end of object_entry_indices
+            reference_records: vec![PoolReferenceRecord {
+                object_entries_starting_index: 0,
+                object_entries_count: 2,
+            }],
+        });
+
+        assert!(matches!(
+            writer.finish(&mut Cursor::new(Vec::new())),
+            Err(DpcError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn test_pool_layout_optimize() {
+        let record = PoolReferenceRecord {
+            object_entries_starting_index: 0,
+            object_entries_count: 1,
+        };
+        let entry = |reference_record_index| PoolEntry {
+            crc32: 1,
+            reference_record_index,
+        };
+
+        let mut layout = PoolLayout {
+            object_entry_indices: vec![0, 0],
+            object_entries: vec![entry(2)],
+            reference_records: vec![record, record],
+        };
+        layout.optimize().unwrap();
+        assert_eq!(layout.reference_records, vec![record]);
+        assert_eq!(layout.object_entries, vec![entry(1)]);
+
+        for reference_record_index in [0, 3] {
+            let mut layout = PoolLayout {
+                object_entry_indices: vec![0],
+                object_entries: vec![entry(reference_record_index)],
+                reference_records: vec![record, record],
+            };
+            assert!(matches!(layout.optimize(), Err(DpcError::Format(_))));
+        }
+    }
+
+    #[test]
+    fn test_corrupt_object_header() {
+        let mut object = BigFileObject::new(1, 100, vec![1, 2, 3, 4], vec![5, 6]);
+        object.header.data_size = 2;
+        let mut buffer = vec![];
+        object.write(&mut buffer).unwrap();
+        assert!(matches!(
+            BigFileObject::read(&mut Cursor::new(buffer)),
+            Err(DpcError::Parse { .. })
+        ));
+        assert!(matches!(
+            object.header.body_size(),
+            Err(DpcError::Format(_))
+        ));
+
+        let mut object = BigFileObject::new(1, 100, vec![], vec![2]);
+        object.header.data_size = u32::MAX;
+        object.header.decompressed_size = u32::MAX;
+        let mut buffer = vec![];
+        object.write(&mut buffer).unwrap();
+        assert!(matches!(
+            BigFileObject::read(&mut Cursor::new(buffer)),
+            Err(DpcError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_pool_object_header() {
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        let block = writer.add_block(0);
+        writer
+            .push(block, BigFileObject::new(1, 100, vec![], vec![2]), false)
+            .unwrap();
+        writer.pool(1);
+        let mut cursor = Cursor::new(Vec::new());
+        let header = writer.finish(&mut cursor).unwrap();
+
+        // data_size of the pool object
+        let offset = (header.pool_manifest_offset + header.pool_manifest_padded_size) as usize;
+        let mut buffer = cursor.into_inner();
+        buffer[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            BigFile::read(&mut Cursor::new(buffer.clone())),
+            Err(DpcError::Format(_))
+        ));
+        assert!(matches!(
+            BigFileIndex::new(&mut Cursor::new(buffer)).and_then(|mut index| index.open_object(1)),
+            Err(DpcError::Format(_))
+        ));
+    }
+}
@@ -10,10 +10,12 @@ use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use crc32fast::Hasher;
 use itertools::Itertools;
 
+use crate::error::DpcError;
+
 pub trait CRC32 {
-    fn hash(self: &Self, name: &[u8]) -> u32;
+    fn hash(&self, name: &[u8]) -> u32;
     fn generate_names(
-        self: &Self,
+        &self,
         input: &mut dyn Read,
         output: &mut dyn Write,
         flush: bool,
@@ -46,7 +48,7 @@ pub trait CRC32 {
     }
 
     fn generate_binary(
-        self: &Self,
+        &self,
         input: &mut dyn Read,
         output: &mut dyn Write,
         unsigned_option: bool,
@@ -143,7 +145,7 @@ const CRC32_TABLE: [u32; CRC32_TABLE_SIZE] = [
 ];
 
 impl CRC32 for AsoboCRC32 {
-    fn hash(self: &Self, name: &[u8]) -> u32 {
+    fn hash(&self, name: &[u8]) -> u32 {
         let mut hash: u32 = 0;
 
         for c in name {
@@ -158,7 +160,7 @@ impl CRC32 for AsoboCRC32 {
 pub struct AsoboCRC32Alt {}
 
 impl CRC32 for AsoboCRC32Alt {
-    fn hash(self: &Self, name: &[u8]) -> u32 {
+    fn hash(&self, name: &[u8]) -> u32 {
         let mut hash: u32 = 0;
 
         for c in name {
@@ -173,7 +175,7 @@ impl CRC32 for AsoboCRC32Alt {
 pub struct IEEECRC32 {}
 
 impl CRC32 for IEEECRC32 {
-    fn hash(self: &Self, name: &[u8]) -> u32 {
+    fn hash(&self, name: &[u8]) -> u32 {
         let mut hasher = Hasher::new();
         hasher.update(name);
         hasher.finalize()
@@ -195,7 +197,7 @@ impl CRC32SubCommand<'_> {
         CRC32SubCommand { algorithms }
     }
 
-    pub fn subcommand(self: &Self) -> App {
+    pub fn subcommand(&self) -> App<'_, '_> {
         SubCommand::with_name("crc32")
             .about("generate name files")
             .arg(
@@ -259,10 +261,10 @@ impl CRC32SubCommand<'_> {
     }
 
     pub fn execute(
-        self: &Self,
+        &self,
         matches: &ArgMatches,
         subcommand_matches: &ArgMatches,
-    ) -> Result<(), io::Error> {
+    ) -> crate::error::Result<()> {
         let binary_option = subcommand_matches.is_present("BINARY");
         let unsigned_option = subcommand_matches.is_present("UNSIGNED");
         let literal_option = subcommand_matches.is_present("LITERAL");
@@ -271,7 +273,9 @@ impl CRC32SubCommand<'_> {
         let (mut input, mut output): (Box<dyn Read>, Box<dyn Write>) = if interactive_option {
             (Box::new(io::stdin()), Box::new(io::stdout()))
         } else {
-            let input_path_string = matches.value_of_os("INPUT").unwrap();
+            let input_path_string = matches
+                .value_of_os("INPUT")
+                .ok_or_else(|| DpcError::Format("an input is required".to_string()))?;
             let input_path = Path::new(input_path_string);
             let output_writer: Box<dyn Write> = match matches.value_of_os("OUTPUT") {
                 Some(output_path_string) => {
@@ -285,45 +289,55 @@ impl CRC32SubCommand<'_> {
             (Box::new(File::open(input_path)?), output_writer)
         };
 
-        match subcommand_matches.value_of("ALGORITHM") {
-            None => panic!("Algorithm is required"),
-            Some(algorithm) => {
-                if let Some(crc32_implementation) = self.algorithms.get(algorithm) {
-                    if binary_option {
-                        let offset = match subcommand_matches.value_of("OFFSET") {
-                            None => None,
-                            Some(off) => Some(off.parse::<usize>().unwrap()),
-                        };
-                        let length = match subcommand_matches.value_of("LENGTH") {
-                            None => None,
-                            Some(len) => Some(len.parse::<usize>().unwrap()),
-                        };
-
-                        crc32_implementation.generate_binary(
-                            input.as_mut(),
-                            output.as_mut(),
-                            interactive_option,
-                            offset,
-                            length,
-                        )?;
-                    } else {
-                        crc32_implementation.generate_names(
-                            input.as_mut(),
-                            output.as_mut(),
-                            interactive_option,
-                            unsigned_option,
-                            literal_option,
-                        )?;
-                    }
-                } else {
-                    panic!("bad algorithm")
-                }
-            }
-        };
+        let algorithm = subcommand_matches
+            .value_of("ALGORITHM")
+            .ok_or_else(|| DpcError::Format("an algorithm is required".to_string()))?;
+        let crc32_implementation = self.algorithms.get(algorithm).ok_or_else(|| {
+            DpcError::Format(format!(
+                "unknown algorithm {}, expected one of {}",
+                algorithm,
+                self.algorithms.keys().sorted().join(", ")
+            ))
+        })?;
+        if binary_option {
+            let offset = parse_usize(subcommand_matches, "OFFSET")?;
+            let length = parse_usize(subcommand_matches, "LENGTH")?;
+
+            crc32_implementation.generate_binary(
+                input.as_mut(),
+                output.as_mut(),
+                interactive_option,
+                offset,
+                length,
+            )?;
+        } else {
+            crc32_implementation.generate_names(
+                input.as_mut(),
+                output.as_mut(),
+                interactive_option,
+                unsigned_option,
+                literal_option,
+            )?;
+        }
 
         Ok(())
     }
 }
 
+fn parse_usize(matches: &ArgMatches, name: &str) -> crate::error::Result<Option<usize>> {
+    matches
+        .value_of(name)
+        .map(|value| {
+            value.parse::<usize>().map_err(|_| {
+                DpcError::Format(format!(
+                    "invalid {} {}, expected a number",
+                    name.to_lowercase(),
+                    value
+                ))
+            })
+        })
+        .transpose()
+}
+
 #[cfg(test)]
 mod test {}
@@ -0,0 +1,281 @@
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use nom_derive::*;
+
+use crate::base_dpc::Options;
+use crate::bigfile::PrimaryHeader;
+use crate::error::{DpcError, Result};
+use crate::game_dpc::GameDPC;
+use crate::game_profile::{GameProfile, PROFILES};
+
+/// A profile and version picked for a BigFile by `detect`. When nothing matched exactly,
+/// `is_exact` is false and `reason` says why the closest supported version was picked
+pub struct Detection {
+    pub profile: &'static GameProfile,
+    pub version_string: &'static str,
+    pub is_exact: bool,
+    pub reason: String,
+}
+
+impl Detection {
+    /// Builds the backend, using the detected version as the effective version when inexact
+    pub fn dpc(&self, options: &Options, custom_args: &[&OsStr]) -> Result<GameDPC> {
+        let mut dpc = GameDPC::with_profile(self.profile, options, custom_args)?;
+        if !self.is_exact {
+            dpc.set_effective_version(self.version_string);
+        }
+        Ok(dpc)
+    }
+}
+
+/// Reads a BigFile's primary header and picks the profile and version that match its version
+/// string, version numbers and block types
+pub fn detect<P: AsRef<Path>>(path: &P) -> Result<Detection> {
+    let mut buffer = [0; 2048];
+    File::open(path.as_ref())?.read_exact(&mut buffer)?;
+    let header = match PrimaryHeader::parse(&buffer) {
+        Ok((_, h)) => h,
+        Err(error) => return Err(DpcError::parse("primary header", 0, &buffer, error)),
+    };
+    detect_header(&header)
+}
+
+pub fn detect_header(header: &PrimaryHeader) -> Result<Detection> {
+    // Only the first block carries the block type, later ones are written as 0
+    let first_block_type = header
+        .block_descriptions
+        .first()
+        .map(|block_description| block_description.block_type);
+
+    let mut best: Option<Candidate> = None;
+    let mut ties: Vec<&'static str> = Vec::new();
+    for profile in PROFILES.iter().copied() {
+        for (version_string, version) in profile.version_lookup.iter() {
+            let score = Score::new(header, first_block_type, version_string, *version);
+            let is_closer = match &best {
+                Some((best_score, best_profile, best_version_string, _)) => {
+                    if score == *best_score
+                        && best_profile.name != profile.name
+                        && best_version_string == version_string
+                        && !ties.contains(&profile.name)
+                    {
+                        ties.push(profile.name);
+                    }
+                    score < *best_score
+                }
+                None => true,
+            };
+            if is_closer {
+                best = Some((score, profile, version_string, *version));
+                ties.clear();
+            }
+        }
+    }
+
+    let (score, profile, version_string, (version_patch, version_minor, block_type)) =
+        best.ok_or_else(|| DpcError::UnsupportedVersion(header.version_string.clone()))?;
+
+    let is_exact = score.string_mismatch == 0 && score.field_mismatches == 0;
+    let mut reason = if is_exact {
+        format!(
+            "{} {} matches the version string, version_patch {}, version_minor {} and block type {}",
+            profile.name, version_string, version_patch, version_minor, block_type
+        )
+    } else {
+        let mut matched = Vec::new();
+        let mut differs = Vec::new();
+        for &(name, expected, actual) in &[
+            ("version_patch", version_patch, header.version_patch),
+            ("version_minor", version_minor, header.version_minor),
+        ] {
+            if expected == actual {
+                matched.push(format!("{} {}", name, expected));
+            } else {
+                differs.push(format!("{} {} (expected {})", name, actual, expected));
+            }
+        }
+        match first_block_type {
+            Some(actual) if actual != block_type => {
+                differs.push(format!("block type {} (expected {})", actual, block_type))
+            }
+            _ => matched.push(format!("block type {}", block_type)),
+        }
+        let mut reason = if score.string_mismatch == 0 {
+            let mut reason = format!(
+                "{} {} matches the version string",
+                profile.name, version_string
+            );
+            if !matched.is_empty() {
+                reason.push_str(format!(" and {}", matched.join(", ")).as_str());
+            }
+            reason
+        } else {
+            let mut reason = format!(
+                "{:?} is not a known version; the closest is {} {}",
+                header.version_string, profile.name, version_string
+            );
+            if !matched.is_empty() {
+                reason.push_str(format!(", which shares {}", matched.join(", ")).as_str());
+            } else {
+                reason.push_str(", the nearest version number");
+            }
+            reason
+        };
+        if !differs.is_empty() {
+            reason.push_str(format!("; differs in {}", differs.join(", ")).as_str());
+        }
+        reason
+    };
+    if !ties.is_empty() {
+        reason.push_str(
+            format!(
+                " (also matches {}, which uses the same version table entry)",
+                ties.join(", ")
+            )
+            .as_str(),
+        );
+    }
+
+    Ok(Detection {
+        profile,
+        version_string,
+        is_exact,
+        reason,
+    })
+}
+
+type Candidate = (Score, &'static GameProfile, &'static str, (u32, u32, u32));
+
+// Ordered so that a smaller score is a closer match
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Score {
+    string_mismatch: u32,
+    field_mismatches: u32,
+    minor_distance: u32,
+    patch_distance: u32,
+    version_number_distance: Vec<u32>,
+}
+
+impl Score {
+    fn new(
+        header: &PrimaryHeader,
+        first_block_type: Option<u32>,
+        version_string: &str,
+        (version_patch, version_minor, block_type): (u32, u32, u32),
+    ) -> Score {
+        let mut field_mismatches = 0;
+        if header.version_patch != version_patch {
+            field_mismatches += 1;
+        }
+        if header.version_minor != version_minor {
+            field_mismatches += 1;
+        }
+        if matches!(first_block_type, Some(x) if x != block_type) {
+            field_mismatches += 1;
+        }
+
+        let numbers = version_numbers(&header.version_string);
+        let version_number_distance = version_numbers(version_string)
+            .iter()
+            .zip(numbers.iter().chain(std::iter::repeat(&0)))
+            .map(|(a, b)| a.abs_diff(*b))
+            .collect();
+
+        Score {
+            string_mismatch: if header.version_string == version_string {
+                0
+            } else {
+                1
+            },
+            field_mismatches,
+            minor_distance: header.version_minor.abs_diff(version_minor),
+            patch_distance: header.version_patch.abs_diff(version_patch),
+            version_number_distance,
+        }
+    }
+}
+
+// "v1.381.67.09 - Asobo Studio - Internal Cross Technology" -> [1, 381, 67, 9]
+fn version_numbers(version_string: &str) -> Vec<u32> {
+    version_string
+        .trim_start_matches('v')
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()
+        .unwrap_or("")
+        .split('.')
+        .filter_map(|x| x.parse::<u32>().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bigfile::BlockDescription;
+
+    const VERSION: &str = "v1.381.67.09 - Asobo Studio - Internal Cross Technology";
+
+    fn header(version_string: &str, version_patch: u32, version_minor: u32) -> PrimaryHeader {
+        let block = |block_type| BlockDescription {
+            block_type,
+            object_count: 1,
+            padded_size: 2048,
+            data_size: 30,
+            working_buffer_offset: 0,
+            crc32: 0,
+        };
+        PrimaryHeader {
+            version_string: version_string.to_string(),
+            is_not_rtc: 1,
+            block_count: 2,
+            block_working_buffer_capacity_even: 30,
+            block_working_buffer_capacity_odd: 30,
+            padded_size: 2048,
+            version_patch,
+            version_minor,
+            block_descriptions: vec![block(253), block(0)],
+            pool_manifest_padded_size: 0,
+            pool_manifest_offset: 0,
+            pool_manifest_unused0: 0,
+            pool_manifest_unused1: 0,
+            pool_object_decompression_buffer_capacity: 0,
+            block_sector_padding_size: 0,
+            pool_sector_padding_size: 0,
+            file_size: 0,
+            incredi_builder_string: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_detect_exact() {
+        let detection = detect_header(&header(VERSION, 272, 380)).unwrap();
+        assert!(detection.is_exact, "{}", detection.reason);
+        assert_eq!(detection.profile.name, "FUEL");
+        assert_eq!(detection.version_string, VERSION);
+    }
+
+    #[test]
+    fn test_detect_inexact() {
+        let detection = detect_header(&header(VERSION, 272, 381)).unwrap();
+        assert!(!detection.is_exact);
+        assert_eq!(detection.version_string, VERSION);
+        assert_eq!(
+            detection.reason,
+            format!(
+                "FUEL {} matches the version string and version_patch 272, block type 253; \
+                 differs in version_minor 381 (expected 380)",
+                VERSION
+            )
+        );
+
+        let unknown = "v1.381.68.09 - Asobo Studio - Internal Cross Technology";
+        let detection = detect_header(&header(unknown, 272, 380)).unwrap();
+        assert!(!detection.is_exact);
+        assert_eq!(detection.version_string, VERSION);
+        assert!(detection
+            .reason
+            .starts_with(&format!("{:?} is not a known version", unknown)));
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::bigfile::{BigFile, BigFileObject};
+use crate::error::{DpcError, Result};
+use crate::parallel::parallel_map;
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Placement {
+    pub blocks: Vec<usize>,
+    pub is_pooled: bool,
+}
+
+impl Placement {
+    // crc32 -> placement of every object, in one pass over the block headers
+    fn all(bigfile: &BigFile) -> HashMap<u32, Placement> {
+        let mut placements: HashMap<u32, Placement> = HashMap::new();
+        for (block_index, block) in bigfile.blocks.iter().enumerate() {
+            for oh in block.object_headers.iter() {
+                let placement = placements.entry(oh.crc32).or_insert_with(|| Placement {
+                    blocks: vec![],
+                    is_pooled: bigfile.is_pooled(oh.crc32),
+                });
+                if placement.blocks.last() != Some(&block_index) {
+                    placement.blocks.push(block_index);
+                }
+            }
+        }
+        placements
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    // JSON pointer into the parsed object
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ChangedObject {
+    pub crc32: u32,
+    pub old_class_crc32: u32,
+    pub new_class_crc32: u32,
+    pub old_size: usize,
+    pub new_size: usize,
+    // None when either side could not be parsed, the bytes just differ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldChange>>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MovedObject {
+    pub crc32: u32,
+    pub old: Placement,
+    pub new: Placement,
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct BigFileDiff {
+    pub added: Vec<u32>,
+    pub removed: Vec<u32>,
+    pub changed: Vec<ChangedObject>,
+    pub moved: Vec<MovedObject>,
+}
+
+impl BigFileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.moved.is_empty()
+    }
+}
+
+// Compares two JSON values and lists the leaves that differ
+pub fn diff_json(old: &Value, new: &Value) -> Vec<FieldChange> {
+    let mut changes = vec![];
+    diff_json_at(String::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_json_at(path: String, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => diff_json_at(path, old, new, changes),
+                    (old, new) => changes.push(FieldChange {
+                        path,
+                        old: old.cloned(),
+                        new: new.cloned(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let path = format!("{}/{}", path, i);
+                match (old.get(i), new.get(i)) {
+                    (Some(old), Some(new)) => diff_json_at(path, old, new, changes),
+                    (old, new) => changes.push(FieldChange {
+                        path,
+                        old: old.cloned(),
+                        new: new.cloned(),
+                    }),
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(FieldChange {
+            path,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => (),
+    }
+}
+
+/// Lists the objects added, removed, changed and moved between blocks or into or out of the
+/// pool. Changed objects are compared after decompression. parse gets the version string of the
+/// BigFile the object comes from and its decompressed data, and returns None for classes without
+/// a format, in which case only the two sizes are reported
+pub fn diff<F>(old: &BigFile, new: &BigFile, threads: usize, parse: F) -> Result<BigFileDiff>
+where
+    F: Fn(&str, &BigFileObject, &[u8]) -> Result<Option<Value>> + Sync,
+{
+    let old_crc32s: BTreeSet<u32> = old.crc32s().copied().collect();
+    let new_crc32s: BTreeSet<u32> = new.crc32s().copied().collect();
+    let common: Vec<u32> = old_crc32s.intersection(&new_crc32s).copied().collect();
+
+    let mut bigfile_diff = BigFileDiff {
+        added: new_crc32s.difference(&old_crc32s).copied().collect(),
+        removed: old_crc32s.difference(&new_crc32s).copied().collect(),
+        ..Default::default()
+    };
+
+    let changed = parallel_map(threads, &common, |crc32| {
+        let old_object = old.object(*crc32).ok_or(DpcError::MissingObject(*crc32))?;
+        let new_object = new.object(*crc32).ok_or(DpcError::MissingObject(*crc32))?;
+        let old_data = old_object.decompressed_data()?;
+        let new_data = new_object.decompressed_data()?;
+        if old_object.header.class_crc32 == new_object.header.class_crc32
+            && old_object.class_object == new_object.class_object
+            && old_data == new_data
+        {
+            return Ok(None);
+        }
+
+        let old_value = parse(&old.header.version_string, old_object, &old_data)?;
+        let new_value = parse(&new.header.version_string, new_object, &new_data)?;
+        Ok(Some(ChangedObject {
+            crc32: *crc32,
+            old_class_crc32: old_object.header.class_crc32,
+            new_class_crc32: new_object.header.class_crc32,
+            old_size: old_object.class_object.len() + old_data.len(),
+            new_size: new_object.class_object.len() + new_data.len(),
+            fields: match (old_value, new_value) {
+                (Some(old_value), Some(new_value)) => Some(diff_json(&old_value, &new_value)),
+                _ => None,
+            },
+        }))
+    })?;
+    bigfile_diff.changed = changed.into_iter().flatten().collect();
+
+    let mut old_placements = Placement::all(old);
+    let mut new_placements = Placement::all(new);
+    for crc32 in common {
+        let old_placement = old_placements.remove(&crc32).unwrap_or_default();
+        let new_placement = new_placements.remove(&crc32).unwrap_or_default();
+        if old_placement != new_placement {
+            bigfile_diff.moved.push(MovedObject {
+                crc32,
+                old: old_placement,
+                new: new_placement,
+            });
+        }
+    }
+
+    Ok(bigfile_diff)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::bigfile::BigFileWriter;
+
+    fn bigfile(objects: &[(usize, BigFileObject)], pooled: &[u32]) -> BigFile {
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        writer.add_block(0);
+        writer.add_block(0);
+        for (block_index, object) in objects {
+            writer.push(*block_index, object.clone(), true).unwrap();
+        }
+        for crc32 in pooled {
+            writer.pool(*crc32);
+        }
+        let mut cursor = Cursor::new(Vec::new());
+        writer.finish(&mut cursor).unwrap();
+        BigFile::read(&mut cursor).unwrap()
+    }
+
+    #[test]
+    fn test_diff_json() {
+        let old = json!({"header": {"a": 1, "b": [1, 2, 3]}, "body": "x"});
+        let new = json!({"header": {"a": 2, "b": [1, 2], "c/d": true}, "body": "x"});
+        assert_eq!(
+            diff_json(&old, &new),
+            vec![
+                FieldChange {
+                    path: "/header/a".to_string(),
+                    old: Some(json!(1)),
+                    new: Some(json!(2)),
+                },
+                FieldChange {
+                    path: "/header/b/2".to_string(),
+                    old: Some(json!(3)),
+                    new: None,
+                },
+                FieldChange {
+                    path: "/header/c~1d".to_string(),
+                    old: None,
+                    new: Some(json!(true)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = bigfile(
+            &[
+                (0, BigFileObject::new(1, 100, vec![], vec![1])),
+                (0, BigFileObject::new(2, 100, vec![], vec![2])),
+                (1, BigFileObject::new(3, 200, vec![], vec![3; 64])),
+            ],
+            &[],
+        );
+        let new = bigfile(
+            &[
+                (1, BigFileObject::new(1, 100, vec![], vec![1])),
+                (0, BigFileObject::new(3, 200, vec![], vec![4; 64])),
+                (0, BigFileObject::new(4, 100, vec![], vec![5])),
+            ],
+            &[1],
+        );
+
+        let bigfile_diff = diff(&old, &new, 2, |_, object, data| {
+            Ok(match object.header.class_crc32 {
+                200 => Some(json!({ "first": data[0] })),
+                _ => None,
+            })
+        })
+        .unwrap();
+
+        assert_eq!(bigfile_diff.added, vec![4]);
+        assert_eq!(bigfile_diff.removed, vec![2]);
+        assert_eq!(bigfile_diff.changed.len(), 1);
+        assert_eq!(bigfile_diff.changed[0].crc32, 3);
+        assert_eq!(
+            bigfile_diff.changed[0].fields,
+            Some(vec![FieldChange {
+                path: "/first".to_string(),
+                old: Some(json!(3)),
+                new: Some(json!(4)),
+            }])
+        );
+        assert_eq!(
+            bigfile_diff.moved,
+            vec![
+                MovedObject {
+                    crc32: 1,
+                    old: Placement {
+                        blocks: vec![0],
+                        is_pooled: false,
+                    },
+                    new: Placement {
+                        blocks: vec![1],
+                        is_pooled: true,
+                    },
+                },
+                MovedObject {
+                    crc32: 3,
+                    old: Placement {
+                        blocks: vec![1],
+                        is_pooled: false,
+                    },
+                    new: Placement {
+                        blocks: vec![0],
+                        is_pooled: false,
+                    },
+                },
+            ]
+        );
+    }
+}
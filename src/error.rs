@@ -0,0 +1,276 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use nom::error::ErrorKind;
+
+use crate::verify::Mismatch;
+
+#[derive(Debug)]
+pub enum DpcError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Glob(String),
+    Format(String),
+    Parse {
+        what: &'static str,
+        crc32: Option<u32>,
+        block_index: Option<usize>,
+        offset: usize,
+        kind: Option<ErrorKind>,
+    },
+    UnsupportedVersion(String),
+    UnsupportedGame(String),
+    UnsupportedFormat(u32),
+    InvalidObjectPath(PathBuf),
+    OutputExists(PathBuf),
+    AmbiguousCrc32 {
+        crc32: u32,
+        paths: Vec<PathBuf>,
+    },
+    MissingObject(u32),
+    InconsistentCompress(u32),
+    AlreadyCompressed(u32),
+    AlreadyDecompressed(u32),
+    ObjectFormat {
+        crc32: u32,
+        error: Box<DpcError>,
+    },
+    VerifyFailed(Vec<Mismatch>),
+    UnsupportedManifestVersion(u64),
+    InvalidManifest {
+        path: PathBuf,
+        errors: Vec<String>,
+    },
+    Aborted,
+}
+
+pub type Result<T> = std::result::Result<T, DpcError>;
+
+impl DpcError {
+    pub fn parse(
+        what: &'static str,
+        base_offset: usize,
+        input: &[u8],
+        error: nom::Err<nom::error::Error<&[u8]>>,
+    ) -> DpcError {
+        let (offset, kind) = match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                (input.len() - e.input.len(), Some(e.code))
+            }
+            nom::Err::Incomplete(_) => (input.len(), None),
+        };
+
+        DpcError::Parse {
+            what,
+            crc32: None,
+            block_index: None,
+            offset: base_offset + offset,
+            kind,
+        }
+    }
+
+    pub fn in_block(self, block_index: usize) -> DpcError {
+        match self {
+            DpcError::Parse {
+                what,
+                crc32,
+                offset,
+                kind,
+                ..
+            } => DpcError::Parse {
+                what,
+                crc32,
+                block_index: Some(block_index),
+                offset,
+                kind,
+            },
+            e => e,
+        }
+    }
+
+    pub fn for_object(self, crc32: u32) -> DpcError {
+        match self {
+            DpcError::Parse {
+                what,
+                block_index,
+                offset,
+                kind,
+                ..
+            } => DpcError::Parse {
+                what,
+                crc32: Some(crc32),
+                block_index,
+                offset,
+                kind,
+            },
+            e => e,
+        }
+    }
+
+    pub fn is_parse(&self) -> bool {
+        match self {
+            DpcError::Parse { .. } => true,
+            DpcError::ObjectFormat { error, .. } => error.is_parse(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for DpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DpcError::Io(e) => write!(f, "{}", e),
+            DpcError::Json(e) => write!(f, "{}", e),
+            DpcError::Glob(e) => write!(f, "{}", e),
+            DpcError::Format(e) => write!(f, "{}", e),
+            DpcError::Parse {
+                what,
+                crc32,
+                block_index,
+                offset,
+                kind,
+            } => {
+                write!(f, "Failed to parse {} at offset {}", what, offset)?;
+                if let Some(kind) = kind {
+                    write!(f, " ({:?})", kind)?;
+                } else {
+                    write!(f, " (incomplete)")?;
+                }
+                if let Some(block_index) = block_index {
+                    write!(f, " on block {}", block_index)?;
+                }
+                if let Some(crc32) = crc32 {
+                    write!(f, " for crc32: {}", crc32)?;
+                }
+                Ok(())
+            }
+            DpcError::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "Unsupported version string: {}. Use -u/--unsafe to bypass this check",
+                    version
+                )
+            }
+            DpcError::UnsupportedGame(name) => {
+                let names: Vec<&str> = crate::game_profile::PROFILES
+                    .iter()
+                    .map(|profile| profile.name)
+                    .collect();
+                write!(
+                    f,
+                    "Unsupported game: {}. Expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            }
+            DpcError::UnsupportedFormat(class_crc32) => {
+                write!(f, "Unsupported format for class crc32: {}", class_crc32)
+            }
+            DpcError::InvalidObjectPath(path) => {
+                write!(f, "Invalid object path: {}", path.display())
+            }
+            DpcError::OutputExists(path) => {
+                write!(f, "Output already exists: {}", path.display())
+            }
+            DpcError::AmbiguousCrc32 { crc32, paths } => {
+                write!(f, "Ambiguous files for crc32 = {}: {:?}", crc32, paths)
+            }
+            DpcError::MissingObject(crc32) => write!(f, "No object for crc32: {}", crc32),
+            DpcError::InconsistentCompress(crc32) => {
+                write!(f, "Inconsistent compress values for crc32 {}", crc32)
+            }
+            DpcError::AlreadyCompressed(crc32) => write!(f, "{} is already compressed", crc32),
+            DpcError::AlreadyDecompressed(crc32) => {
+                write!(f, "{} is already decompressed", crc32)
+            }
+            DpcError::ObjectFormat { crc32, error } => write!(f, "{}: {}", crc32, error),
+            DpcError::VerifyFailed(mismatches) => {
+                write!(f, "Verification found {} mismatches", mismatches.len())?;
+                for mismatch in mismatches.iter() {
+                    write!(f, "\n  {}", mismatch)?;
+                }
+                Ok(())
+            }
+            DpcError::UnsupportedManifestVersion(version) => write!(
+                f,
+                "manifest_version {} is newer than this version of dpc supports ({})",
+                version,
+                crate::manifest::MANIFEST_VERSION
+            ),
+            DpcError::InvalidManifest { path, errors } => {
+                write!(f, "Invalid manifest {}", path.display())?;
+                for error in errors.iter() {
+                    write!(f, "\n  {}", error)?;
+                }
+                Ok(())
+            }
+            DpcError::Aborted => write!(f, "Aborting"),
+        }
+    }
+}
+
+impl std::error::Error for DpcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DpcError::Io(e) => Some(e),
+            DpcError::Json(e) => Some(e),
+            DpcError::ObjectFormat { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DpcError {
+    fn from(error: io::Error) -> Self {
+        DpcError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for DpcError {
+    fn from(error: serde_json::Error) -> Self {
+        DpcError::Json(error)
+    }
+}
+
+impl From<glob::PatternError> for DpcError {
+    fn from(error: glob::PatternError) -> Self {
+        DpcError::Glob(error.to_string())
+    }
+}
+
+impl From<glob::GlobError> for DpcError {
+    fn from(error: glob::GlobError) -> Self {
+        DpcError::Glob(error.to_string())
+    }
+}
+
+impl From<ddsfile::Error> for DpcError {
+    fn from(error: ddsfile::Error) -> Self {
+        DpcError::Format(error.to_string())
+    }
+}
+
+impl From<hound::Error> for DpcError {
+    fn from(error: hound::Error) -> Self {
+        DpcError::Format(error.to_string())
+    }
+}
+
+impl From<gltf::Error> for DpcError {
+    fn from(error: gltf::Error) -> Self {
+        DpcError::Format(error.to_string())
+    }
+}
+
+impl From<obj::ObjError> for DpcError {
+    fn from(error: obj::ObjError) -> Self {
+        DpcError::Format(error.to_string())
+    }
+}
+
+impl From<image::ImageError> for DpcError {
+    fn from(error: image::ImageError) -> Self {
+        DpcError::Format(error.to_string())
+    }
+}
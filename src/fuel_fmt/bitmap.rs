@@ -1,16 +1,211 @@
 use std::fs::File;
-use std::io::{Error, ErrorKind, Write};
+use std::io::{Cursor, Write};
 use std::path::Path;
 
 use binwrite::BinWrite;
 use byteorder::ByteOrder;
 use byteorder::LittleEndian;
+use image::codecs::dxt::{DXTVariant, DxtDecoder, DxtEncoder};
+use image::imageops::FilterType;
+use image::{DynamicImage, GrayAlphaImage, ImageBuffer, ImageDecoder, RgbImage, RgbaImage};
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::fuel_fmt::common::{write_option, FUELObjectFormatTrait, HasReferences};
+use crate::asobo_fmt::common::{write_option, HasReferences, ObjectFormatTrait};
+use crate::error::DpcError;
 use ddsfile::{D3DFormat, Dds};
 
+#[derive(Clone, Copy)]
+enum PixelFormat {
+    Dxt1,
+    Dxt5,
+    A8L8,
+}
+
+impl PixelFormat {
+    fn d3d_format(self) -> D3DFormat {
+        match self {
+            PixelFormat::Dxt1 => D3DFormat::DXT1,
+            PixelFormat::Dxt5 => D3DFormat::DXT5,
+            PixelFormat::A8L8 => D3DFormat::A8L8,
+        }
+    }
+
+    fn level_size(self, width: u32, height: u32) -> usize {
+        let blocks = (width as usize).div_ceil(4) * (height as usize).div_ceil(4);
+        match self {
+            PixelFormat::Dxt1 => blocks * 8,
+            PixelFormat::Dxt5 => blocks * 16,
+            PixelFormat::A8L8 => width as usize * height as usize * 2,
+        }
+    }
+
+    fn decode(self, data: &[u8], width: u32, height: u32) -> Result<DynamicImage, DpcError> {
+        let variant = match self {
+            PixelFormat::Dxt1 => DXTVariant::DXT1,
+            PixelFormat::Dxt5 => DXTVariant::DXT5,
+            PixelFormat::A8L8 => {
+                let size = self.level_size(width, height);
+                return GrayAlphaImage::from_raw(width, height, data[..size].to_vec())
+                    .map(DynamicImage::ImageLumaA8)
+                    .ok_or_else(|| DpcError::Format(String::from("truncated A8L8 bitmap")));
+            }
+        };
+
+        let block_width = width.div_ceil(4) * 4;
+        let block_height = height.div_ceil(4) * 4;
+        let decoder = DxtDecoder::new(Cursor::new(data), block_width, block_height, variant)?;
+        let mut buffer = vec![0; decoder.total_bytes() as usize];
+        decoder.read_image(&mut buffer)?;
+
+        let image =
+            match variant {
+                DXTVariant::DXT1 => RgbImage::from_raw(block_width, block_height, buffer)
+                    .map(DynamicImage::ImageRgb8),
+                _ => RgbaImage::from_raw(block_width, block_height, buffer)
+                    .map(DynamicImage::ImageRgba8),
+            }
+            .ok_or_else(|| DpcError::Format(String::from("truncated DXT bitmap")))?;
+
+        Ok(image.crop_imm(0, 0, width, height))
+    }
+
+    fn encode(self, image: &RgbaImage) -> Result<Vec<u8>, DpcError> {
+        let variant = match self {
+            PixelFormat::Dxt1 => DXTVariant::DXT1,
+            PixelFormat::Dxt5 => DXTVariant::DXT5,
+            PixelFormat::A8L8 => {
+                return Ok(DynamicImage::ImageRgba8(image.clone())
+                    .to_luma_alpha8()
+                    .into_raw())
+            }
+        };
+
+        // DXT works on whole 4x4 blocks so the small mips are padded by repeating the edge
+        let (width, height) = image.dimensions();
+        let block_width = width.div_ceil(4) * 4;
+        let block_height = height.div_ceil(4) * 4;
+        let padded: RgbaImage = ImageBuffer::from_fn(block_width, block_height, |x, y| {
+            *image.get_pixel(x.min(width - 1), y.min(height - 1))
+        });
+
+        let data = match variant {
+            DXTVariant::DXT1 => DynamicImage::ImageRgba8(padded).to_rgb8().into_raw(),
+            _ => padded.into_raw(),
+        };
+
+        let mut output = Vec::with_capacity(self.level_size(width, height));
+        DxtEncoder::new(&mut output).encode(&data, block_width, block_height, variant)?;
+        Ok(output)
+    }
+}
+
+fn mip_dimensions(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
+
+fn pixels_crc32(image: &RgbaImage) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(image.as_raw());
+    hasher.finalize()
+}
+
+// Returns the crc32 of the pixels written to data.png
+fn write_pngs(
+    format: PixelFormat,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    mip_map_count: u32,
+    output_path: &Path,
+) -> Result<Option<u32>, DpcError> {
+    let mut png_crc32 = None;
+    let mut offset = 0;
+    for level in 0..mip_map_count.max(1) {
+        let (level_width, level_height) = mip_dimensions(width, height, level);
+        let size = format.level_size(level_width, level_height);
+        if offset + size > data.len() {
+            break;
+        }
+
+        let image = format.decode(&data[offset..offset + size], level_width, level_height)?;
+        if level == 0 {
+            image.save(output_path.join("data.png"))?;
+            png_crc32 = Some(pixels_crc32(&image.to_rgba8()));
+        } else {
+            image.save(output_path.join(format!("data.mip{}.png", level)))?;
+        }
+
+        offset += size;
+    }
+
+    Ok(png_crc32)
+}
+
+// The PNG only takes over from the DDS once it differs from what unpack wrote, so edits to the
+// DDS are kept as long as the PNG is left alone
+fn read_png(
+    input_path: &Path,
+    format: PixelFormat,
+    dds: Option<&Dds>,
+    png_crc32: Option<u32>,
+) -> Result<Option<RgbaImage>, DpcError> {
+    let png_path = input_path.join("data.png");
+    if !png_path.is_file() {
+        return Ok(None);
+    }
+
+    let png = image::open(png_path)?.to_rgba8();
+
+    match png_crc32 {
+        Some(png_crc32) => {
+            if pixels_crc32(&png) == png_crc32 {
+                return Ok(None);
+            }
+        }
+        // Unpacked without a crc32, compare against the DDS instead
+        None => {
+            if let Some(dds) = dds {
+                let (width, height) = (dds.get_width(), dds.get_height());
+                if format.level_size(width, height) <= dds.data.len() {
+                    let original = format.decode(&dds.data, width, height)?.to_rgba8();
+                    if original == png {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Some(png))
+}
+
+fn encode_mips(
+    format: PixelFormat,
+    image: &RgbaImage,
+    mip_map_count: u32,
+) -> Result<Vec<u8>, DpcError> {
+    let (width, height) = image.dimensions();
+    let mut data = format.encode(image)?;
+    for level in 1..mip_map_count.max(1) {
+        let (level_width, level_height) = mip_dimensions(width, height, level);
+        let mip = image::imageops::resize(image, level_width, level_height, FilterType::Triangle);
+        data.extend(format.encode(&mip)?);
+    }
+    Ok(data)
+}
+
+fn read_dds(input_path: &Path) -> Result<Option<Dds>, DpcError> {
+    let dds_path = input_path.join("data.dds");
+    if !dds_path.is_file() {
+        return Ok(None);
+    }
+
+    let mut dds_file = File::open(dds_path)?;
+    Ok(Some(Dds::read(&mut dds_file)?))
+}
+
 #[derive(BinWrite)]
 #[binwrite(little)]
 // https://docs.microsoft.com/en-us/windows/win32/direct3ddds/dds-header
@@ -38,6 +233,16 @@ struct BitmapZHeader {
     u4: u8,
 }
 
+impl BitmapZHeader {
+    fn pixel_format(&self) -> PixelFormat {
+        if self.dxt_version1 == 14 {
+            PixelFormat::Dxt1
+        } else {
+            PixelFormat::Dxt5
+        }
+    }
+}
+
 impl HasReferences for BitmapZHeader {
     fn hard_links(&self) -> Vec<u32> {
         vec![]
@@ -51,6 +256,9 @@ impl HasReferences for BitmapZHeader {
 #[derive(Serialize, Deserialize)]
 struct BitmapObject {
     bitmap_header: BitmapZHeader,
+    // crc32 of the pixels unpack wrote to data.png
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    png_crc32: Option<u32>,
 }
 
 impl HasReferences for BitmapObject {
@@ -78,6 +286,18 @@ struct BitmapZHeaderAlternate {
     zero1: u16,
 }
 
+impl BitmapZHeaderAlternate {
+    fn pixel_format(&self) -> PixelFormat {
+        if self.dxt_version0 == 7 {
+            PixelFormat::A8L8
+        } else if self.dxt_version0 == 14 {
+            PixelFormat::Dxt1
+        } else {
+            PixelFormat::Dxt5
+        }
+    }
+}
+
 impl HasReferences for BitmapZHeaderAlternate {
     fn hard_links(&self) -> Vec<u32> {
         vec![]
@@ -124,36 +344,56 @@ impl HasReferences for BitmapZAlternate {
 struct BitmapObjectAlternate {
     bitmap_header: BitmapZHeaderAlternate,
     bitmap: BitmapZAlternate,
+    // crc32 of the pixels unpack wrote to data.png
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    png_crc32: Option<u32>,
 }
 
-pub struct BitmapObjectFormat;
+pub struct BitmapObjectFormat {
+    all_mips: bool,
+}
 
 impl BitmapObjectFormat {
-    pub fn new<'a>() -> &'a Self {
-        &Self {}
+    pub fn new(all_mips: bool) -> &'static Self {
+        static FORMATS: [BitmapObjectFormat; 2] = [
+            BitmapObjectFormat { all_mips: false },
+            BitmapObjectFormat { all_mips: true },
+        ];
+        &FORMATS[all_mips as usize]
     }
 }
 
-impl FUELObjectFormatTrait for BitmapObjectFormat {
+impl ObjectFormatTrait for BitmapObjectFormat {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
-        let dds_path = input_path.join("data.dds");
-        let mut dds_file = File::open(dds_path)?;
-
-        let dds = Dds::read(&mut dds_file).unwrap();
-
         let mut object: BitmapObject = serde_json::from_reader(json_file)?;
-        object.bitmap_header.width = dds.get_width();
-        object.bitmap_header.height = dds.get_height();
-        object.bitmap_header.write(header)?;
-        dds.data.write(body)?;
+        let format = object.bitmap_header.pixel_format();
+
+        let dds = read_dds(input_path)?;
+        if let Some(png) = read_png(input_path, format, dds.as_ref(), object.png_crc32)? {
+            let data = encode_mips(format, &png, object.bitmap_header.mip_map_count as u32)?;
+            object.bitmap_header.width = png.width();
+            object.bitmap_header.height = png.height();
+            object.bitmap_header.data_size = data.len() as u32;
+            object.bitmap_header.write(header)?;
+            data.write(body)?;
+        } else {
+            let dds = match dds {
+                Some(dds) => dds,
+                None => Dds::read(&mut File::open(input_path.join("data.dds"))?)?,
+            };
+            object.bitmap_header.width = dds.get_width();
+            object.bitmap_header.height = dds.get_height();
+            object.bitmap_header.write(header)?;
+            dds.data.write(body)?;
+        }
 
         Ok((
             object.bitmap_header.hard_links(),
@@ -162,17 +402,17 @@ impl FUELObjectFormatTrait for BitmapObjectFormat {
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
-        let bitmap_header = match BitmapZHeader::parse(&header) {
+        let bitmap_header = match BitmapZHeader::parse(header) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
         };
 
         let dds_path = output_path.join("data.dds");
@@ -182,29 +422,52 @@ impl FUELObjectFormatTrait for BitmapObjectFormat {
             bitmap_header.height,
             bitmap_header.width,
             None,
-            if bitmap_header.dxt_version1 == 14 {
-                D3DFormat::DXT1
-            } else {
-                D3DFormat::DXT5
-            },
+            bitmap_header.pixel_format().d3d_format(),
             Some(bitmap_header.mip_map_count as u32),
             None,
-        )
-        .unwrap();
+        )?;
 
         dds.data = Vec::from(body);
 
-        dds.write(&mut output_dds_file).unwrap();
+        dds.write(&mut output_dds_file)?;
 
-        let object = BitmapObject { bitmap_header };
+        let png_crc32 = write_pngs(
+            bitmap_header.pixel_format(),
+            body,
+            bitmap_header.width,
+            bitmap_header.height,
+            if self.all_mips {
+                bitmap_header.mip_map_count as u32
+            } else {
+                1
+            },
+            output_path,
+        )?;
+
+        let object = BitmapObject {
+            bitmap_header,
+            png_crc32,
+        };
 
-        output_file.write(serde_json::to_string_pretty(&object)?.as_bytes())?;
+        output_file.write_all(serde_json::to_string_pretty(&object)?.as_bytes())?;
 
         Ok((
             object.bitmap_header.hard_links(),
             object.bitmap_header.soft_links(),
         ))
     }
+
+    fn to_json(&self, header: &[u8], _body: &[u8]) -> Result<Value, DpcError> {
+        let bitmap_header = match BitmapZHeader::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        Ok(serde_json::to_value(BitmapObject {
+            bitmap_header,
+            png_crc32: None,
+        })?)
+    }
 }
 
 pub struct BitmapObjectFormatAlt;
@@ -215,13 +478,13 @@ impl BitmapObjectFormatAlt {
     }
 }
 
-impl FUELObjectFormatTrait for BitmapObjectFormatAlt {
+impl ObjectFormatTrait for BitmapObjectFormatAlt {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
@@ -229,18 +492,28 @@ impl FUELObjectFormatTrait for BitmapObjectFormatAlt {
 
         object.bitmap_header.write(header)?;
 
-        let dds_path = input_path.join("data.dds");
-        let mut dds_file = File::open(dds_path)?;
-
-        let dds = Dds::read(&mut dds_file).unwrap();
-
-        object.bitmap.width = dds.get_width();
-        object.bitmap.height = dds.get_height();
+        let format = object.bitmap_header.pixel_format();
+
+        let dds = read_dds(input_path)?;
+        let data = if let Some(png) = read_png(input_path, format, dds.as_ref(), object.png_crc32)?
+        {
+            object.bitmap.width = png.width();
+            object.bitmap.height = png.height();
+            format.encode(&png)?
+        } else {
+            let dds = match dds {
+                Some(dds) => dds,
+                None => Dds::read(&mut File::open(input_path.join("data.dds"))?)?,
+            };
+            object.bitmap.width = dds.get_width();
+            object.bitmap.height = dds.get_height();
+            dds.data
+        };
 
         object.bitmap.data.clear();
         object.bitmap.write(body)?;
 
-        dds.data.write(body).unwrap();
+        data.write(body)?;
 
         Ok((
             object.bitmap_header.hard_links(),
@@ -249,22 +522,22 @@ impl FUELObjectFormatTrait for BitmapObjectFormatAlt {
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
-        let bitmap_header = match BitmapZHeaderAlternate::parse(&header) {
+        let bitmap_header = match BitmapZHeaderAlternate::parse(header) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
         };
 
         let bitmap = match BitmapZAlternate::parse(body) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
         };
 
         let dds_path = output_path.join("data.dds");
@@ -274,32 +547,157 @@ impl FUELObjectFormatTrait for BitmapObjectFormatAlt {
             bitmap.height,
             bitmap.width,
             None,
-            if bitmap_header.dxt_version0 == 7 {
-                D3DFormat::A8L8
-            } else if bitmap_header.dxt_version0 == 14 {
-                D3DFormat::DXT1
-            } else {
-                D3DFormat::DXT5
-            },
+            bitmap_header.pixel_format().d3d_format(),
             Some(0),
             None,
-        )
-        .unwrap();
+        )?;
 
         dds.data = bitmap.data.clone();
 
-        dds.write(&mut output_dds_file).unwrap();
+        dds.write(&mut output_dds_file)?;
+
+        let png_crc32 = write_pngs(
+            bitmap_header.pixel_format(),
+            &bitmap.data,
+            bitmap.width,
+            bitmap.height,
+            1,
+            output_path,
+        )?;
 
         let object = BitmapObjectAlternate {
             bitmap_header,
             bitmap,
+            png_crc32,
         };
 
-        output_file.write(serde_json::to_string_pretty(&object)?.as_bytes())?;
+        output_file.write_all(serde_json::to_string_pretty(&object)?.as_bytes())?;
 
         Ok((
             object.bitmap_header.hard_links(),
             object.bitmap_header.soft_links(),
         ))
     }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        let bitmap_header = match BitmapZHeaderAlternate::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        let bitmap = match BitmapZAlternate::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
+
+        Ok(serde_json::to_value(BitmapObjectAlternate {
+            bitmap_header,
+            bitmap,
+            png_crc32: None,
+        })?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use image::{GenericImageView, Rgba};
+    use tempdir::TempDir;
+
+    use super::*;
+
+    // Black and white halves, exact in every pixel format
+    fn image(width: u32, height: u32) -> RgbaImage {
+        ImageBuffer::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                Rgba([0, 0, 0, 255])
+            } else {
+                Rgba([255, 255, 255, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn test_png_roundtrip() {
+        // Not a multiple of the 4x4 DXT block size
+        let (width, height, mip_map_count) = (6, 5, 3);
+        let original = image(width, height);
+
+        for format in [PixelFormat::Dxt1, PixelFormat::Dxt5, PixelFormat::A8L8] {
+            let data = encode_mips(format, &original, mip_map_count).unwrap();
+            let sizes: Vec<usize> = (0..mip_map_count)
+                .map(|level| {
+                    let (level_width, level_height) = mip_dimensions(width, height, level);
+                    format.level_size(level_width, level_height)
+                })
+                .collect();
+            assert_eq!(data.len(), sizes.iter().sum::<usize>());
+
+            let dir = TempDir::new("dpc-bitmap").unwrap();
+            write_pngs(format, &data, width, height, mip_map_count, dir.path()).unwrap();
+            let png = read_png(dir.path(), format, None, None).unwrap().unwrap();
+            assert_eq!(png.dimensions(), (width, height));
+            assert!(png == original);
+
+            for level in 1..mip_map_count {
+                let mip = image::open(dir.path().join(format!("data.mip{}.png", level))).unwrap();
+                assert_eq!(mip.dimensions(), mip_dimensions(width, height, level));
+            }
+            assert!(!dir
+                .path()
+                .join(format!("data.mip{}.png", mip_map_count))
+                .exists());
+        }
+    }
+
+    #[test]
+    fn test_pack_source() {
+        let (width, height) = (8, 8);
+        let format = PixelFormat::Dxt5;
+        let bitmap_header = BitmapZHeader {
+            friendly_name_crc32: 1,
+            dw_caps2: 0,
+            width,
+            height,
+            data_size: 0,
+            u1: 0,
+            bitmap_type: 0,
+            zero: 0,
+            u7: 0.0,
+            dxt_version0: 0,
+            mip_map_count: 1,
+            u2: 0,
+            u3: 0,
+            dxt_version1: 0,
+            u4: 0,
+        };
+        let mut header = vec![];
+        bitmap_header.write(&mut header).unwrap();
+        let body = format.encode(&image(width, height)).unwrap();
+
+        let bitmap_format = BitmapObjectFormat::new(false);
+        let pack = |dir: &Path| {
+            let (mut header, mut body) = (vec![], vec![]);
+            bitmap_format.pack(dir, &mut header, &mut body).unwrap();
+            (BitmapZHeader::parse(&header).unwrap().1, body)
+        };
+
+        let dir = TempDir::new("dpc-bitmap").unwrap();
+        bitmap_format.unpack(&header, &body, dir.path()).unwrap();
+        assert_eq!(pack(dir.path()).1, body);
+
+        // An edited DDS is kept while the PNG is left as unpack wrote it
+        let mut dds = Dds::read(&mut File::open(dir.path().join("data.dds")).unwrap()).unwrap();
+        let edited: RgbaImage = ImageBuffer::from_pixel(width, height, Rgba([255, 0, 0, 255]));
+        dds.data = format.encode(&edited).unwrap();
+        dds.write(&mut File::create(dir.path().join("data.dds")).unwrap())
+            .unwrap();
+        assert_eq!(pack(dir.path()).1, dds.data);
+
+        // An edited PNG takes over, along with its dimensions
+        image(4, 16).save(dir.path().join("data.png")).unwrap();
+        let (packed_header, packed_body) = pack(dir.path());
+        assert_eq!((packed_header.width, packed_header.height), (4, 16));
+        assert_eq!(packed_header.data_size as usize, packed_body.len());
+        assert_eq!(packed_body, format.encode(&image(4, 16)).unwrap());
+    }
 }
@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    FUELObjectFormat, HasReferences, PascalArray, PascalStringNULL, ResourceObjectZ,
+use crate::asobo_fmt::common::{
+    HasReferences, ObjectFormat, PascalArray, PascalStringNULL, ResourceObjectZ,
 };
 
 #[derive(BinWrite)]
@@ -37,4 +37,4 @@ impl HasReferences for GameObjZ {
     }
 }
 
-pub type GameObjObjectFormat = FUELObjectFormat<ResourceObjectZ, GameObjZ>;
+pub type GameObjObjectFormat = ObjectFormat<ResourceObjectZ, GameObjZ>;
@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    write_option, FUELObjectFormat, FixedVec, HasReferences, ResourceObjectZ, Vec3f, Vec4f,
+use crate::asobo_fmt::common::{
+    write_option, FixedVec, HasReferences, ObjectFormat, ResourceObjectZ, Vec3f, Vec4f,
 };
 
 #[derive(BinWrite)]
@@ -133,6 +133,6 @@ impl HasReferences for MaterialZAltAlt {
     }
 }
 
-pub type MaterialObjectFormat = FUELObjectFormat<ResourceObjectZ, MaterialZ>;
-pub type MaterialObjectFormatAlt = FUELObjectFormat<ResourceObjectZ, MaterialZAlt>;
-pub type MaterialObjectFormatAltAlt = FUELObjectFormat<ResourceObjectZ, MaterialZAltAlt>;
+pub type MaterialObjectFormat = ObjectFormat<ResourceObjectZ, MaterialZ>;
+pub type MaterialObjectFormatAlt = ObjectFormat<ResourceObjectZ, MaterialZAlt>;
+pub type MaterialObjectFormatAltAlt = ObjectFormat<ResourceObjectZ, MaterialZAltAlt>;
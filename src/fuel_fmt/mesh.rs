@@ -3,13 +3,21 @@ use ::nom::IResult;
 use binwrite::{BinWrite, WriterOption};
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
 use std::io::Write;
+use std::marker::PhantomData;
+use std::path::Path;
 
-use crate::fuel_fmt::common::{
-    DynBox, DynSphere, FUELObjectFormat, FadeDistances, FixedVec, HasReferences, Mat4f,
-    NumeratorFloat, PascalArray, PascalString, Quat, RangeBeginEnd, RangeBeginSize, Vec2f, Vec3,
-    Vec3f, Vec4f, VertexVectorComponent,
+use crate::asobo_fmt::common::{
+    DynBox, DynSphere, FadeDistances, FixedVec, HasReferences, Mat4f, NumeratorFloat, ObjectFormat,
+    ObjectFormatTrait, PascalArray, PascalString, Quat, RangeBeginEnd, RangeBeginSize, SphereZ,
+    Vec2f, Vec3, Vec3f, Vec4f, VertexVectorComponent,
 };
+use crate::error::DpcError;
+use crate::glb::{self, GlbBuilder, GlbVertices, ImportedPrimitive};
+use crate::names::NameTable;
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -161,9 +169,10 @@ impl VertexBufferData {
                     VertexBufferData::VertexLayoutPositionCase(parse_result.1),
                 ))
             }
-            _ => {
-                panic!("Invalid vertex size")
-            }
+            _ => Err(nom::Err::Error(nom::error::Error::new(
+                i,
+                nom::error::ErrorKind::Verify,
+            ))),
         }
     }
 }
@@ -682,7 +691,1153 @@ impl HasReferences for MeshZAltAltAlt {
     }
 }
 
-pub type MeshObjectFormat = FUELObjectFormat<MeshZHeader, MeshZ>;
-pub type MeshObjectFormatAlt = FUELObjectFormat<MeshZHeaderAlt, MeshZAlt>;
-pub type MeshObjectFormatAltAlt = FUELObjectFormat<MeshZHeaderAltAlt, MeshZAltAlt>;
-pub type MeshObjectFormatAltAltAlt = FUELObjectFormat<MeshZHeaderAltAlt, MeshZAltAltAlt>;
+fn vertex_vector(vector: &VertexVector3u8) -> [f32; 3] {
+    glb::normalize([
+        -((vector.data[0].data as f32 / 255.0) * 2.0 - 1.0),
+        -((vector.data[1].data as f32 / 255.0) * 2.0 - 1.0),
+        -((vector.data[2].data as f32 / 255.0) * 2.0 - 1.0),
+    ])
+}
+
+fn vec3f(vector: &Vec3f) -> [f32; 3] {
+    [vector.data[0], vector.data[1], vector.data[2]]
+}
+
+fn vec2f(vector: &Vec2f) -> [f32; 2] {
+    [vector.data[0], vector.data[1]]
+}
+
+fn to_vertex_vector(vector: [f32; 3]) -> VertexVector3u8 {
+    FixedVec {
+        data: vector
+            .iter()
+            .map(|x| VertexVectorComponent {
+                data: ((1.0 - x) / 2.0 * 255.0).round().clamp(0.0, 255.0) as u8,
+            })
+            .collect(),
+    }
+}
+
+fn to_vec3f(vector: [f32; 3]) -> Vec3f {
+    FixedVec {
+        data: vector.to_vec(),
+    }
+}
+
+fn to_vec2f(vector: [f32; 2]) -> Vec2f {
+    FixedVec {
+        data: vector.to_vec(),
+    }
+}
+
+impl VertexBufferData {
+    fn glb_vertices(&self) -> GlbVertices {
+        let mut vertices = GlbVertices::default();
+        match self {
+            VertexBufferData::VertexLayout4BlendCase(data) => {
+                for vertex in data {
+                    let tangent = vertex_vector(&vertex.tangent);
+                    vertices.positions.push(vec3f(&vertex.position));
+                    vertices.normals.push(vertex_vector(&vertex.normal));
+                    vertices
+                        .tangents
+                        .push([tangent[0], tangent[1], tangent[2], 1.0]);
+                    vertices.uvs.push(vec2f(&vertex.uv));
+                    vertices.joints.push([
+                        vertex.blend_indies.data[0].index as u16,
+                        vertex.blend_indies.data[1].index as u16,
+                        vertex.blend_indies.data[2].index as u16,
+                        vertex.blend_indies.data[3].index as u16,
+                    ]);
+                    vertices.weights.push(glb::normalize_weights([
+                        vertex.blend_weights.data[0],
+                        vertex.blend_weights.data[1],
+                        vertex.blend_weights.data[2],
+                        vertex.blend_weights.data[3],
+                    ]));
+                }
+            }
+            VertexBufferData::VertexLayout1BlendCase(data) => {
+                for vertex in data {
+                    let tangent = vertex_vector(&vertex.tangent);
+                    vertices.positions.push(vec3f(&vertex.position));
+                    vertices.normals.push(vertex_vector(&vertex.normal));
+                    vertices
+                        .tangents
+                        .push([tangent[0], tangent[1], tangent[2], 1.0]);
+                    vertices.uvs.push(vec2f(&vertex.uv));
+                    vertices
+                        .joints
+                        .push([vertex.blend_index.index as u16, 0, 0, 0]);
+                    vertices.weights.push(glb::normalize_weights([
+                        vertex.blend_weight,
+                        0.0,
+                        0.0,
+                        0.0,
+                    ]));
+                }
+            }
+            VertexBufferData::VertexLayoutNoBlendCase(data) => {
+                for vertex in data {
+                    let tangent = vertex_vector(&vertex.tangent);
+                    vertices.positions.push(vec3f(&vertex.position));
+                    vertices.normals.push(vertex_vector(&vertex.normal));
+                    vertices
+                        .tangents
+                        .push([tangent[0], tangent[1], tangent[2], 1.0]);
+                    vertices.uvs.push(vec2f(&vertex.uv));
+                    vertices.lightmap_uvs.push(vec2f(&vertex.luv));
+                }
+            }
+            VertexBufferData::VertexLayoutPositionCase(data) => {
+                for vertex in data {
+                    vertices.positions.push(vec3f(&vertex.position));
+                }
+            }
+        }
+        vertices
+    }
+
+    fn empty_like(&self) -> VertexBufferData {
+        match self {
+            VertexBufferData::VertexLayout4BlendCase(_) => {
+                VertexBufferData::VertexLayout4BlendCase(Vec::new())
+            }
+            VertexBufferData::VertexLayout1BlendCase(_) => {
+                VertexBufferData::VertexLayout1BlendCase(Vec::new())
+            }
+            VertexBufferData::VertexLayoutNoBlendCase(_) => {
+                VertexBufferData::VertexLayoutNoBlendCase(Vec::new())
+            }
+            VertexBufferData::VertexLayoutPositionCase(_) => {
+                VertexBufferData::VertexLayoutPositionCase(Vec::new())
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            VertexBufferData::VertexLayout4BlendCase(data) => data.len(),
+            VertexBufferData::VertexLayout1BlendCase(data) => data.len(),
+            VertexBufferData::VertexLayoutNoBlendCase(data) => data.len(),
+            VertexBufferData::VertexLayoutPositionCase(data) => data.len(),
+        }
+    }
+
+    fn vertex_size(&self) -> u16 {
+        match self {
+            VertexBufferData::VertexLayout4BlendCase(_) => 60,
+            VertexBufferData::VertexLayout1BlendCase(_) => 48,
+            VertexBufferData::VertexLayoutNoBlendCase(_) => 36,
+            VertexBufferData::VertexLayoutPositionCase(_) => 12,
+        }
+    }
+
+    fn push_vertices(&mut self, vertices: &GlbVertices) {
+        for i in 0..vertices.positions.len() {
+            let position = to_vec3f(vertices.positions[i]);
+            let tangent = vertices.tangents[i];
+            let tangent = to_vertex_vector([tangent[0], tangent[1], tangent[2]]);
+            let normal = to_vertex_vector(vertices.normals[i]);
+            let uv = to_vec2f(vertices.uvs[i]);
+            match self {
+                VertexBufferData::VertexLayout4BlendCase(data) => {
+                    data.push(VertexLayout4Blend {
+                        position,
+                        tangent,
+                        pad0: 0,
+                        normal,
+                        pad1: 0,
+                        uv,
+                        blend_indies: FixedVec {
+                            data: vertices.joints[i]
+                                .iter()
+                                .map(|x| VertexBlendIndex { index: *x as f32 })
+                                .collect(),
+                        },
+                        blend_weights: FixedVec {
+                            data: vertices.weights[i].to_vec(),
+                        },
+                    });
+                }
+                VertexBufferData::VertexLayout1BlendCase(data) => {
+                    data.push(VertexLayout1Blend {
+                        position,
+                        tangent,
+                        pad0: 0,
+                        normal,
+                        pad1: 0,
+                        uv,
+                        blend_index: VertexBlendIndex {
+                            index: vertices.joints[i][0] as f32,
+                        },
+                        pad2: FixedVec { data: vec![0; 3] },
+                        blend_weight: vertices.weights[i][0],
+                    });
+                }
+                VertexBufferData::VertexLayoutNoBlendCase(data) => {
+                    data.push(VertexLayoutNoBlend {
+                        position,
+                        tangent,
+                        pad0: 0,
+                        normal,
+                        pad1: 0,
+                        uv,
+                        luv: to_vec2f(vertices.lightmap_uvs[i]),
+                    });
+                }
+                VertexBufferData::VertexLayoutPositionCase(data) => {
+                    data.push(VertexLayoutPosition { position });
+                }
+            }
+        }
+    }
+}
+
+fn resolve_material_name(material_name: u32, material_crc32s: &[u32]) -> u32 {
+    if material_crc32s.contains(&material_name) {
+        material_name
+    } else if let Some(crc32) = material_crc32s.get(material_name as usize) {
+        *crc32
+    } else {
+        material_name
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn glb_nodes(
+    builder: &mut GlbBuilder,
+    vertex_buffers: &[VertexBufferExt],
+    index_buffers: &[IndexBufferExt],
+    vertex_groups: &[VertexGroup],
+    strip_vertices: GlbVertices,
+    strips: &[Strip],
+    material_crc32s: &[u32],
+    names: &NameTable,
+) -> Vec<usize> {
+    let mut nodes = Vec::new();
+
+    let vertices: Vec<GlbVertices> = vertex_buffers
+        .iter()
+        .map(|vertex_buffer| vertex_buffer.vertices.glb_vertices())
+        .collect();
+
+    let mut primitives = Vec::new();
+    if !vertex_groups.is_empty() {
+        for vertex_group in vertex_groups {
+            let vertices = match vertices.get(vertex_group.vertex_buffer_index as usize) {
+                Some(vertices) if !vertices.positions.is_empty() => vertices,
+                _ => continue,
+            };
+            let index_buffer = match index_buffers.get(vertex_group.index_buffer_index as usize) {
+                Some(index_buffer) => index_buffer,
+                None => continue,
+            };
+            let begin = vertex_group.index_buffer_index_begin as usize;
+            let end = begin + vertex_group.face_count as usize * 3;
+            if begin == end || end > index_buffer.indices.len() {
+                continue;
+            }
+
+            // Each group only gets the vertices its indices reach so import can append them
+            // group by group without duplicating the shared buffer
+            let group_indices = &index_buffer.indices[begin..end];
+            let first = *group_indices.iter().min().unwrap_or(&0) as usize;
+            let last = *group_indices.iter().max().unwrap_or(&0) as usize;
+            if last >= vertices.positions.len() {
+                continue;
+            }
+            let attributes = builder.push_vertices(&vertices.slice(first..last + 1));
+
+            let indices: Vec<u32> = group_indices
+                .iter()
+                .map(|x| (*x as usize - first) as u32)
+                .collect();
+            let indices = builder.push_indices(&indices);
+            let material = if vertex_group.material_index >= 0 {
+                material_crc32s
+                    .get(vertex_group.material_index as usize)
+                    .map(|crc32| builder.material(*crc32, names))
+            } else {
+                None
+            };
+            primitives.push(GlbBuilder::primitive(
+                &attributes,
+                Some(indices),
+                material,
+                glb::TRIANGLES,
+            ));
+        }
+    } else {
+        for (vertices, index_buffer) in vertices.iter().zip(index_buffers) {
+            if vertices.positions.is_empty() || index_buffer.indices.is_empty() {
+                continue;
+            }
+            let attributes = builder.push_vertices(vertices);
+            let indices: Vec<u32> = index_buffer.indices.iter().map(|x| *x as u32).collect();
+            let indices = builder.push_indices(&indices);
+            primitives.push(GlbBuilder::primitive(
+                &attributes,
+                Some(indices),
+                None,
+                glb::TRIANGLES,
+            ));
+        }
+    }
+
+    if !primitives.is_empty() {
+        let mesh = builder.add_mesh("mesh", primitives);
+        nodes.push(builder.add_node(json!({ "name": "mesh", "mesh": mesh })));
+    }
+
+    if !strip_vertices.positions.is_empty() && !strips.is_empty() {
+        let attributes = builder.push_vertices(&strip_vertices);
+        let mut primitives = Vec::new();
+        for strip in strips {
+            if strip.strip_vertices_indices.data.is_empty() {
+                continue;
+            }
+            let indices: Vec<u32> = strip
+                .strip_vertices_indices
+                .data
+                .iter()
+                .map(|x| *x as u32)
+                .collect();
+            let indices = builder.push_indices(&indices);
+            let material = builder.material(
+                resolve_material_name(strip.material_name, material_crc32s),
+                names,
+            );
+            primitives.push(GlbBuilder::primitive(
+                &attributes,
+                Some(indices),
+                Some(material),
+                glb::TRIANGLE_STRIP,
+            ));
+        }
+
+        if !primitives.is_empty() {
+            let mesh = builder.add_mesh("strips", primitives);
+            nodes.push(builder.add_node(json!({ "name": "strips", "mesh": mesh })));
+        }
+    }
+
+    nodes
+}
+
+fn import_buffers(
+    primitives: &[ImportedPrimitive],
+    vertex_buffers: &mut [VertexBufferExt],
+    index_buffers: &mut [IndexBufferExt],
+    vertex_groups: &mut Vec<VertexGroup>,
+    material_crc32s: &[u32],
+) -> Result<(), DpcError> {
+    if vertex_buffers.is_empty() || index_buffers.is_empty() {
+        return Err(DpcError::Format(String::from(
+            "cannot import geometry into a mesh without vertex and index buffers",
+        )));
+    }
+
+    let mut new_vertices: Vec<VertexBufferData> = vertex_buffers
+        .iter()
+        .map(|x| x.vertices.empty_like())
+        .collect();
+    let mut new_indices: Vec<Vec<u16>> = vec![Vec::new(); index_buffers.len()];
+
+    let mut old_vertex_groups = std::mem::take(vertex_groups);
+    let mut unuseds1: Vec<Option<Vec<MeshZVertexGroupUnused1>>> = Vec::new();
+    for vertex_group in old_vertex_groups.iter_mut() {
+        unuseds1.push(Some(std::mem::take(&mut vertex_group.unuseds1.data)));
+    }
+
+    for (i, primitive) in primitives.iter().enumerate() {
+        // Each primitive reuses the buffers and flags of the group it was exported from
+        let template = old_vertex_groups.get(i).or(old_vertex_groups.first());
+        let (vertex_buffer_index, index_buffer_index) = match template {
+            Some(template) => (
+                template.vertex_buffer_index as usize,
+                template.index_buffer_index as usize,
+            ),
+            None => (i.min(new_vertices.len() - 1), i.min(new_indices.len() - 1)),
+        };
+        if vertex_buffer_index >= new_vertices.len() || index_buffer_index >= new_indices.len() {
+            return Err(DpcError::Format(format!(
+                "vertex group {} references a missing buffer",
+                i
+            )));
+        }
+
+        let vertices = &mut new_vertices[vertex_buffer_index];
+        let indices = &mut new_indices[index_buffer_index];
+        let base = vertices.len();
+        let vertex_count = primitive.vertices.positions.len();
+        if base + vertex_count > u16::MAX as usize + 1 {
+            return Err(DpcError::Format(format!(
+                "vertex buffer {} exceeds {} vertices",
+                vertex_buffer_index,
+                u16::MAX as usize + 1
+            )));
+        }
+
+        let index_begin = indices.len();
+        for index in primitive.indices.iter() {
+            if *index as usize >= vertex_count {
+                return Err(DpcError::Format(format!(
+                    "index {} out of range in primitive {}",
+                    index, i
+                )));
+            }
+            indices.push((base + *index as usize) as u16);
+        }
+        vertices.push_vertices(&primitive.vertices);
+
+        if let Some(template) = template {
+            let material_index = primitive
+                .material
+                .and_then(|crc32| material_crc32s.iter().position(|x| *x == crc32))
+                .map(|x| x as i16)
+                .unwrap_or(template.material_index);
+
+            // Keep whichever end convention the original group used
+            let end_bias = template.vertex_count as i64
+                - (template.vertex_buffer_range.end as i64
+                    - template.vertex_buffer_range.begin as i64);
+            let end = ((base + vertex_count) as i64 - end_bias)
+                .max(0)
+                .min(u16::MAX as i64);
+
+            vertex_groups.push(VertexGroup {
+                vertex_buffer_index: vertex_buffer_index as u32,
+                index_buffer_index: index_buffer_index as u32,
+                quad_range: RangeBeginSize {
+                    begin: template.quad_range.begin,
+                    size: template.quad_range.size,
+                },
+                flags: template.flags,
+                vertex_buffer_range: RangeBeginEnd {
+                    begin: base as u16,
+                    end: end as u16,
+                },
+                vertex_count: vertex_count as u32,
+                index_buffer_index_begin: index_begin as u32,
+                face_count: (primitive.indices.len() / 3) as u32,
+                zero: template.zero,
+                vertex_buffer_range_begin_or_zero: if template.vertex_buffer_range_begin_or_zero
+                    == 0
+                {
+                    0
+                } else {
+                    base as u32
+                },
+                vertex_size: vertices.vertex_size(),
+                material_index,
+                unuseds1: PascalArray {
+                    data: unuseds1
+                        .get_mut(i)
+                        .and_then(Option::take)
+                        .unwrap_or_default(),
+                },
+            });
+        }
+    }
+
+    for (vertex_buffer, vertices) in vertex_buffers.iter_mut().zip(new_vertices) {
+        vertex_buffer.vertex_count = vertices.len() as u32;
+        vertex_buffer.vertices = vertices;
+    }
+
+    for (index_buffer, indices) in index_buffers.iter_mut().zip(new_indices) {
+        index_buffer.index_count = indices.len() as u32;
+        index_buffer.indices = indices;
+    }
+
+    Ok(())
+}
+
+fn short_vec_weird(position: [f32; 3]) -> Option<ShortVecWeird> {
+    let mut data = Vec::with_capacity(3);
+    for component in position.iter() {
+        let value = (component * 1024.0).round();
+        if value < i16::MIN as f32 || value > i16::MAX as f32 {
+            return None;
+        }
+        data.push(NumeratorFloat { data: value as i16 });
+    }
+    Some(FixedVec { data })
+}
+
+fn collision_node(
+    faces: &mut [([u16; 3], [f32; 3], [f32; 3])],
+    begin: usize,
+    aabbs: &mut Vec<CollisionAABB>,
+) -> usize {
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for (_, face_min, face_max) in faces.iter() {
+        for i in 0..3 {
+            min[i] = min[i].min(face_min[i]);
+            max[i] = max[i].max(face_max[i]);
+        }
+    }
+
+    let index = aabbs.len();
+    aabbs.push(CollisionAABB {
+        min: to_vec3f(min),
+        collision_aabb_range: RangeBeginEnd { begin: 0, end: 0 },
+        max: to_vec3f(max),
+        collision_faces_range: RangeBeginSize {
+            begin: begin as u16,
+            size: faces.len() as u16,
+        },
+    });
+
+    // Leaves own a contiguous run of faces, inner nodes point at their two children
+    if faces.len() > 8 {
+        let extent = [max[0] - min[0], max[1] - min[1], max[2] - min[2]];
+        // total_cmp so NaN extents from an imported glTF can't panic
+        let axis = (0..3)
+            .max_by(|a, b| extent[*a].total_cmp(&extent[*b]))
+            .unwrap_or(0);
+        faces.sort_by(|a, b| (a.1[axis] + a.2[axis]).total_cmp(&(b.1[axis] + b.2[axis])));
+
+        let middle = faces.len() / 2;
+        let (left, right) = faces.split_at_mut(middle);
+        let left = collision_node(left, begin, aabbs);
+        let right = collision_node(right, begin + middle, aabbs);
+
+        aabbs[index].collision_aabb_range = RangeBeginEnd {
+            begin: left as u16,
+            end: right as u16,
+        };
+        aabbs[index].collision_faces_range = RangeBeginSize { begin: 0, size: 0 };
+    }
+
+    index
+}
+
+fn import_collision(
+    primitives: &[ImportedPrimitive],
+    collision_aabbs: &mut Vec<CollisionAABB>,
+    collision_faces: &mut Vec<CollisionFace>,
+    short_vec_weirds: &mut Vec<ShortVecWeird>,
+) -> Result<(), DpcError> {
+    // Meshes that shipped without collision keep none
+    let surface_type = match collision_faces.first() {
+        Some(collision_face) => collision_face.surface_type,
+        None => return Ok(()),
+    };
+
+    let mut vertices: Vec<[f32; 3]> = Vec::new();
+    let mut vertex_indices: HashMap<[i16; 3], u16> = HashMap::new();
+    let mut faces = Vec::new();
+    for primitive in primitives {
+        let positions = &primitive.vertices.positions;
+        for triangle in primitive.indices.chunks_exact(3) {
+            let mut face = [0u16; 3];
+            for (i, index) in triangle.iter().enumerate() {
+                let vertex = short_vec_weird(positions[*index as usize]).ok_or_else(|| {
+                    DpcError::Format(String::from(
+                        "collision vertex out of the 16-bit fixed point range",
+                    ))
+                })?;
+                let key = [
+                    vertex.data[0].data,
+                    vertex.data[1].data,
+                    vertex.data[2].data,
+                ];
+                face[i] = match vertex_indices.get(&key) {
+                    Some(index) => *index,
+                    None => {
+                        if vertices.len() > u16::MAX as usize {
+                            return Err(DpcError::Format(String::from(
+                                "too many collision vertices",
+                            )));
+                        }
+                        let index = vertices.len() as u16;
+                        vertex_indices.insert(key, index);
+                        vertices.push([
+                            key[0] as f32 / 1024.0,
+                            key[1] as f32 / 1024.0,
+                            key[2] as f32 / 1024.0,
+                        ]);
+                        index
+                    }
+                };
+            }
+
+            if face[0] == face[1] || face[1] == face[2] || face[0] == face[2] {
+                continue;
+            }
+
+            let corners = [
+                vertices[face[0] as usize],
+                vertices[face[1] as usize],
+                vertices[face[2] as usize],
+            ];
+            let (min, max) = glb::bounds(&corners);
+            faces.push((face, min, max));
+        }
+    }
+
+    if faces.len() > u16::MAX as usize {
+        return Err(DpcError::Format(String::from("too many collision faces")));
+    }
+
+    collision_aabbs.clear();
+    if !faces.is_empty() {
+        collision_node(&mut faces, 0, collision_aabbs);
+    }
+
+    *collision_faces = faces
+        .into_iter()
+        .map(|(face, _, _)| CollisionFace {
+            short_vec_weirds_indices: FixedVec {
+                data: face.to_vec(),
+            },
+            surface_type,
+        })
+        .collect();
+
+    *short_vec_weirds = vertices
+        .into_iter()
+        .map(|x| {
+            short_vec_weird(x).ok_or_else(|| {
+                DpcError::Format(String::from(
+                    "collision vertex out of the 16-bit fixed point range",
+                ))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(())
+}
+
+// The older layouts keep their collision vertices and bounding radius in fields
+// we have not mapped yet, so a glb import there would leave them stale
+fn unsupported_import(what: &str) -> DpcError {
+    DpcError::Format(format!(
+        "cannot import data.glb, the {} of this mesh version cannot be rebuilt",
+        what
+    ))
+}
+
+fn check_no_collision(collision_faces: &[CollisionFace]) -> Result<(), DpcError> {
+    if collision_faces.is_empty() {
+        Ok(())
+    } else {
+        Err(unsupported_import("collision"))
+    }
+}
+
+pub trait MeshGlbHeader {
+    fn transform(&self) -> Vec<f32>;
+    fn set_bounding_sphere(&mut self, sphere: SphereZ) -> Result<(), DpcError>;
+}
+
+pub trait MeshGlbBody {
+    fn glb_nodes(&self, builder: &mut GlbBuilder, names: &NameTable) -> Vec<usize>;
+    fn import_primitives(&mut self, primitives: &[ImportedPrimitive]) -> Result<(), DpcError>;
+}
+
+impl MeshGlbHeader for MeshZHeader {
+    fn transform(&self) -> Vec<f32> {
+        self.transform.data.clone()
+    }
+
+    fn set_bounding_sphere(&mut self, sphere: SphereZ) -> Result<(), DpcError> {
+        self.radius = sphere.radius;
+        Ok(())
+    }
+}
+
+impl MeshGlbHeader for MeshZHeaderAlt {
+    fn transform(&self) -> Vec<f32> {
+        self.transform.data.clone()
+    }
+
+    fn set_bounding_sphere(&mut self, _sphere: SphereZ) -> Result<(), DpcError> {
+        Err(unsupported_import("bounding sphere"))
+    }
+}
+
+impl MeshGlbHeader for MeshZHeaderAltAlt {
+    fn transform(&self) -> Vec<f32> {
+        self.transform.data.clone()
+    }
+
+    fn set_bounding_sphere(&mut self, _sphere: SphereZ) -> Result<(), DpcError> {
+        Err(unsupported_import("bounding sphere"))
+    }
+}
+
+impl MeshGlbBody for MeshZ {
+    fn glb_nodes(&self, builder: &mut GlbBuilder, names: &NameTable) -> Vec<usize> {
+        let mut strip_vertices = GlbVertices::default();
+        strip_vertices.positions = self.strip_vertices.data.iter().map(vec3f).collect();
+        if self.normals.len() == self.strip_vertices.len() {
+            strip_vertices.normals = self
+                .normals
+                .data
+                .iter()
+                .map(|x| glb::normalize(vec3f(x)))
+                .collect();
+        }
+        if self.texcoords.len() == self.strip_vertices.len() {
+            strip_vertices.uvs = self.texcoords.data.iter().map(vec2f).collect();
+        }
+
+        glb_nodes(
+            builder,
+            &self.mesh_buffers.vertex_buffers.data,
+            &self.mesh_buffers.index_buffers.data,
+            &self.mesh_buffers.vertex_groups.data,
+            strip_vertices,
+            &self.strips.data,
+            &self.material_crc32s.data,
+            names,
+        )
+    }
+
+    fn import_primitives(&mut self, primitives: &[ImportedPrimitive]) -> Result<(), DpcError> {
+        import_buffers(
+            primitives,
+            &mut self.mesh_buffers.vertex_buffers.data,
+            &mut self.mesh_buffers.index_buffers.data,
+            &mut self.mesh_buffers.vertex_groups.data,
+            &self.material_crc32s.data,
+        )?;
+        import_collision(
+            primitives,
+            &mut self.collision_aabbs.data,
+            &mut self.collision_faces.data,
+            &mut self.short_vec_weirds.data,
+        )
+    }
+}
+
+impl MeshGlbBody for MeshZAlt {
+    fn glb_nodes(&self, builder: &mut GlbBuilder, names: &NameTable) -> Vec<usize> {
+        let strip_vertices = GlbVertices {
+            positions: self.vecs.data.iter().map(vec3f).collect(),
+            ..Default::default()
+        };
+
+        glb_nodes(
+            builder,
+            &self.sub_meshes.data,
+            &self.indices.data,
+            &self.unknown13s.data,
+            strip_vertices,
+            &self.unknown2s.data,
+            &self.material_crc32s.data,
+            names,
+        )
+    }
+
+    fn import_primitives(&mut self, primitives: &[ImportedPrimitive]) -> Result<(), DpcError> {
+        check_no_collision(&self.unknown7s.data)?;
+        import_buffers(
+            primitives,
+            &mut self.sub_meshes.data,
+            &mut self.indices.data,
+            &mut self.unknown13s.data,
+            &self.material_crc32s.data,
+        )
+    }
+}
+
+impl MeshGlbBody for MeshZAltAlt {
+    fn glb_nodes(&self, builder: &mut GlbBuilder, names: &NameTable) -> Vec<usize> {
+        let strip_vertices = GlbVertices {
+            positions: self.vecs.data.iter().map(vec3f).collect(),
+            ..Default::default()
+        };
+
+        glb_nodes(
+            builder,
+            &self.sub_meshes.data,
+            &self.indices.data,
+            &self.unknown13s.data,
+            strip_vertices,
+            &self.unknown2s.data,
+            &self.material_crc32s.data,
+            names,
+        )
+    }
+
+    fn import_primitives(&mut self, primitives: &[ImportedPrimitive]) -> Result<(), DpcError> {
+        check_no_collision(&self.unknown7s.data)?;
+        import_buffers(
+            primitives,
+            &mut self.sub_meshes.data,
+            &mut self.indices.data,
+            &mut self.unknown13s.data,
+            &self.material_crc32s.data,
+        )
+    }
+}
+
+impl MeshGlbBody for MeshZAltAltAlt {
+    fn glb_nodes(&self, builder: &mut GlbBuilder, names: &NameTable) -> Vec<usize> {
+        let strip_vertices = GlbVertices {
+            positions: self.vecs.data.iter().map(vec3f).collect(),
+            ..Default::default()
+        };
+
+        glb_nodes(
+            builder,
+            &self.sub_meshes.data,
+            &self.indices.data,
+            &[],
+            strip_vertices,
+            &self.unknown2s.data,
+            &self.material_crc32s1.data,
+            names,
+        )
+    }
+
+    fn import_primitives(&mut self, primitives: &[ImportedPrimitive]) -> Result<(), DpcError> {
+        check_no_collision(&self.unknown7s.data)?;
+        import_buffers(
+            primitives,
+            &mut self.sub_meshes.data,
+            &mut self.indices.data,
+            &mut Vec::new(),
+            &self.material_crc32s1.data,
+        )
+    }
+}
+
+pub struct MeshGlbObjectFormat<T, U> {
+    x: PhantomData<T>,
+    y: PhantomData<U>,
+}
+
+impl<T, U> MeshGlbObjectFormat<T, U> {
+    pub fn new<'a>() -> &'a Self {
+        &Self {
+            x: PhantomData,
+            y: PhantomData,
+        }
+    }
+}
+
+impl<T, U> ObjectFormatTrait for MeshGlbObjectFormat<T, U>
+where
+    for<'a> T: Parse<&'a [u8]> + Serialize + Deserialize<'a> + BinWrite + HasReferences,
+    for<'a> U: Parse<&'a [u8]> + Serialize + Deserialize<'a> + BinWrite + HasReferences,
+    T: MeshGlbHeader,
+    U: MeshGlbBody,
+{
+    fn pack(
+        &self,
+        input_path: &Path,
+        header: &mut Vec<u8>,
+        body: &mut Vec<u8>,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        let import_path = match glb::find_import(input_path) {
+            Some(import_path) => import_path,
+            None => return ObjectFormat::<T, U>::new().pack(input_path, header, body),
+        };
+
+        let json_path = input_path.join("object.json");
+        let json_file = File::open(json_path)?;
+
+        #[derive(Serialize, Deserialize)]
+        struct Object<T, U> {
+            header: T,
+            body: U,
+        }
+
+        let mut object: Object<T, U> = serde_json::from_reader(json_file)?;
+
+        let primitives = glb::import_primitives(&import_path)?;
+        object.body.import_primitives(&primitives)?;
+
+        let positions: Vec<[f32; 3]> = primitives
+            .iter()
+            .flat_map(|x| x.vertices.positions.iter().copied())
+            .collect();
+        let (center, radius) = glb::bounding_sphere(&positions);
+        object.header.set_bounding_sphere(SphereZ {
+            center: to_vec3f(center),
+            radius,
+        })?;
+
+        object.header.write(header)?;
+        object.body.write(body)?;
+
+        let soft_links = [
+            &object.header.soft_links()[..],
+            &object.body.soft_links()[..],
+        ]
+        .concat();
+        let hard_links = [
+            &object.header.hard_links()[..],
+            &object.body.hard_links()[..],
+        ]
+        .concat();
+
+        Ok((hard_links, soft_links))
+    }
+
+    fn unpack(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        self.unpack_named(header, body, output_path, &NameTable::new())
+    }
+
+    fn unpack_named(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+        names: &NameTable,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        let references = ObjectFormat::<T, U>::new().unpack(header, body, output_path)?;
+
+        let header = match T::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        let body = match U::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
+
+        let mut builder = GlbBuilder::new();
+        let children = body.glb_nodes(&mut builder, names);
+        builder.add_node(json!({
+            "name": "root",
+            "matrix": header.transform(),
+            "children": children
+        }));
+
+        let mut glb_file = File::create(output_path.join("data.glb"))?;
+        builder.write(&mut glb_file)?;
+
+        Ok(references)
+    }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        ObjectFormat::<T, U>::new().to_json(header, body)
+    }
+}
+
+pub type MeshObjectFormat = MeshGlbObjectFormat<MeshZHeader, MeshZ>;
+pub type MeshObjectFormatAlt = MeshGlbObjectFormat<MeshZHeaderAlt, MeshZAlt>;
+pub type MeshObjectFormatAltAlt = MeshGlbObjectFormat<MeshZHeaderAltAlt, MeshZAltAlt>;
+pub type MeshObjectFormatAltAltAlt = MeshGlbObjectFormat<MeshZHeaderAltAlt, MeshZAltAltAlt>;
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    const POSITIONS: [[f32; 3]; 4] = [
+        [-1.0, -1.0, 0.0],
+        [1.0, -1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [-1.0, 1.0, 0.0],
+    ];
+    const INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+    fn mesh_header() -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&[0u8; 8 + 4 * 4]);
+        for i in 0..16 {
+            let value: f32 = if i % 5 == 0 { 1.0 } else { 0.0 };
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[0u8; 4 + 4 + 2 + 4 + 3 * 4 + 4 + 4]);
+        data
+    }
+
+    // groups are (index buffer begin, face count, material index) over the whole vertex buffer
+    fn mesh_body(groups: &[(u32, u32, i16)]) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&[0u8; 6 * 4]);
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&1234u32.to_le_bytes());
+        data.extend_from_slice(&5678u32.to_le_bytes());
+        data.extend_from_slice(&[0u8; 3 * 4]);
+
+        // One vertex buffer in the 36 byte layout without blending
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&4u32.to_le_bytes());
+        data.extend_from_slice(&36u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for position in POSITIONS.iter() {
+            for x in position.iter() {
+                data.extend_from_slice(&x.to_le_bytes());
+            }
+            data.extend_from_slice(&[255, 128, 128, 0, 128, 128, 255, 0]);
+            let uv = [(position[0] + 1.0) / 2.0, (position[1] + 1.0) / 2.0];
+            for x in uv.iter().chain(uv.iter()) {
+                data.extend_from_slice(&x.to_le_bytes());
+            }
+        }
+
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&(INDICES.len() as u32).to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for index in INDICES.iter() {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        data.extend_from_slice(&(groups.len() as u32).to_le_bytes());
+        for (index_buffer_index_begin, face_count, material_index) in groups.iter() {
+            data.extend_from_slice(&[0u8; 4 * 5]);
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(&4u16.to_le_bytes());
+            data.extend_from_slice(&4u32.to_le_bytes());
+            data.extend_from_slice(&index_buffer_index_begin.to_le_bytes());
+            data.extend_from_slice(&face_count.to_le_bytes());
+            data.extend_from_slice(&[0u8; 4 * 2]);
+            data.extend_from_slice(&36u16.to_le_bytes());
+            data.extend_from_slice(&material_index.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+        }
+
+        data.extend_from_slice(&[0u8; 4 * 4 + 4]);
+        data
+    }
+
+    // Unpacks to GLB and packs the GLB back
+    fn glb_roundtrip(header: &[u8], body: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let dir = TempDir::new("dpc-mesh").unwrap();
+        let format = MeshObjectFormat::new();
+        format.unpack(header, body, dir.path()).unwrap();
+        fs::rename(dir.path().join("data.glb"), dir.path().join("import.glb")).unwrap();
+
+        let mut new_header = Vec::new();
+        let mut new_body = Vec::new();
+        format
+            .pack(dir.path(), &mut new_header, &mut new_body)
+            .unwrap();
+        (new_header, new_body)
+    }
+
+    #[test]
+    fn test_glb_roundtrip() {
+        let header = mesh_header();
+        // One vertex group covering the whole buffer with the second material
+        let body = mesh_body(&[(0, 2, 1)]);
+        let (new_header, new_body) = glb_roundtrip(&header, &body);
+        let (_, new_header) = MeshZHeader::parse(&new_header).unwrap();
+        let (_, mesh) = MeshZ::parse(&new_body).unwrap();
+
+        assert_eq!(new_header.radius, 2.0f32.sqrt());
+        assert_eq!(new_body.len(), body.len());
+
+        let buffers = &mesh.mesh_buffers;
+        assert_eq!(buffers.vertex_buffers.len(), 1);
+        let vertex_buffer = &buffers.vertex_buffers.data[0];
+        assert_eq!(vertex_buffer.vertex_count, 4);
+        assert_eq!(vertex_buffer.vertex_size, 36);
+        match &vertex_buffer.vertices {
+            VertexBufferData::VertexLayoutNoBlendCase(vertices) => {
+                let positions: Vec<[f32; 3]> =
+                    vertices.iter().map(|x| vec3f(&x.position)).collect();
+                assert_eq!(positions, POSITIONS.to_vec());
+            }
+            _ => panic!("vertex layout changed"),
+        }
+
+        assert_eq!(buffers.index_buffers.len(), 1);
+        assert_eq!(buffers.index_buffers.data[0].index_count, 6);
+        assert_eq!(buffers.index_buffers.data[0].indices, INDICES.to_vec());
+
+        assert_eq!(buffers.vertex_groups.len(), 1);
+        let vertex_group = &buffers.vertex_groups.data[0];
+        assert_eq!(vertex_group.vertex_count, 4);
+        assert_eq!(vertex_group.face_count, 2);
+        assert_eq!(vertex_group.vertex_size, 36);
+        assert_eq!(vertex_group.material_index, 1);
+        assert_eq!(vertex_group.vertex_buffer_range.end, 4);
+    }
+
+    #[test]
+    fn test_glb_roundtrip_groups() {
+        let header = mesh_header();
+        // One triangle per group, sharing vertices 0 and 2
+        let body = mesh_body(&[(0, 1, 0), (3, 1, 1)]);
+
+        let triangles = |body: &[u8]| {
+            let (_, mesh) = MeshZ::parse(body).unwrap();
+            let buffers = &mesh.mesh_buffers;
+            let positions: Vec<[f32; 3]> = match &buffers.vertex_buffers.data[0].vertices {
+                VertexBufferData::VertexLayoutNoBlendCase(vertices) => {
+                    vertices.iter().map(|x| vec3f(&x.position)).collect()
+                }
+                _ => panic!("vertex layout changed"),
+            };
+            let indices = &buffers.index_buffers.data[0].indices;
+            let triangles: Vec<(i16, Vec<[f32; 3]>)> = buffers
+                .vertex_groups
+                .data
+                .iter()
+                .map(|vertex_group| {
+                    let begin = vertex_group.index_buffer_index_begin as usize;
+                    let end = begin + vertex_group.face_count as usize * 3;
+                    (
+                        vertex_group.material_index,
+                        indices[begin..end]
+                            .iter()
+                            .map(|x| positions[*x as usize])
+                            .collect(),
+                    )
+                })
+                .collect();
+            (positions.len(), triangles)
+        };
+
+        let (_, original) = triangles(&body);
+        let (_, body) = glb_roundtrip(&header, &body);
+        let (vertex_count, roundtrip) = triangles(&body);
+        assert_eq!(roundtrip, original);
+        // Each group brings the vertices it uses, not the whole buffer
+        assert_eq!(vertex_count, 3 + 4);
+
+        let (_, body) = glb_roundtrip(&header, &body);
+        assert_eq!(triangles(&body), (vertex_count, original));
+    }
+
+    #[test]
+    fn test_collision_node_nan() {
+        let mut faces: Vec<([u16; 3], [f32; 3], [f32; 3])> = (0..16)
+            .map(|i| {
+                let x = if i % 3 == 0 { f32::NAN } else { i as f32 };
+                ([0, 1, 2], [x, 0.0, 0.0], [x + 1.0, 1.0, 1.0])
+            })
+            .collect();
+        let mut aabbs = Vec::new();
+        assert_eq!(collision_node(&mut faces, 0, &mut aabbs), 0);
+        assert_eq!(aabbs.len(), 3);
+    }
+
+    #[test]
+    fn test_alt_bounding_sphere() {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(&[0u8; 4 + 4 + 4 * 4 + 16 * 4 + 4 + 4 + 2]);
+        data.extend_from_slice(&[0u8; 4 + 3 * 4 + 4 + 4 + 4 * 4]);
+        let (_, mut header) = MeshZHeaderAlt::parse(&data).unwrap();
+        let sphere = SphereZ {
+            center: to_vec3f([0.0, 0.0, 0.0]),
+            radius: 1.0,
+        };
+        assert!(matches!(
+            header.set_bounding_sphere(sphere),
+            Err(DpcError::Format(_))
+        ));
+    }
+}
@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, ResourceObjectZ};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, ResourceObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -26,4 +26,4 @@ impl HasReferences for MeshDataZ {
     }
 }
 
-pub type MeshDataObjectFormat = FUELObjectFormat<ResourceObjectZ, MeshDataZ>;
+pub type MeshDataObjectFormat = ObjectFormat<ResourceObjectZ, MeshDataZ>;
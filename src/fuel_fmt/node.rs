@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{
-    Color, FUELObjectFormat, FixedVec, HasReferences, Mat4f, Quat, Rect, ResourceObjectZ, SphereZ,
+use crate::asobo_fmt::common::{
+    Color, FixedVec, HasReferences, Mat4f, ObjectFormat, Quat, Rect, ResourceObjectZ, SphereZ,
     Vec3f,
 };
 
@@ -109,9 +109,67 @@ impl HasReferences for NodeZAlt {
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        vec![]
+        let mut v = Vec::new();
+        if self.parent_crc32 != 0 {
+            v.push(self.parent_crc32)
+        }
+        if self.some_node_crc320 != 0 {
+            v.push(self.some_node_crc320)
+        }
+        if self.some_node_crc321 != 0 {
+            v.push(self.some_node_crc321)
+        }
+        if self.some_node_crc322 != 0 {
+            v.push(self.some_node_crc322)
+        }
+        if self.some_crc320 != 0 {
+            v.push(self.some_crc320)
+        }
+        if self.some_crc321 != 0 {
+            v.push(self.some_crc321)
+        }
+        if self.some_crc322 != 0 {
+            v.push(self.some_crc322)
+        }
+        if self.some_crc323 != 0 {
+            v.push(self.some_crc323)
+        }
+        if self.some_crc324 != 0 {
+            v.push(self.some_crc324)
+        }
+        v
     }
 }
 
-pub type NodeObjectFormat = FUELObjectFormat<ResourceObjectZ, NodeZ>;
-pub type NodeObjectFormatAlt = FUELObjectFormat<ResourceObjectZ, NodeZAlt>;
+pub type NodeObjectFormat = ObjectFormat<ResourceObjectZ, NodeZ>;
+pub type NodeObjectFormatAlt = ObjectFormat<ResourceObjectZ, NodeZAlt>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_node_links() {
+        // parent, head child, next node, lod, lod data, user define
+        let mut data: Vec<u8> = Vec::new();
+        for crc32 in [1, 2, 0, 4, 5, 6, 7, 0, 0, 0u32] {
+            data.extend_from_slice(&crc32.to_le_bytes());
+        }
+        data.extend_from_slice(&[0u8; 16 + 12 + 4 + 16 + 16 + 16 + 16 + 32 + 2 + 64]);
+        let (_, node) = NodeZ::parse(&data[..]).unwrap();
+        assert_eq!(node.hard_links(), Vec::<u32>::new());
+        assert_eq!(node.soft_links(), vec![1, 2, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_node_alt_links() {
+        let mut data: Vec<u8> = Vec::new();
+        for crc32 in [1, 0, 3, 0, 5, 0, 0, 0, 9u32] {
+            data.extend_from_slice(&crc32.to_le_bytes());
+        }
+        data.extend_from_slice(&[0u8; 64 + 208 + 64 + 4 + 4 + 2 + 4 + 4]);
+        let (_, node) = NodeZAlt::parse(&data[..]).unwrap();
+        assert_eq!(node.hard_links(), Vec::<u32>::new());
+        assert_eq!(node.soft_links(), vec![1, 3, 5, 9]);
+    }
+}
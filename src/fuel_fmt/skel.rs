@@ -1,10 +1,18 @@
+use std::fs::File;
+use std::path::Path;
+
 use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::fuel_fmt::common::{
-    FUELObjectFormat, HasReferences, Mat4f, PascalArray, Quat, ResourceObjectZ, Vec3f, Vec3i32,
+use crate::asobo_fmt::common::{
+    HasReferences, Mat4f, ObjectFormat, ObjectFormatTrait, PascalArray, Quat, ResourceObjectZ,
+    Vec3f, Vec3i32,
 };
+use crate::error::DpcError;
+use crate::glb::{GlbBuilder, GlbJoint};
+use crate::names::NameTable;
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -102,4 +110,124 @@ impl HasReferences for SkelZ {
     }
 }
 
-pub type SkelObjectFormat = FUELObjectFormat<ResourceObjectZ, SkelZ>;
+impl SkelZ {
+    fn glb_joints(&self, names: &NameTable) -> Vec<GlbJoint> {
+        self.bones
+            .data
+            .iter()
+            .map(|bone| GlbJoint {
+                name: names.name_or_crc32(bone.bone_name),
+                parent: if bone.parent_index >= 0 {
+                    Some(bone.parent_index as usize)
+                } else {
+                    None
+                },
+                translation: [bone.vec1.data[0], bone.vec1.data[1], bone.vec1.data[2]],
+                rotation: [
+                    bone.quat.data[0],
+                    bone.quat.data[1],
+                    bone.quat.data[2],
+                    bone.quat.data[3],
+                ],
+                scale: [bone.vec0.data[0], bone.vec0.data[1], bone.vec0.data[2]],
+                model_transform: {
+                    let mut model_transform = [0.0; 16];
+                    model_transform.copy_from_slice(&bone.transformation.data);
+                    model_transform
+                },
+            })
+            .collect()
+    }
+}
+
+pub struct SkelObjectFormat;
+
+impl SkelObjectFormat {
+    pub fn new<'a>() -> &'a Self {
+        &Self {}
+    }
+}
+
+impl ObjectFormatTrait for SkelObjectFormat {
+    fn pack(
+        &self,
+        input_path: &Path,
+        header: &mut Vec<u8>,
+        body: &mut Vec<u8>,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        ObjectFormat::<ResourceObjectZ, SkelZ>::new().pack(input_path, header, body)
+    }
+
+    fn unpack(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        self.unpack_named(header, body, output_path, &NameTable::new())
+    }
+
+    fn unpack_named(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+        names: &NameTable,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        let references =
+            ObjectFormat::<ResourceObjectZ, SkelZ>::new().unpack(header, body, output_path)?;
+
+        let skel = match SkelZ::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
+
+        let mut builder = GlbBuilder::new();
+        builder.add_skin("skeleton", &skel.glb_joints(names));
+
+        let mut glb_file = File::create(output_path.join("data.glb"))?;
+        builder.write(&mut glb_file)?;
+
+        Ok(references)
+    }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        ObjectFormat::<ResourceObjectZ, SkelZ>::new().to_json(header, body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_skel_joints() {
+        let mut data: Vec<u8> = vec![0; 20];
+        let bones = [(-1i32, 10u32), (0, 11), (1, 12), (0, 13)];
+        data.extend_from_slice(&(bones.len() as u32).to_le_bytes());
+        for (parent_index, bone_name) in bones {
+            // Everything up to parent_index
+            data.extend_from_slice(&[0; 228]);
+            data.extend_from_slice(&parent_index.to_le_bytes());
+            data.extend_from_slice(&[0; 12]);
+            data.extend_from_slice(&bone_name.to_le_bytes());
+        }
+        // The arrays after the bones
+        data.extend_from_slice(&[0; 7 * 4]);
+        let (_, skel) = SkelZ::parse(&data[..]).unwrap();
+
+        let mut names = NameTable::new();
+        names.insert(10, "root");
+        names.insert(11, "spine");
+        names.insert(12, "head");
+        let joints = skel.glb_joints(&names);
+        assert_eq!(
+            joints.iter().map(|x| x.parent).collect::<Vec<_>>(),
+            vec![None, Some(0), Some(1), Some(0)]
+        );
+        assert_eq!(
+            joints.iter().map(|x| x.name.as_str()).collect::<Vec<_>>(),
+            vec!["root", "spine", "head", "13"]
+        );
+    }
+}
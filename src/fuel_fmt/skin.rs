@@ -1,20 +1,22 @@
 use binwrite::BinWrite;
+use nom::multi::length_count;
+use nom::number::complete::le_u32;
+use nom::IResult;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::fuel_fmt::common::{FUELObjectFormat, HasReferences, ObjectZ, PascalArray};
-
-static mut SKIN_DATA_COUNT: u32 = 0;
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, ObjectZ, PascalArray};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
 #[derive(Serialize, Deserialize, NomLE)]
+#[nom(ExtraArgs(data_count: u32))]
 struct SkinZSkinSubsection {
     vertex_group_crc32: u32,
     unknown_crc320: u32,
     unknown_crc321: u32,
     unknown_crc322: u32,
-    #[nom(Count(unsafe { SKIN_DATA_COUNT as usize }))]
+    #[nom(Count(data_count as usize))]
     data: Vec<u32>,
 }
 
@@ -30,9 +32,9 @@ pub struct SkinZ {
     u3: u32,
     #[nom(Verify(*one_and_a_half == 1.5))]
     one_and_a_half: f32,
-    #[nom(PostExec(unsafe { SKIN_DATA_COUNT = data_count }))]
     #[nom(Verify(*data_count == 21))]
     data_count: u32,
+    #[nom(Parse = "{ |i| parse_skin_sections(i, data_count) }")]
     skin_sections: PascalArray<PascalArray<SkinZSkinSubsection>>,
 }
 
@@ -46,11 +48,39 @@ pub struct SkinZAlt {
     u1: u32,
     u2: u8,
     one_and_a_half: f32,
-    #[nom(PostExec(unsafe { SKIN_DATA_COUNT = data_count }))]
     data_count: u32,
+    #[nom(Parse = "{ |i| parse_skin_sections(i, data_count) }")]
     skin_sections: PascalArray<PascalArray<SkinZSkinSubsection>>,
 }
 
+fn parse_skin_sections(
+    i: &[u8],
+    data_count: u32,
+) -> IResult<&[u8], PascalArray<PascalArray<SkinZSkinSubsection>>> {
+    let (i, data) = length_count(le_u32, |i| {
+        let (i, data) = length_count(le_u32, |i| SkinZSkinSubsection::parse_le(i, data_count))(i)?;
+        Ok((i, PascalArray { data }))
+    })(i)?;
+    Ok((i, PascalArray { data }))
+}
+
+fn skin_section_links(skin_sections: &PascalArray<PascalArray<SkinZSkinSubsection>>) -> Vec<u32> {
+    skin_sections
+        .data
+        .iter()
+        .flat_map(|section| section.data.iter())
+        .flat_map(|subsection| {
+            vec![
+                subsection.vertex_group_crc32,
+                subsection.unknown_crc320,
+                subsection.unknown_crc321,
+                subsection.unknown_crc322,
+            ]
+        })
+        .filter(|x| *x != 0)
+        .collect()
+}
+
 impl HasReferences for SkinZ {
     fn hard_links(&self) -> Vec<u32> {
         vec![]
@@ -59,6 +89,7 @@ impl HasReferences for SkinZ {
     fn soft_links(&self) -> Vec<u32> {
         let mut v = Vec::new();
         v.append(&mut self.mesh_crc32s.data.clone());
+        v.append(&mut skin_section_links(&self.skin_sections));
         v
     }
 }
@@ -69,9 +100,57 @@ impl HasReferences for SkinZAlt {
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        vec![]
+        let mut v = Vec::new();
+        v.append(&mut self.mesh_crc32s.data.clone());
+        v.append(&mut skin_section_links(&self.skin_sections));
+        v
     }
 }
 
-pub type SkinObjectFormat = FUELObjectFormat<ObjectZ, SkinZ>;
-pub type SkinObjectFormatAlt = FUELObjectFormat<ObjectZ, SkinZAlt>;
+pub type SkinObjectFormat = ObjectFormat<ObjectZ, SkinZ>;
+pub type SkinObjectFormatAlt = ObjectFormat<ObjectZ, SkinZAlt>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn skin_alt_data(data_count: u32) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        // one mesh crc32, u0, u1
+        for x in [1, 10, 0, 0u32] {
+            data.extend_from_slice(&x.to_le_bytes());
+        }
+        data.push(0);
+        data.extend_from_slice(&1.5f32.to_le_bytes());
+        data.extend_from_slice(&data_count.to_le_bytes());
+        // one section holding one subsection
+        for x in [1, 1, 2, 3, 4, 5u32] {
+            data.extend_from_slice(&x.to_le_bytes());
+        }
+        for x in 0..data_count {
+            data.extend_from_slice(&x.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_skin_alt_data_count() {
+        let threads: Vec<_> = [1u32, 3, 7]
+            .iter()
+            .map(|&data_count| {
+                std::thread::spawn(move || {
+                    let data = skin_alt_data(data_count);
+                    let (_, skin) = SkinZAlt::parse(&data[..]).unwrap();
+                    assert_eq!(
+                        skin.skin_sections.data[0].data[0].data.len(),
+                        data_count as usize
+                    );
+                    assert_eq!(skin.soft_links(), vec![10, 2, 3, 4, 5]);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+}
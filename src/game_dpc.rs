@@ -0,0 +1,1644 @@
+use std::borrow::Cow;
+use std::cmp::max;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::ffi::OsString;
+use std::fs;
+use std::fs::File;
+use std::fs::{metadata, OpenOptions};
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::option::Option::Some;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use ::nom::bytes::streaming::take;
+use binwrite::BinWrite;
+use clap::{App, AppSettings, Arg};
+use glob::glob;
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use memmap2::Mmap;
+use nom::combinator::map_res;
+use nom::error::Error as NomError;
+use nom::multi::count;
+use nom::AsBytes;
+use nom::IResult;
+use nom_derive::*;
+use serde::Deserialize;
+use serde::Serialize;
+
+use base_dpc::FormatOptions;
+use base_dpc::ObjectFormatTrait;
+use base_dpc::Options;
+use base_dpc::OutputStatus;
+use base_dpc::DPC;
+
+use crate::base_dpc;
+use crate::bigfile::{
+    calculate_padded_size, calculate_padding_size, BigFile, BigFileIndex, BigFileObject,
+    BigFileWriter, CompressionReport, CompressionTotals, ObjectHeader, PoolEntry, PoolLayout,
+    PoolManifest, PoolReferenceRecord, PrimaryHeader,
+};
+use crate::diff;
+use crate::error::{DpcError, Result};
+use crate::game_profile;
+use crate::game_profile::GameProfile;
+use crate::graph::DependencyGraph;
+use crate::lz::{lzrs_decompress_object, LzrsDecoder, LzrsEncoder};
+use crate::manifest;
+use crate::names::NameTable;
+use crate::object_store::ObjectStore;
+use crate::parallel::parallel_map;
+use crate::patch::BigFilePatch;
+use crate::verify;
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Header {
+    version_string: String,
+    version_minor: u32,
+    version_patch: u32,
+    block_type: u32,
+    is_rtc: bool,
+    pool_manifest_unused: u32,
+    incredi_builder_string: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectDescription {
+    crc32: u32,
+    compress: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Block {
+    offset: u32,
+    objects: Vec<ObjectDescription>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    manifest_version: u64,
+    header: Header,
+    blocks: Vec<Block>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pool: Option<Pool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    store: Option<StoreManifest>,
+}
+
+// Objects kept in a shared ObjectStore instead of objects/
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StoreManifest {
+    path: PathBuf,
+    // crc32 -> file name in the store
+    objects: BTreeMap<u32, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PoolObjectEntry {
+    crc32: u32,
+    reference_record_index: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(deny_unknown_fields)]
+struct JsonReferenceRecord {
+    object_entries_starting_index: u32,
+    object_entries_count: u16,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Pool {
+    object_entry_indices: Vec<u32>,
+    object_entries: Vec<PoolObjectEntry>,
+    reference_records: Vec<JsonReferenceRecord>,
+}
+
+impl Manifest {
+    fn new() -> Manifest {
+        Manifest {
+            manifest_version: manifest::MANIFEST_VERSION,
+            header: Header {
+                version_string: String::from(""),
+                version_minor: 0,
+                version_patch: 0,
+                block_type: 0,
+                is_rtc: false,
+                pool_manifest_unused: 0,
+                incredi_builder_string: None,
+            },
+            blocks: vec![],
+            pool: None,
+            store: None,
+        }
+    }
+}
+
+pub struct GameDPC {
+    profile: &'static GameProfile,
+    options: Options,
+    unoptimized_pool: bool,
+    no_pool: bool,
+    effective_version_string: String,
+    is_effective_version_trusted: bool,
+    format_options: FormatOptions,
+    name_files: Vec<PathBuf>,
+    names: NameTable,
+    version: String,
+    is_mmap: bool,
+    is_verify: bool,
+    patch_block: Option<usize>,
+    store_path: Option<PathBuf>,
+}
+
+impl GameDPC {
+    pub fn with_profile(
+        profile: &'static GameProfile,
+        options: &Options,
+        custom_args: &[&OsStr],
+    ) -> Result<GameDPC> {
+        let matches = App::new(format!("{} dpc backend", profile.name.to_lowercase()))
+            .version("version 1.0.0")
+            .author(profile.author)
+            .about(profile.name)
+            .arg(
+                Arg::with_name("UNOPTIMIZED-POOL")
+                    .short("p")
+                    .long("unoptimized-pool")
+                    .help("Don't minify the pool manifest"),
+            )
+            .arg(
+                Arg::with_name("NO-POOL")
+                    .short("n")
+                    .long("no-pool")
+                    .help("Don't use a pool"),
+            )
+            .arg(
+                Arg::with_name("EFFECTIVE-VERSION-STRING")
+                    .short("T")
+                    .long("effective-version-string")
+                    .help("Version string to compare against"),
+            )
+            .arg(
+                Arg::with_name("PNG-MIPS")
+                    .short("m")
+                    .long("png-mips")
+                    .help("Write every mip level of a bitmap as PNG"),
+            )
+            .arg(
+                Arg::with_name("NAMES")
+                    .short("N")
+                    .long("names")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .help("Name file of `hash \"name\"` lines used to name objects"),
+            )
+            .arg(
+                Arg::with_name("MMAP")
+                    .long("mmap")
+                    .help("Memory-map the BigFile instead of reading it when extracting"),
+            )
+            .arg(
+                Arg::with_name("VERIFY")
+                    .long("verify")
+                    .help("Reopen the BigFile after create and check it against the objects"),
+            )
+            .arg(
+                Arg::with_name("PATCH-BLOCK")
+                    .long("patch-block")
+                    .takes_value(true)
+                    .help("Block that patch adds objects missing from the BigFile to"),
+            )
+            .arg(
+                Arg::with_name("STORE")
+                    .long("store")
+                    .takes_value(true)
+                    .help("Shared object store that extract moves objects into and create reads them from"),
+            )
+            .settings(&[AppSettings::NoBinaryName])
+            .get_matches_from_safe(custom_args)
+            .map_err(|error| DpcError::Format(error.message))?;
+
+        Ok(GameDPC {
+            profile,
+            options: *options,
+            unoptimized_pool: matches.is_present("UNOPTIMIZED-POOL"),
+            no_pool: matches.is_present("NO-POOL"),
+            effective_version_string: matches
+                .value_of("EFFECTIVE-VERSION-STRING")
+                .unwrap_or(profile.default_version)
+                .to_string(),
+            is_effective_version_trusted: false,
+            format_options: FormatOptions {
+                png_mips: matches.is_present("PNG-MIPS"),
+            },
+            name_files: matches
+                .values_of("NAMES")
+                .map(|values| values.map(PathBuf::from).collect())
+                .unwrap_or_default(),
+            names: NameTable::new(),
+            version: String::from(profile.default_version),
+            is_mmap: matches.is_present("MMAP"),
+            is_verify: matches.is_present("VERIFY"),
+            patch_block: match matches.value_of("PATCH-BLOCK") {
+                Some(block) => Some(block.parse().map_err(|_| {
+                    DpcError::Format(format!(
+                        "invalid patch block {}, expected a block index",
+                        block
+                    ))
+                })?),
+                None => None,
+            },
+            store_path: matches.value_of("STORE").map(PathBuf::from),
+        })
+    }
+
+    pub fn profile(&self) -> &'static GameProfile {
+        self.profile
+    }
+
+    // Lets an unknown BigFile version be handled as version_string without -u
+    pub fn set_effective_version(&mut self, version_string: &str) {
+        self.effective_version_string = version_string.to_string();
+        self.is_effective_version_trusted = true;
+    }
+
+    fn is_known_version(&self, version_string: &str) -> bool {
+        self.profile.lookup_version(version_string).is_some()
+    }
+
+    fn allows_unknown_version(&self) -> bool {
+        self.options.is_unsafe || self.is_effective_version_trusted
+    }
+
+    fn object_formats(
+        &self,
+        version: &str,
+    ) -> Result<HashMap<u32, &'static dyn ObjectFormatTrait>> {
+        let use_version = if self.is_known_version(version) {
+            version.to_string()
+        } else if self.allows_unknown_version() {
+            self.effective_version_string.clone()
+        } else {
+            return Err(DpcError::UnsupportedVersion(version.to_string()));
+        };
+
+        self.profile
+            .object_formats(&use_version, &self.format_options)
+    }
+
+    // Unpacks borrowed object data, decompressing it first if needed
+    fn unpack_object(
+        &self,
+        oh: &ObjectHeader,
+        class_object: &[u8],
+        data: &[u8],
+        output_path: &Path,
+    ) -> Result<(Vec<u32>, Vec<u32>)> {
+        let formats = self.object_formats(&self.version)?;
+        let object_format = formats
+            .get(&oh.class_crc32)
+            .ok_or(DpcError::UnsupportedFormat(oh.class_crc32))?;
+
+        fs::create_dir_all(output_path)?;
+
+        let decompressed_buffer;
+        let data = if oh.compressed_size != 0 {
+            decompressed_buffer = lzrs_decompress_object(data)?;
+            &decompressed_buffer[..]
+        } else {
+            data
+        };
+
+        let references = object_format
+            .unpack_named(class_object, data, output_path, &self.names)
+            .map_err(|error| DpcError::ObjectFormat {
+                crc32: oh.crc32,
+                error: Box::new(error),
+            })?;
+        self.names.annotate_file(&output_path.join("object.json"))?;
+        Ok(references)
+    }
+
+    // Reuses an existing `{crc32}_*.{Class}` file so renamed objects keep their names
+    fn object_file_path(&self, objects_path: &Path, oh: &ObjectHeader) -> Result<PathBuf> {
+        let class_name = match self.profile.class_name(oh.class_crc32) {
+            Some(class_name) => String::from(class_name),
+            None => oh.class_crc32.to_string(),
+        };
+        let default_object_file_path =
+            objects_path.join(self.names.object_file_name(oh.crc32, class_name.as_str()));
+
+        if default_object_file_path.is_file() {
+            return Ok(default_object_file_path);
+        }
+
+        let paths = glob(
+            objects_path
+                .join(format!("{}_*.{}", oh.crc32, class_name))
+                .to_string_lossy()
+                .as_ref(),
+        )?
+        .collect::<std::result::Result<Vec<PathBuf>, _>>()?;
+        match paths.len() {
+            0 => Ok(default_object_file_path),
+            1 => Ok(paths[0].clone()),
+            _ => Err(DpcError::AmbiguousCrc32 {
+                crc32: oh.crc32,
+                paths,
+            }),
+        }
+    }
+
+    // Parses decompressed object data into the object.json its format would unpack. None when the
+    // class has no format or the object does not parse, so it can only be compared byte for byte
+    fn parse_object_json(
+        &self,
+        version: &str,
+        object: &BigFileObject,
+        data: &[u8],
+    ) -> Result<Option<serde_json::Value>> {
+        let formats = self.object_formats(version)?;
+        let object_format = match formats.get(&object.header.class_crc32) {
+            Some(object_format) => object_format,
+            None => return Ok(None),
+        };
+
+        Ok(object_format.to_json(&object.class_object, data).ok())
+    }
+
+    // `{crc32 or name}.{Class}.d` -> crc32
+    fn object_path_crc32(&self, input_path: &Path) -> Option<u32> {
+        Path::new(input_path.file_stem()?)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| self.names.parse_stem(stem))
+    }
+
+    // Packs a `{crc32}.{Class}.d` directory into an object
+    fn pack_object(&self, input_path: &Path) -> Result<BigFileObject> {
+        let invalid_object_path = || DpcError::InvalidObjectPath(input_path.to_path_buf());
+
+        let p = Path::new(input_path.file_stem().ok_or_else(invalid_object_path)?);
+        let crc32 = self
+            .object_path_crc32(input_path)
+            .ok_or_else(invalid_object_path)?;
+        let class_name = p
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .ok_or_else(invalid_object_path)?;
+
+        let class_crc32: u32;
+        if let Some(v) = self.profile.class_crc32(class_name) {
+            class_crc32 = v
+        } else {
+            class_crc32 = class_name
+                .parse::<u32>()
+                .map_err(|_| invalid_object_path())?;
+        }
+
+        let use_version = if self.is_known_version(&self.version) {
+            &self.version
+        } else if self.allows_unknown_version() {
+            &self.effective_version_string
+        } else {
+            return Err(DpcError::UnsupportedVersion(self.version.clone()));
+        };
+
+        if let Some(object_format) = self
+            .profile
+            .object_formats(use_version, &self.format_options)?
+            .get(&class_crc32)
+        {
+            let mut header: Vec<u8> = Vec::new();
+            let mut body: Vec<u8> = Vec::new();
+            let res = object_format.pack(input_path, &mut header, &mut body);
+            if let Err(error) = res {
+                if !self.options.is_unsafe {
+                    return Err(DpcError::ObjectFormat {
+                        crc32,
+                        error: Box::new(error),
+                    });
+                }
+
+                if !self.options.is_quiet {
+                    println!("Warn: object parser failed {}: {}", crc32, error);
+                }
+            }
+
+            Ok(BigFileObject::new(crc32, class_crc32, header, body))
+        } else {
+            Err(DpcError::UnsupportedFormat(class_crc32))
+        }
+    }
+
+    fn print_compression_report(
+        &self,
+        pb: &ProgressBar,
+        report: &CompressionReport,
+        elapsed: Duration,
+    ) {
+        let mut total = CompressionTotals::default();
+        for (class_crc32, totals) in report.iter() {
+            let class_name = match self.profile.class_name(*class_crc32) {
+                Some(class_name) => class_name.to_string(),
+                None => class_crc32.to_string(),
+            };
+            pb.println(format_compression_totals(&class_name, totals));
+
+            total.object_count += totals.object_count;
+            total.decompressed_size += totals.decompressed_size;
+            total.compressed_size += totals.compressed_size;
+            total.duration += totals.duration;
+        }
+        pb.println(format_compression_totals("Total", &total));
+        pb.println(format!(
+            "Compressed in {:.2}s with {} threads",
+            elapsed.as_secs_f64(),
+            self.options.thread_count()
+        ));
+    }
+}
+
+fn format_compression_totals(name: &str, totals: &CompressionTotals) -> String {
+    format!(
+        "{}: {} objects, {} -> {} bytes ({:.1}%) in {:.2}s",
+        name,
+        totals.object_count,
+        totals.decompressed_size,
+        totals.compressed_size,
+        totals.compressed_size as f64 * 100.0 / max(totals.decompressed_size, 1) as f64,
+        totals.duration.as_secs_f64()
+    )
+}
+
+// The sizes in an extracted object header have to add up to what was written after it
+fn check_object_size(oh: &ObjectHeader, written_size: u64) -> Result<()> {
+    let body_size = if oh.compressed_size != 0 {
+        oh.compressed_size
+    } else {
+        oh.decompressed_size
+    };
+    if oh.class_object_size as u64 + body_size as u64 != oh.data_size as u64
+        || oh.data_size as u64 + 24 != written_size
+    {
+        return Err(DpcError::Format(format!(
+            "object {} has a data size of {} but {} bytes of class object and data",
+            oh.crc32,
+            oh.data_size,
+            written_size.saturating_sub(24)
+        )));
+    }
+    Ok(())
+}
+
+fn fmt_directory(object_file_path: &Path) -> PathBuf {
+    let mut path = OsString::new();
+    path.push(object_file_path.as_os_str());
+    path.push(".d");
+    PathBuf::from(path)
+}
+
+// The BigFile being extracted, either mapped so reads borrow from it or read on demand
+enum InputSource {
+    Mapped(Mmap),
+    File(File),
+}
+
+impl InputSource {
+    fn open<P: AsRef<Path>>(path: &P, is_mmap: bool) -> Result<InputSource> {
+        let file = File::open(path.as_ref())?;
+        if is_mmap {
+            // SAFETY: the mapping is only sound while nothing else modifies or truncates the file,
+            // which extract can't enforce and relies on the caller for, as with any --mmap input
+            Ok(InputSource::Mapped(unsafe { Mmap::map(&file)? }))
+        } else {
+            Ok(InputSource::File(file))
+        }
+    }
+
+    fn len(&self) -> Result<u64> {
+        match self {
+            InputSource::Mapped(mmap) => Ok(mmap.len() as u64),
+            InputSource::File(file) => Ok(file.metadata()?.len()),
+        }
+    }
+
+    // Both sources fail the same way on a read past the end of the file
+    fn read_at(&self, offset: u64, len: usize) -> Result<Cow<'_, [u8]>> {
+        let file_len = self.len()?;
+        if offset.saturating_add(len as u64) > file_len {
+            return Err(DpcError::Format(format!(
+                "{} bytes at {} run past the end of the file at {}",
+                len, offset, file_len
+            )));
+        }
+
+        match self {
+            InputSource::Mapped(mmap) => {
+                Ok(Cow::Borrowed(&mmap[offset as usize..offset as usize + len]))
+            }
+            InputSource::File(file) => {
+                let mut file: &File = file;
+                file.seek(SeekFrom::Start(offset))?;
+                let mut buffer = vec![0; len];
+                file.read_exact(&mut buffer)?;
+                Ok(Cow::Owned(buffer))
+            }
+        }
+    }
+}
+
+impl DPC for GameDPC {
+    fn new(options: &Options, custom_args: &[&OsStr]) -> Result<GameDPC> {
+        let mut profile = &game_profile::FUEL;
+        let mut args: Vec<&OsStr> = Vec::new();
+        let mut i = 0;
+        while i < custom_args.len() {
+            let arg = custom_args[i].to_string_lossy();
+            let name = if arg == "-G" || arg == "--game" {
+                i += 1;
+                Some(
+                    custom_args
+                        .get(i)
+                        .map(|x| x.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                )
+            } else if let Some(name) = arg.strip_prefix("--game=") {
+                Some(name.to_string())
+            } else {
+                args.push(custom_args[i]);
+                None
+            };
+            if let Some(name) = name {
+                profile = game_profile::find(&name).ok_or(DpcError::UnsupportedGame(name))?;
+            }
+            i += 1;
+        }
+        GameDPC::with_profile(profile, options, &args)
+    }
+
+    fn extract<P: AsRef<Path>>(&mut self, input_path: &P, output_path: &P) -> Result<OutputStatus> {
+        self.names = NameTable::from_files(&self.name_files)?;
+
+        let mut global_objects: HashMap<u32, ObjectDescription> = HashMap::new();
+        let mut global_object_headers: HashMap<u32, ObjectHeader> = HashMap::new();
+        let mut global_object_references: HashMap<u32, (Vec<u32>, Vec<u32>)> = HashMap::new();
+
+        let input = InputSource::open(input_path, self.is_mmap)?;
+
+        if !base_dpc::should_write(self.options.overwrite_policy, output_path, "directory")? {
+            return Ok(OutputStatus::Skipped(output_path.as_ref().to_path_buf()));
+        }
+
+        fs::create_dir_all(output_path.as_ref())?;
+
+        let manifest_path = output_path.as_ref().join("manifest.json");
+        let mut manifest_file = File::create(manifest_path)?;
+
+        let mut manifest_json = Manifest::new();
+
+        #[derive(NomLE, Clone, Debug, PartialEq, Eq)]
+        struct BlockObject<'a> {
+            #[nom(
+                Parse = "ObjectHeader::parse",
+                Verify = "header.class_object_size <= header.data_size"
+            )]
+            header: ObjectHeader,
+            #[nom(Take = "header.class_object_size as usize")]
+            class_object: &'a [u8],
+            #[nom(Take = "(header.data_size - header.class_object_size) as usize")]
+            data: &'a [u8],
+        }
+
+        #[derive(NomLE, Clone, Debug, PartialEq, Eq)]
+        struct PoolObject<'a> {
+            #[nom(Parse = "ObjectHeader::parse")]
+            header: ObjectHeader,
+            #[nom(Take = "header.data_size as usize", AlignAfter(2048))]
+            data: &'a [u8],
+        }
+
+        let buffer = input.read_at(0, 2048)?;
+        let header = match PrimaryHeader::parse(&buffer) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("primary header", 0, &buffer, error)),
+        };
+
+        if !self.is_known_version(&header.version_string) && !self.allows_unknown_version() {
+            return Err(DpcError::UnsupportedVersion(header.version_string.clone()));
+        }
+
+        self.version = header.version_string.clone();
+        manifest_json.header.version_string = header.version_string.clone();
+        manifest_json.header.version_minor = header.version_minor;
+        manifest_json.header.version_patch = header.version_patch;
+        manifest_json.header.block_type = header
+            .block_descriptions
+            .first()
+            .map_or(0, |block_description| block_description.block_type);
+        manifest_json.header.is_rtc = header.is_not_rtc == 0;
+        manifest_json.header.pool_manifest_unused = header.pool_manifest_unused0;
+        if header.block_sector_padding_size != 0xFFFFFFFF {
+            manifest_json.header.incredi_builder_string =
+                Some(header.incredi_builder_string.clone());
+        }
+
+        let mut object_count = 0;
+
+        for block_description in header.block_descriptions.iter() {
+            object_count += block_description.object_count;
+        }
+
+        let pb = match self.options.is_quiet {
+            false => ProgressBar::new(object_count as u64),
+            true => ProgressBar::hidden(),
+        };
+
+        let mut crc32s = HashSet::new();
+
+        let objects_path = output_path.as_ref().join("objects");
+        fs::create_dir_all(&objects_path)?;
+        let threads = self.options.thread_count();
+        let mut offset: u64 = 2048;
+
+        for (i, block_description) in header.block_descriptions.iter().enumerate() {
+            let x = i + 1;
+            pb.println(format!(
+                "Processing block {}/{}",
+                x,
+                header.block_descriptions.len()
+            ));
+
+            let mut v = vec![];
+
+            let block_offset = offset as usize;
+            let buff = input.read_at(offset, block_description.padded_size as usize)?;
+            offset += block_description.padded_size as u64;
+
+            let objects = match count(BlockObject::parse, block_description.object_count as usize)(
+                buff.as_bytes(),
+            ) {
+                Ok((_, h)) => h,
+                Err(error) => {
+                    return Err(DpcError::parse("block", block_offset, &buff, error).in_block(x))
+                }
+            };
+
+            let mut new_objects = vec![];
+
+            for object in objects.iter() {
+                v.push(ObjectDescription {
+                    crc32: object.header.crc32,
+                    compress: object.header.compressed_size != 0,
+                });
+
+                if crc32s.insert(object.header.crc32) {
+                    let object_file_path = self.object_file_path(&objects_path, &object.header)?;
+
+                    global_object_headers.insert(object.header.crc32, object.header);
+
+                    global_objects.insert(
+                        object.header.crc32,
+                        ObjectDescription {
+                            crc32: object.header.crc32,
+                            compress: object.header.compressed_size != 0,
+                        },
+                    );
+
+                    new_objects.push((object, object_file_path));
+                } else {
+                    pb.inc(1);
+                }
+            }
+
+            let references = parallel_map(threads, &new_objects, |(object, object_file_path)| {
+                let mut object_file = File::create(object_file_path)?;
+                let mut oh = object.header;
+                let decompressed_buffer;
+                let data = if self.options.is_lz && object.header.compressed_size != 0 {
+                    pb.println(format!("Decompressing {}", object.header.crc32));
+                    decompressed_buffer = lzrs_decompress_object(object.data)?;
+
+                    oh.compressed_size = 0;
+                    oh.data_size = oh.class_object_size + oh.decompressed_size;
+
+                    oh.write(&mut object_file)?;
+                    object_file.write_all(object.class_object)?;
+                    object_file.write_all(&decompressed_buffer)?;
+                    check_object_size(&oh, object_file.stream_position()?)?;
+                    &decompressed_buffer[..]
+                } else {
+                    pb.println(format!("Processing {}", object.header.crc32));
+                    oh.write(&mut object_file)?;
+                    object_file.write_all(object.class_object)?;
+                    object_file.write_all(object.data)?;
+                    check_object_size(&oh, object_file.stream_position()?)?;
+                    object.data
+                };
+
+                let mut references = None;
+                if oh.data_size > oh.class_object_size && self.options.is_recursive {
+                    pb.println(format!("Extracting {}", oh.crc32));
+                    match self.unpack_object(
+                        &oh,
+                        object.class_object,
+                        data,
+                        &fmt_directory(object_file_path),
+                    ) {
+                        Ok(x) => references = Some(x),
+                        Err(DpcError::UnsupportedFormat(_)) => (),
+                        Err(e) if e.is_parse() => (),
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                pb.inc(1);
+                Ok((oh.crc32, references))
+            })?;
+
+            for (crc32, references) in references {
+                if let Some(x) = references {
+                    global_object_references.insert(crc32, x);
+                }
+            }
+
+            manifest_json.blocks.push(Block {
+                offset: block_description.working_buffer_offset,
+                objects: v,
+            });
+        }
+
+        if header.pool_manifest_offset != 0 {
+            let pool_manifest_offset = offset as usize;
+            let buf = input.read_at(offset, header.pool_manifest_padded_size as usize)?;
+            offset += header.pool_manifest_padded_size as u64;
+
+            let pool_manifest = match PoolManifest::parse(&buf) {
+                Ok((_, h)) => h,
+                Err(error) => {
+                    return Err(DpcError::parse(
+                        "pool manifest",
+                        pool_manifest_offset,
+                        &buf,
+                        error,
+                    ))
+                }
+            };
+
+            if pool_manifest.crc32s.len() != pool_manifest.reference_records_indices.len() {
+                return Err(DpcError::Format(format!(
+                    "pool manifest has {} object entries but {} reference record indices",
+                    pool_manifest.crc32s.len(),
+                    pool_manifest.reference_records_indices.len()
+                )));
+            }
+
+            let mut object_entries = vec![];
+            for (crc32, reference_record_index) in pool_manifest
+                .crc32s
+                .iter()
+                .zip(pool_manifest.reference_records_indices.iter())
+            {
+                object_entries.push(PoolObjectEntry {
+                    crc32: *crc32,
+                    reference_record_index: *reference_record_index,
+                })
+            }
+
+            let mut json_reference_records = vec![];
+            for reference_record in pool_manifest.reference_records.iter() {
+                json_reference_records.push(JsonReferenceRecord {
+                    object_entries_starting_index: reference_record.objects_crc32_starting_index,
+                    object_entries_count: reference_record.objects_crc32_count,
+                })
+            }
+
+            manifest_json.pool = Some(Pool {
+                object_entry_indices: pool_manifest.objects_crc32s.clone(),
+                object_entries,
+                reference_records: json_reference_records,
+            });
+
+            // Mapped input borrows the pool straight from the file, otherwise it is read to EOF
+            let pool_size = input.len()?.checked_sub(offset).ok_or_else(|| {
+                DpcError::Format(format!(
+                    "pool starts at {} past the end of the file",
+                    offset
+                ))
+            })?;
+            let bufff = input.read_at(offset, pool_size as usize)?;
+
+            let pool_objects = match count(PoolObject::parse, pool_manifest.objects_crc32s.len())(
+                bufff.as_bytes(),
+            ) {
+                Ok((_, h)) => h,
+                Err(error) => return Err(DpcError::parse("pool", offset as usize, &bufff, error)),
+            };
+
+            pb.println("Processing pool");
+            pb.set_position(0);
+            pb.set_length(pool_objects.len() as u64);
+
+            // An object can be in the pool more than once, only the first copy is extracted
+            let mut pool_crc32s = HashSet::new();
+            let mut unique_pool_objects = vec![];
+            for pool_object in pool_objects.iter() {
+                if pool_crc32s.insert(pool_object.header.crc32) {
+                    let oh = *global_object_headers
+                        .get(&pool_object.header.crc32)
+                        .ok_or(DpcError::MissingObject(pool_object.header.crc32))?;
+                    let object_file_path = self.object_file_path(&objects_path, &oh)?;
+                    unique_pool_objects.push((pool_object, oh, object_file_path));
+                } else {
+                    pb.inc(1);
+                }
+            }
+
+            let references = parallel_map(
+                threads,
+                &unique_pool_objects,
+                |(pool_object, oh, object_file_path)| {
+                    pb.println(format!("Processing {}", pool_object.header.crc32));
+
+                    let mut object_file = OpenOptions::new()
+                        .read(true)
+                        .write(true)
+                        .open(object_file_path)?;
+
+                    let mut oh = *oh;
+                    let mut class_object = vec![0; oh.class_object_size as usize];
+                    object_file.seek(SeekFrom::Start(24))?;
+                    object_file.read_exact(&mut class_object)?;
+
+                    let decompressed_buffer;
+                    let data = if self.options.is_lz && (pool_object.header.compressed_size != 0) {
+                        pb.println(format!("Decompressing {}", pool_object.header.crc32));
+                        decompressed_buffer = lzrs_decompress_object(pool_object.data)?;
+                        object_file.write_all(&decompressed_buffer)?;
+                        oh.data_size = oh.class_object_size + pool_object.header.decompressed_size;
+                        &decompressed_buffer[..]
+                    } else {
+                        object_file.write_all(pool_object.data)?;
+                        oh.data_size = oh.class_object_size
+                            + if pool_object.header.compressed_size != 0 {
+                                pool_object.header.compressed_size
+                            } else {
+                                pool_object.header.decompressed_size
+                            };
+                        pool_object.data
+                    };
+
+                    if self.options.is_lz && pool_object.header.compressed_size != 0 {
+                        oh.compressed_size = 0;
+                    } else {
+                        oh.compressed_size = pool_object.header.compressed_size;
+                    }
+                    oh.decompressed_size = pool_object.header.decompressed_size;
+                    check_object_size(&oh, object_file.stream_position()?)?;
+
+                    object_file.seek(SeekFrom::Start(0))?;
+                    oh.write(&mut object_file)?;
+
+                    let mut references = None;
+                    if self.options.is_recursive {
+                        pb.println(format!("Extracting {}", oh.crc32));
+                        references = Some(self.unpack_object(
+                            &oh,
+                            &class_object,
+                            data,
+                            &fmt_directory(object_file_path),
+                        )?);
+                    }
+
+                    pb.inc(1);
+                    Ok((
+                        oh.crc32,
+                        pool_object.header.compressed_size != 0,
+                        references,
+                    ))
+                },
+            )?;
+
+            for (crc32, compress, references) in references {
+                global_objects
+                    .get_mut(&crc32)
+                    .ok_or(DpcError::MissingObject(crc32))?
+                    .compress = compress;
+                if let Some(x) = references {
+                    global_object_references.insert(crc32, x);
+                }
+            }
+        }
+
+        if let Some(store_path) = &self.store_path {
+            pb.println("Moving objects to the store");
+            let store = ObjectStore::open(store_path)?;
+            let object_file_paths = global_object_headers
+                .values()
+                .sorted_by_key(|oh| oh.crc32)
+                .map(|oh| Ok((oh.crc32, self.object_file_path(&objects_path, oh)?)))
+                .collect::<Result<Vec<(u32, PathBuf)>>>()?;
+            let file_names = parallel_map(threads, &object_file_paths, |(_, path)| {
+                store.insert_file(path)
+            })?;
+            manifest_json.store = Some(StoreManifest {
+                path: store.path().to_path_buf(),
+                objects: object_file_paths
+                    .into_iter()
+                    .map(|(crc32, _)| crc32)
+                    .zip(file_names)
+                    .collect(),
+            });
+        }
+
+        pb.finish_and_clear();
+
+        for block in manifest_json.blocks.iter_mut() {
+            for object in block.objects.iter_mut() {
+                let od: &ObjectDescription = global_objects
+                    .get(&object.crc32)
+                    .ok_or(DpcError::MissingObject(object.crc32))?;
+                object.compress = od.compress;
+            }
+        }
+
+        manifest_file.write_all(serde_json::to_string_pretty(&manifest_json)?.as_bytes())?;
+
+        let mut references_file = File::create(output_path.as_ref().join("references.txt"))?;
+        for (crc32, x) in global_object_references
+            .iter()
+            .sorted_by_key(|(crc32, _)| **crc32)
+        {
+            references_file.write_all(
+                format!(
+                    "{} > {} & {}\n",
+                    self.names.describe(*crc32),
+                    self.names.describe_all(&x.0),
+                    self.names.describe_all(&x.1)
+                )
+                .as_ref(),
+            )?;
+        }
+
+        let mut graph = DependencyGraph::new();
+        for (crc32, oh) in global_object_headers
+            .iter()
+            .sorted_by_key(|(crc32, _)| **crc32)
+        {
+            graph.add_node(
+                *crc32,
+                self.profile.class_name(oh.class_crc32),
+                self.names.get(*crc32),
+            );
+        }
+        for (crc32, x) in global_object_references
+            .iter()
+            .sorted_by_key(|(crc32, _)| **crc32)
+        {
+            graph.add_links(*crc32, &x.0, &x.1);
+        }
+        graph.name_nodes(&self.names);
+        fs::write(output_path.as_ref().join("graph.json"), graph.to_json()?)?;
+        fs::write(output_path.as_ref().join("graph.dot"), graph.to_dot())?;
+
+        Ok(OutputStatus::Written)
+    }
+
+    //
+    // CREATE
+    //
+
+    fn create<P: AsRef<Path>>(&mut self, input_path: &P, output_path: &P) -> Result<OutputStatus> {
+        self.names = NameTable::from_files(&self.name_files)?;
+
+        // Older manifests are migrated in memory, the file is left as is
+        let manifest_value =
+            manifest::load(&input_path.as_ref().join("manifest.json"), self.profile)?;
+
+        if !base_dpc::should_write(self.options.overwrite_policy, output_path, "DPC")? {
+            return Ok(OutputStatus::Skipped(output_path.as_ref().to_path_buf()));
+        }
+
+        let mut manifest_json: Manifest = serde_json::from_value(manifest_value)?;
+
+        self.version = manifest_json.header.version_string.clone();
+
+        if self.no_pool || !self.profile.has_pool {
+            manifest_json.header.pool_manifest_unused = 0;
+            manifest_json.pool = None;
+        }
+
+        let mut index: HashMap<u32, std::path::PathBuf> = HashMap::new();
+
+        if self.options.is_recursive {
+            let mut paths = vec![];
+            for entry in fs::read_dir(input_path.as_ref().join("objects"))? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    paths.push(path);
+                }
+            }
+            paths.sort();
+
+            let results = parallel_map(self.options.thread_count(), &paths, |path| {
+                Ok(self.fmt_create(path, &path.with_extension("")))
+            })?;
+            for (path, res) in paths.iter().zip(results) {
+                if let Err(error) = res {
+                    if !self.options.is_unsafe {
+                        return Err(error);
+                    }
+
+                    if !self.options.is_quiet {
+                        let object = self
+                            .object_path_crc32(path)
+                            .map_or_else(|| path.display().to_string(), |crc32| crc32.to_string());
+                        println!("Warn: object parser failed {}: {}", object, error);
+                    }
+                }
+            }
+        }
+
+        for path in fs::read_dir(input_path.as_ref().join("objects"))? {
+            let actual_os_path = path?.path();
+            let actual_path: &Path = actual_os_path.as_path();
+            if metadata(actual_path)?.is_file() {
+                let crc32: u32 = match actual_path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| self.names.parse_stem(stem))
+                {
+                    Some(x) => x,
+                    None => continue,
+                };
+                if let Some(other_path) = index.get(&crc32) {
+                    return Err(DpcError::AmbiguousCrc32 {
+                        crc32,
+                        paths: vec![other_path.clone(), actual_os_path.clone()],
+                    });
+                }
+
+                index.insert(crc32, actual_os_path);
+            }
+        }
+
+        // Objects left in objects/ take precedence over the store
+        if let Some(store_manifest) = &manifest_json.store {
+            let store =
+                ObjectStore::open(self.store_path.as_ref().unwrap_or(&store_manifest.path))?;
+            for (crc32, file_name) in store_manifest.objects.iter() {
+                index
+                    .entry(*crc32)
+                    .or_insert_with(|| store.object_path(file_name));
+            }
+        }
+
+        let Header {
+            version_patch,
+            version_minor,
+            block_type,
+            ..
+        } = manifest_json.header;
+
+        if version_patch == 0 && !self.options.is_unsafe {
+            return Err(DpcError::UnsupportedVersion(
+                manifest_json.header.version_string.clone(),
+            ));
+        }
+
+        let mut writer = BigFileWriter::new(
+            &manifest_json.header.version_string,
+            version_patch,
+            version_minor,
+            block_type,
+        )
+        .rtc(manifest_json.header.is_rtc)
+        .pool_manifest_unused(manifest_json.header.pool_manifest_unused)
+        .incredi_builder_string(
+            manifest_json
+                .header
+                .incredi_builder_string
+                .as_deref()
+                .unwrap_or_default(),
+        )
+        .lz(self.options.is_lz)
+        .compression_level(self.options.compression_level)
+        .threads(self.options.thread_count());
+
+        let mut object_count = 0;
+        for block in manifest_json.blocks.iter() {
+            object_count += block.objects.len();
+        }
+
+        let pb = match self.options.is_quiet {
+            false => ProgressBar::new(object_count as u64),
+            true => ProgressBar::hidden(),
+        };
+
+        if let Some(pool) = &manifest_json.pool {
+            let mut pool_layout = PoolLayout {
+                object_entry_indices: pool.object_entry_indices.clone(),
+                object_entries: pool
+                    .object_entries
+                    .iter()
+                    .map(|entry| PoolEntry {
+                        crc32: entry.crc32,
+                        reference_record_index: entry.reference_record_index,
+                    })
+                    .collect(),
+                reference_records: pool
+                    .reference_records
+                    .iter()
+                    .map(|record| PoolReferenceRecord {
+                        object_entries_starting_index: record.object_entries_starting_index,
+                        object_entries_count: record.object_entries_count,
+                    })
+                    .collect(),
+            };
+
+            if self.options.is_optimization && !self.unoptimized_pool {
+                pb.println("Optimizing the pool");
+                pool_layout.optimize()?;
+            }
+
+            writer.set_pool_layout(pool_layout);
+        }
+
+        for (x, block) in manifest_json.blocks.iter().enumerate() {
+            pb.println(format!(
+                "Processing block {}/{}",
+                x + 1,
+                manifest_json.blocks.len()
+            ));
+
+            let block_index = writer.add_block(block.offset);
+
+            for object in block.objects.iter() {
+                pb.println(format!("Processing {}", object.crc32));
+
+                if writer.contains(object.crc32) {
+                    writer.push_existing(block_index, object.crc32, object.compress)?;
+                } else {
+                    let object_path = index
+                        .get(&object.crc32)
+                        .ok_or(DpcError::MissingObject(object.crc32))?;
+                    let bigfile_object = BigFileObject::read(&mut File::open(object_path)?)
+                        .map_err(|error| error.for_object(object.crc32))?;
+
+                    writer.push(block_index, bigfile_object, object.compress)?;
+                }
+
+                pb.inc(1);
+            }
+        }
+
+        if self.options.is_lz {
+            pb.println("Compressing objects");
+            let start = Instant::now();
+            let report = writer.compress()?;
+            self.print_compression_report(&pb, &report, start.elapsed());
+        }
+
+        // The output only replaces an existing BigFile once it is written and verified
+        let result = base_dpc::write_output(output_path, |dpc_file| {
+            pb.println("Writing BigFile");
+            writer.finish(dpc_file)?;
+
+            if self.is_verify {
+                pb.println("Verifying BigFile");
+                let mut mismatches = verify::verify_layout(dpc_file)?;
+                let bigfile = BigFile::read(dpc_file)?;
+                mismatches.extend(verify::verify_objects(
+                    &bigfile,
+                    &index,
+                    self.options.thread_count(),
+                )?);
+                if !mismatches.is_empty() {
+                    return Err(DpcError::VerifyFailed(mismatches));
+                }
+            }
+
+            Ok(())
+        });
+
+        pb.finish_and_clear();
+        result?;
+
+        Ok(OutputStatus::Written)
+    }
+
+    fn patch<P: AsRef<Path>>(
+        &mut self,
+        input_path: &P,
+        objects_path: &P,
+        output_path: &P,
+    ) -> Result<OutputStatus> {
+        self.names = NameTable::from_files(&self.name_files)?;
+
+        if !base_dpc::should_write(self.options.overwrite_policy, output_path, "DPC")? {
+            return Ok(OutputStatus::Skipped(output_path.as_ref().to_path_buf()));
+        }
+
+        let index = BigFileIndex::new(BufReader::new(File::open(input_path.as_ref())?))?;
+        self.version = index.header.version_string.clone();
+
+        let objects_path = objects_path.as_ref();
+        let mut object_paths = vec![];
+        if objects_path.is_dir() && objects_path.extension() != Some(OsStr::new("d")) {
+            for entry in fs::read_dir(objects_path)? {
+                object_paths.push(entry?.path());
+            }
+            object_paths.sort();
+        } else {
+            object_paths.push(objects_path.to_path_buf());
+        }
+
+        let objects = parallel_map(self.options.thread_count(), &object_paths, |path| {
+            if path.is_dir() {
+                self.pack_object(path)
+            } else {
+                BigFileObject::read(&mut File::open(path)?)
+            }
+        })?;
+
+        let mut patch = BigFilePatch::new()
+            .compression_level(self.options.compression_level)
+            .threads(self.options.thread_count());
+        for object in objects {
+            let crc32 = object.header.crc32;
+            if index.contains(crc32) {
+                patch.replace(object);
+            } else if let Some(block_index) = self.patch_block {
+                patch.add(block_index, object, self.options.is_lz);
+            } else {
+                return Err(DpcError::MissingObject(crc32));
+            }
+        }
+        drop(index);
+
+        // The input is streamed and closed again before the output is renamed over it, so the
+        // output can replace the input
+        base_dpc::write_output(output_path, |output| {
+            let mut input = BufReader::new(File::open(input_path.as_ref())?);
+            patch.apply(&mut input, output)
+        })?;
+
+        Ok(OutputStatus::Written)
+    }
+
+    fn diff<P: AsRef<Path>>(
+        &self,
+        old_path: &P,
+        new_path: &P,
+        output_path: &P,
+    ) -> Result<OutputStatus> {
+        if !base_dpc::should_write(self.options.overwrite_policy, output_path, "json")? {
+            return Ok(OutputStatus::Skipped(output_path.as_ref().to_path_buf()));
+        }
+
+        let old = BigFile::read(&mut File::open(old_path.as_ref())?)?;
+        let new = BigFile::read(&mut File::open(new_path.as_ref())?)?;
+
+        let bigfile_diff = diff::diff(
+            &old,
+            &new,
+            self.options.thread_count(),
+            |version, object, data| self.parse_object_json(version, object, data),
+        )?;
+
+        base_dpc::write_output(output_path, |output| {
+            Ok(serde_json::to_writer_pretty(output, &bigfile_diff)?)
+        })?;
+
+        if !self.options.is_quiet {
+            println!(
+                "{} added, {} removed, {} changed, {} moved",
+                bigfile_diff.added.len(),
+                bigfile_diff.removed.len(),
+                bigfile_diff.changed.len(),
+                bigfile_diff.moved.len()
+            );
+        }
+
+        Ok(OutputStatus::Written)
+    }
+
+    fn validate<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<OutputStatus> {
+        let mut dpc_file = File::open(input_path.as_ref())?;
+
+        if !base_dpc::should_write(self.options.overwrite_policy, output_path, "json")? {
+            return Ok(OutputStatus::Skipped(output_path.as_ref().to_path_buf()));
+        }
+
+        let mut primary_header_buffer = Vec::new();
+        dpc_file.read_to_end(&mut primary_header_buffer)?;
+
+        #[derive(Serialize, NomLE, Clone, Debug, PartialEq, Eq)]
+        struct DPCObjectHeader {
+            data_size: u32,
+            class_object_size: u32,
+            decompressed_size: u32,
+            #[nom(
+                Verify = "if *compressed_size != 0 { data_size == class_object_size + *compressed_size } else { data_size == class_object_size + decompressed_size }"
+            )]
+            compressed_size: u32,
+            class_crc32: u32,
+            #[nom(SkipAfter(data_size))]
+            crc32: u32,
+        }
+
+        #[derive(Serialize, NomLE, Clone, Debug, PartialEq, Eq)]
+        struct DPCPoolObjectHeader {
+            data_size: u32,
+            class_object_size: u32,
+            decompressed_size: u32,
+            #[nom(
+                Verify = "if *compressed_size != 0 { data_size == class_object_size + *compressed_size } else { data_size == class_object_size + decompressed_size }"
+            )]
+            compressed_size: u32,
+            class_crc32: u32,
+            #[nom(SkipAfter(calculate_padded_size(data_size + 24) as usize - 24))]
+            crc32: u32,
+        }
+
+        #[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+        struct DPCBlock {
+            objects: Vec<DPCObjectHeader>,
+        }
+
+        // named_args!(parse_dpcblock(padding_size: usize, object_count: usize)<DPCBlock>, do_parse!(
+        //     objects: count(DPCObjectHeader::parse, object_count) >>
+        //     take!(padding_size) >>
+        //     (DPCBlock { objects: objects })
+        // ));
+        fn parse_dpcblock(
+            i: &[u8],
+            padding_size: usize,
+            object_count: usize,
+        ) -> IResult<&[u8], DPCBlock, nom::error::Error<&[u8]>> {
+            let parse = map_res(
+                take::<usize, &[u8], NomError<&[u8]>>(padding_size),
+                count(DPCObjectHeader::parse, object_count),
+            )(i);
+            parse.map(|obj| (obj.1 .0, DPCBlock { objects: obj.1 .1 }))
+        }
+
+        #[derive(Serialize, Nom, Clone, Debug, PartialEq, Eq)]
+        struct DPCPool {
+            #[nom(AlignAfter(2048))]
+            manifest: PoolManifest,
+            #[nom(Count = "manifest.objects_crc32s.len()")]
+            #[nom(AlignAfter(2048))]
+            objects: Vec<DPCPoolObjectHeader>,
+        }
+
+        #[derive(Serialize, Nom, Clone, Debug, PartialEq, Eq)]
+        #[nom(Exact)]
+        struct DPCFile {
+            #[nom(AlignAfter(2048))]
+            primary_header: PrimaryHeader,
+            #[nom(
+                PreExec = "let mut x = 0;",
+                Count = "primary_header.block_count",
+                Parse = "|i| { let res = parse_dpcblock(i, calculate_padding_size(primary_header.block_descriptions[x].data_size) as usize, primary_header.block_descriptions[x].object_count as usize); x += 1; res }"
+            )]
+            blocks: Vec<DPCBlock>,
+            #[nom(Cond = "primary_header.pool_manifest_offset != 0")]
+            #[nom(AlignAfter(2048))]
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pool: Option<DPCPool>,
+        }
+
+        let dpc_json = match DPCFile::parse(&primary_header_buffer[..]) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("dpc", 0, &primary_header_buffer, error)),
+        };
+
+        let mut output_file = File::create(output_path.as_ref())?;
+        output_file.write_all(serde_json::to_string_pretty(&dpc_json)?.as_bytes())?;
+
+        Ok(OutputStatus::Written)
+    }
+
+    fn compress_object<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()> {
+        let mut input_file = File::open(input_path)?;
+        let mut output_file = File::create(output_path)?;
+
+        let mut object_header_buffer = [0; 24];
+        input_file.read_exact(&mut object_header_buffer)?;
+
+        let mut object_header = match ObjectHeader::parse(&object_header_buffer) {
+            Ok((_, h)) => h,
+            Err(error) => {
+                return Err(DpcError::parse(
+                    "object header",
+                    0,
+                    &object_header_buffer,
+                    error,
+                ))
+            }
+        };
+
+        if object_header.compressed_size != 0 {
+            return Err(DpcError::AlreadyCompressed(object_header.crc32));
+        }
+
+        let mut class_object_data = vec![0; object_header.class_object_size as usize];
+        input_file.read_exact(&mut class_object_data)?;
+
+        let mut decompressed_buffer = vec![0; object_header.decompressed_size as usize];
+        input_file.read_exact(&mut decompressed_buffer)?;
+
+        let mut encoder =
+            LzrsEncoder::new(vec![]).compression_level(self.options.compression_level);
+        encoder.write_all(&decompressed_buffer)?;
+        let compressed_buffer = encoder.finish()?;
+
+        object_header.compressed_size = compressed_buffer.len() as u32;
+        object_header.data_size = object_header.class_object_size + object_header.compressed_size;
+
+        object_header.write(&mut output_file)?;
+        output_file.write_all(&class_object_data)?;
+        output_file.write_all(&compressed_buffer)?;
+
+        Ok(())
+    }
+
+    fn decompress_object<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()> {
+        let mut input_file = File::open(input_path)?;
+        let mut output_file = File::create(output_path)?;
+
+        let mut object_header_buffer = [0; 24];
+        input_file.read_exact(&mut object_header_buffer)?;
+
+        let mut object_header = match ObjectHeader::parse(&object_header_buffer) {
+            Ok((_, h)) => h,
+            Err(error) => {
+                return Err(DpcError::parse(
+                    "object header",
+                    0,
+                    &object_header_buffer,
+                    error,
+                ))
+            }
+        };
+
+        if object_header.compressed_size == 0 {
+            return Err(DpcError::AlreadyDecompressed(object_header.crc32));
+        }
+
+        let mut class_object_data = vec![0; object_header.class_object_size as usize];
+        input_file.read_exact(&mut class_object_data)?;
+
+        let mut decompressed_buffer = Vec::with_capacity(object_header.decompressed_size as usize);
+        LzrsDecoder::new(&mut input_file)?.read_to_end(&mut decompressed_buffer)?;
+
+        object_header.compressed_size = 0;
+        object_header.data_size = object_header.class_object_size + object_header.decompressed_size;
+
+        object_header.write(&mut output_file)?;
+        output_file.write_all(&class_object_data)?;
+        output_file.write_all(&decompressed_buffer)?;
+
+        Ok(())
+    }
+
+    fn split_object<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()> {
+        let extension = match output_path.as_ref().extension().and_then(|x| x.to_str()) {
+            Some(x) => x.to_owned(),
+            None => {
+                return Err(DpcError::InvalidObjectPath(
+                    output_path.as_ref().to_path_buf(),
+                ))
+            }
+        };
+
+        let mut header_path = output_path.as_ref().to_path_buf();
+        header_path.set_extension(extension.clone() + ".header");
+        let mut header_file = File::create(header_path)?;
+
+        let mut data_path = output_path.as_ref().to_path_buf();
+        data_path.set_extension(extension + ".data");
+        let mut data_file = File::create(data_path)?;
+
+        let mut input_file = File::open(input_path)?;
+
+        let mut object_header_buffer = [0; 24];
+        input_file.read_exact(&mut object_header_buffer)?;
+
+        let object_header = match ObjectHeader::parse(&object_header_buffer) {
+            Ok((_, h)) => h,
+            Err(error) => {
+                return Err(DpcError::parse(
+                    "object header",
+                    0,
+                    &object_header_buffer,
+                    error,
+                ))
+            }
+        };
+
+        let mut header_buffer = vec![0; object_header.class_object_size as usize];
+        input_file.read_exact(&mut header_buffer)?;
+        header_file.write_all(&header_buffer)?;
+
+        let mut data_buffer = vec![0; object_header.body_size()? as usize];
+        input_file.read_exact(&mut data_buffer)?;
+        data_file.write_all(&data_buffer)?;
+
+        Ok(())
+    }
+
+    fn fmt_extract<P: AsRef<Path>>(
+        &self,
+        input_path: &P,
+        output_path: &P,
+    ) -> Result<(Vec<u32>, Vec<u32>)> {
+        let object = BigFileObject::read(&mut File::open(input_path)?)?;
+
+        self.unpack_object(
+            &object.header,
+            &object.class_object,
+            &object.data,
+            output_path.as_ref(),
+        )
+    }
+
+    fn fmt_create<P: AsRef<Path>>(&self, input_path: &P, output_path: &P) -> Result<()> {
+        let object = self.pack_object(input_path.as_ref())?;
+        object.write(&mut File::create(output_path)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::base_dpc::OverwritePolicy;
+    use crate::lz::CompressionLevel;
+
+    fn options() -> Options {
+        Options {
+            is_quiet: true,
+            overwrite_policy: OverwritePolicy::Error,
+            is_unsafe: false,
+            is_lz: false,
+            is_optimization: false,
+            compression_level: CompressionLevel::default(),
+            is_recursive: false,
+            threads: 1,
+        }
+    }
+
+    #[test]
+    fn test_new_game() {
+        let dpc = GameDPC::new(&options(), &[OsStr::new("--game=walle")]).unwrap();
+        assert_eq!(dpc.profile.name, "WALL-E");
+
+        let dpc = GameDPC::new(&options(), &[OsStr::new("-G"), OsStr::new("FUELL")]);
+        assert!(matches!(dpc, Err(DpcError::UnsupportedGame(name)) if name == "FUELL"));
+        let dpc = GameDPC::new(&options(), &[OsStr::new("-G")]);
+        assert!(matches!(dpc, Err(DpcError::UnsupportedGame(name)) if name.is_empty()));
+
+        // Bad backend arguments are an error rather than exiting the process
+        let dpc = GameDPC::new(&options(), &[OsStr::new("--no-such-flag")]);
+        assert!(matches!(dpc, Err(DpcError::Format(_))));
+        let dpc = GameDPC::new(&options(), &[OsStr::new("--patch-block=one")]);
+        assert!(matches!(dpc, Err(DpcError::Format(_))));
+    }
+
+    #[test]
+    fn test_input_source_read_at() {
+        let dir = tempdir::TempDir::new("dpc-input").unwrap();
+        let path = dir.path().join("input.DPC");
+        fs::write(&path, (0..10).collect::<Vec<u8>>()).unwrap();
+
+        for is_mmap in [false, true] {
+            let input = InputSource::open(&path, is_mmap).unwrap();
+            assert_eq!(input.len().unwrap(), 10);
+            assert_eq!(&*input.read_at(2, 4).unwrap(), &[2, 3, 4, 5]);
+            assert_eq!(&*input.read_at(6, 4).unwrap(), &[6, 7, 8, 9]);
+            assert!(matches!(input.read_at(8, 4), Err(DpcError::Format(_))));
+            assert!(matches!(
+                input.read_at(u64::MAX, 1),
+                Err(DpcError::Format(_))
+            ));
+        }
+    }
+}
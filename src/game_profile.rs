@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use crate::asobo_fmt;
+use crate::base_dpc::{FormatOptions, ObjectFormatTrait};
+use crate::error::Result;
+use crate::fuel_fmt;
+use crate::walle_fmt;
+
+// Adds the formats of a version that differ from the shared ones in asobo_fmt
+pub type FormatRegistry =
+    fn(&str, &FormatOptions, &mut HashMap<u32, &'static dyn ObjectFormatTrait>) -> Result<()>;
+
+pub struct GameProfile {
+    pub name: &'static str,
+    pub author: &'static str,
+    // version_string -> (version_patch, version_minor, block_type)
+    pub version_lookup: &'static [(&'static str, (u32, u32, u32))],
+    pub default_version: &'static str,
+    pub class_names: &'static [(u32, &'static str)],
+    pub formats: FormatRegistry,
+    pub has_pool: bool,
+}
+
+impl GameProfile {
+    pub fn lookup_version(&self, version_string: &str) -> Option<(u32, u32, u32)> {
+        self.version_lookup
+            .iter()
+            .find(|(x, _)| *x == version_string)
+            .map(|(_, x)| *x)
+    }
+
+    pub fn object_formats(
+        &self,
+        version: &str,
+        options: &FormatOptions,
+    ) -> Result<HashMap<u32, &'static dyn ObjectFormatTrait>> {
+        let mut formats = asobo_fmt::get_formats();
+        (self.formats)(version, options, &mut formats)?;
+        Ok(formats)
+    }
+
+    pub fn class_name(&self, class_crc32: u32) -> Option<&'static str> {
+        self.class_names
+            .iter()
+            .find(|(x, _)| *x == class_crc32)
+            .map(|(_, x)| *x)
+    }
+
+    pub fn class_crc32(&self, class_name: &str) -> Option<u32> {
+        self.class_names
+            .iter()
+            .find(|(_, x)| *x == class_name)
+            .map(|(x, _)| *x)
+    }
+}
+
+pub static CLASS_NAMES: &[(u32, &str)] = &[
+    (549480509, "Omni_Z"),
+    (705810152, "Rtc_Z"),
+    (838505646, "GenWorld_Z"),
+    (848525546, "LightData_Z"),
+    (849267944, "Sound_Z"),
+    (849861735, "MaterialObj_Z"),
+    (866453734, "RotShape_Z"),
+    (954499543, "ParticlesData_Z"),
+    (968261323, "World_Z"),
+    (1114947943, "Warp_Z"),
+    (1135194223, "Spline_Z"),
+    (1175485833, "Animation_Z"),
+    (1387343541, "Mesh_Z"),
+    (1391959958, "UserDefine_Z"),
+    (1396791303, "Skin_Z"),
+    (1471281566, "Bitmap_Z"),
+    (1536002910, "Fonts_Z"),
+    (1625945536, "RotShapeData_Z"),
+    (1706265229, "Surface_Z"),
+    (1910554652, "SplineGraph_Z"),
+    (1943824915, "Lod_Z"),
+    (2204276779, "Material_Z"),
+    (2245010728, "Node_Z"),
+    (2259852416, "Binary_Z"),
+    (2398393906, "CollisionVol_Z"),
+    (2906362741, "WorldRef_Z"),
+    (3312018398, "Particles_Z"),
+    (3412401859, "LodData_Z"),
+    (3611002348, "Skel_Z"),
+    (3626109572, "MeshData_Z"),
+    (3747817665, "SurfaceDatas_Z"),
+    (3834418854, "MaterialAnim_Z"),
+    (3845834591, "GwRoad_Z"),
+    (4096629181, "GameObj_Z"),
+    (4240844041, "Camera_Z"),
+    (4117606081, "AnimFrame_Z"),
+    (3979333606, "CameraZone_Z"),
+    (72309972, "Occluder_Z"),
+    (1390918523, "Graph_Z"),
+    (1918499807, "Light_Z"),
+    (3210467954, "HFogData_Z"),
+    (2735949084, "HFog_Z"),
+    (2203168663, "Flare_Z"),
+    (1393846573, "FlareData_Z"),
+];
+
+pub static FUEL: GameProfile = GameProfile {
+    name: "FUEL",
+    author: "widberg <https://github.com/widberg>",
+    version_lookup: &[
+        (
+            "v1.530.62.09 - Asobo Studio - Internal Cross Technology",
+            (290, 529, 150),
+        ),
+        (
+            "v1.381.67.09 - Asobo Studio - Internal Cross Technology",
+            (272, 380, 253),
+        ),
+        (
+            "v1.381.66.09 - Asobo Studio - Internal Cross Technology",
+            (272, 380, 252),
+        ),
+        (
+            "v1.381.65.09 - Asobo Studio - Internal Cross Technology",
+            (271, 380, 249),
+        ),
+        (
+            "v1.381.64.09 - Asobo Studio - Internal Cross Technology",
+            (271, 380, 249),
+        ),
+        (
+            "v1.379.60.09 - Asobo Studio - Internal Cross Technology",
+            (269, 380, 211),
+        ),
+        (
+            "v1.325.50.07 - Asobo Studio - Internal Cross Technology",
+            (262, 326, 146),
+        ),
+        (
+            "v1.220.50.07 - Asobo Studio - Internal Cross Technology",
+            (262, 221, 144),
+        ),
+    ],
+    default_version: "v1.381.67.09 - Asobo Studio - Internal Cross Technology",
+    class_names: CLASS_NAMES,
+    formats: fuel_fmt::get_formats,
+    has_pool: true,
+};
+
+pub static WALLE: GameProfile = GameProfile {
+    name: "WALL-E",
+    author: "pizzart",
+    version_lookup: &[
+        (
+            "v1.325.50.07 - Asobo Studio - Internal Cross Technology",
+            (262, 326, 146),
+        ),
+        (
+            "v1.291.03.06 - Asobo Studio - Internal Cross Technology",
+            (291, 298, 169),
+        ),
+        (
+            "v1.220.50.07 - Asobo Studio - Internal Cross Technology",
+            (262, 221, 144),
+        ),
+    ],
+    default_version: "v1.291.03.06 - Asobo Studio - Internal Cross Technology",
+    class_names: CLASS_NAMES,
+    formats: walle_fmt::get_formats,
+    has_pool: true,
+};
+
+pub static PROFILES: &[&GameProfile] = &[&FUEL, &WALLE];
+
+pub fn find(name: &str) -> Option<&'static GameProfile> {
+    PROFILES.iter().copied().find(|profile| {
+        profile
+            .name
+            .replace('-', "")
+            .eq_ignore_ascii_case(&name.replace('-', ""))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_versions() {
+        for profile in PROFILES.iter() {
+            assert!(
+                profile.lookup_version(profile.default_version).is_some(),
+                "{} has no lookup for {}",
+                profile.name,
+                profile.default_version
+            );
+            assert!(profile
+                .object_formats(profile.default_version, &FormatOptions::default())
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find("fuel").map(|x| x.name), Some("FUEL"));
+        assert_eq!(find("WALL-E").map(|x| x.name), Some("WALL-E"));
+        assert_eq!(find("walle").map(|x| x.name), Some("WALL-E"));
+        assert!(find("FUELL").is_none());
+    }
+}
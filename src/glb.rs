@@ -0,0 +1,934 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use gltf::binary::{Glb, Header};
+use gltf::mesh::Mode;
+use serde_json::{json, Map, Value};
+
+use crate::error::DpcError;
+use crate::names::NameTable;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+
+pub const TRIANGLES: u32 = 4;
+pub const TRIANGLE_STRIP: u32 = 5;
+
+#[derive(Default)]
+pub struct GlbVertices {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub tangents: Vec<[f32; 4]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub lightmap_uvs: Vec<[f32; 2]>,
+    pub joints: Vec<[u16; 4]>,
+    pub weights: Vec<[f32; 4]>,
+}
+
+impl GlbVertices {
+    // The vertices in range, attributes the buffer doesn't have stay empty
+    pub fn slice(&self, range: Range<usize>) -> GlbVertices {
+        fn slice<T: Copy>(data: &[T], range: &Range<usize>) -> Vec<T> {
+            data.get(range.clone())
+                .map(<[T]>::to_vec)
+                .unwrap_or_default()
+        }
+
+        GlbVertices {
+            positions: slice(&self.positions, &range),
+            normals: slice(&self.normals, &range),
+            tangents: slice(&self.tangents, &range),
+            uvs: slice(&self.uvs, &range),
+            lightmap_uvs: slice(&self.lightmap_uvs, &range),
+            joints: slice(&self.joints, &range),
+            weights: slice(&self.weights, &range),
+        }
+    }
+}
+
+pub fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if length == 0.0 {
+        [0.0, 0.0, 1.0]
+    } else {
+        [v[0] / length, v[1] / length, v[2] / length]
+    }
+}
+
+pub fn normalize_weights(weights: [f32; 4]) -> [f32; 4] {
+    let sum: f32 = weights.iter().sum();
+    if sum == 0.0 {
+        [1.0, 0.0, 0.0, 0.0]
+    } else {
+        [
+            weights[0] / sum,
+            weights[1] / sum,
+            weights[2] / sum,
+            weights[3] / sum,
+        ]
+    }
+}
+
+pub struct GlbJoint {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub model_transform: [f32; 16],
+}
+
+pub enum GlbKeyframes {
+    Translation(Vec<[f32; 3]>),
+    Rotation(Vec<[f32; 4]>),
+    Scale(Vec<[f32; 3]>),
+}
+
+// Linearly interpolated keyframes of one joint property, times in seconds
+pub struct GlbChannel {
+    pub joint: usize,
+    pub times: Vec<f32>,
+    pub keyframes: GlbKeyframes,
+}
+
+fn invert(m: [f32; 16]) -> [f32; 16] {
+    let mut inv = [0.0f32; 16];
+    inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14] - m[9] * m[6] * m[15]
+        + m[9] * m[7] * m[14]
+        + m[13] * m[6] * m[11]
+        - m[13] * m[7] * m[10];
+    inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14] + m[8] * m[6] * m[15]
+        - m[8] * m[7] * m[14]
+        - m[12] * m[6] * m[11]
+        + m[12] * m[7] * m[10];
+    inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13] - m[8] * m[5] * m[15]
+        + m[8] * m[7] * m[13]
+        + m[12] * m[5] * m[11]
+        - m[12] * m[7] * m[9];
+    inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13] + m[8] * m[5] * m[14]
+        - m[8] * m[6] * m[13]
+        - m[12] * m[5] * m[10]
+        + m[12] * m[6] * m[9];
+    inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14] + m[9] * m[2] * m[15]
+        - m[9] * m[3] * m[14]
+        - m[13] * m[2] * m[11]
+        + m[13] * m[3] * m[10];
+    inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14] - m[8] * m[2] * m[15]
+        + m[8] * m[3] * m[14]
+        + m[12] * m[2] * m[11]
+        - m[12] * m[3] * m[10];
+    inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13] + m[8] * m[1] * m[15]
+        - m[8] * m[3] * m[13]
+        - m[12] * m[1] * m[11]
+        + m[12] * m[3] * m[9];
+    inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13] - m[8] * m[1] * m[14]
+        + m[8] * m[2] * m[13]
+        + m[12] * m[1] * m[10]
+        - m[12] * m[2] * m[9];
+    inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14] - m[5] * m[2] * m[15]
+        + m[5] * m[3] * m[14]
+        + m[13] * m[2] * m[7]
+        - m[13] * m[3] * m[6];
+    inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14] + m[4] * m[2] * m[15]
+        - m[4] * m[3] * m[14]
+        - m[12] * m[2] * m[7]
+        + m[12] * m[3] * m[6];
+    inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13] - m[4] * m[1] * m[15]
+        + m[4] * m[3] * m[13]
+        + m[12] * m[1] * m[7]
+        - m[12] * m[3] * m[5];
+    inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13] + m[4] * m[1] * m[14]
+        - m[4] * m[2] * m[13]
+        - m[12] * m[1] * m[6]
+        + m[12] * m[2] * m[5];
+    inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10] + m[5] * m[2] * m[11]
+        - m[5] * m[3] * m[10]
+        - m[9] * m[2] * m[7]
+        + m[9] * m[3] * m[6];
+    inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10] - m[4] * m[2] * m[11]
+        + m[4] * m[3] * m[10]
+        + m[8] * m[2] * m[7]
+        - m[8] * m[3] * m[6];
+    inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9] + m[4] * m[1] * m[11]
+        - m[4] * m[3] * m[9]
+        - m[8] * m[1] * m[7]
+        + m[8] * m[3] * m[5];
+    inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9] - m[4] * m[1] * m[10]
+        + m[4] * m[2] * m[9]
+        + m[8] * m[1] * m[6]
+        - m[8] * m[2] * m[5];
+
+    let det = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8] + m[3] * inv[12];
+    if det == 0.0 {
+        let mut identity = [0.0; 16];
+        identity[0] = 1.0;
+        identity[5] = 1.0;
+        identity[10] = 1.0;
+        identity[15] = 1.0;
+        return identity;
+    }
+
+    for x in inv.iter_mut() {
+        *x /= det;
+    }
+    inv
+}
+
+#[derive(Default)]
+pub struct GlbBuilder {
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    skins: Vec<Value>,
+    animations: Vec<Value>,
+    bin: Vec<u8>,
+    material_indices: HashMap<u32, usize>,
+}
+
+impl GlbBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn push_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.bin.len(),
+            "byteLength": data.len()
+        });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+
+        self.bin.extend_from_slice(data);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn push_accessor(&mut self, accessor: Value) -> usize {
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    pub fn push_f32<const N: usize>(
+        &mut self,
+        data: &[[f32; N]],
+        target: Option<u32>,
+        with_bounds: bool,
+    ) -> usize {
+        let mut bytes = Vec::with_capacity(data.len() * N * 4);
+        for element in data {
+            for component in element {
+                bytes.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+        let buffer_view = self.push_view(&bytes, target);
+
+        let type_ = match N {
+            1 => "SCALAR",
+            2 => "VEC2",
+            3 => "VEC3",
+            4 => "VEC4",
+            16 => "MAT4",
+            _ => unreachable!(),
+        };
+
+        let mut accessor = json!({
+            "bufferView": buffer_view,
+            "componentType": FLOAT,
+            "count": data.len(),
+            "type": type_
+        });
+
+        if with_bounds {
+            let mut min = [f32::MAX; N];
+            let mut max = [f32::MIN; N];
+            for element in data {
+                for i in 0..N {
+                    min[i] = min[i].min(element[i]);
+                    max[i] = max[i].max(element[i]);
+                }
+            }
+            accessor["min"] = json!(min.to_vec());
+            accessor["max"] = json!(max.to_vec());
+        }
+
+        self.push_accessor(accessor)
+    }
+
+    pub fn push_joints(&mut self, data: &[[u16; 4]]) -> usize {
+        let mut bytes = Vec::with_capacity(data.len() * 8);
+        for element in data {
+            for component in element {
+                bytes.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+        let buffer_view = self.push_view(&bytes, Some(ARRAY_BUFFER));
+
+        self.push_accessor(json!({
+            "bufferView": buffer_view,
+            "componentType": UNSIGNED_SHORT,
+            "count": data.len(),
+            "type": "VEC4"
+        }))
+    }
+
+    pub fn push_indices(&mut self, data: &[u32]) -> usize {
+        let mut bytes = Vec::with_capacity(data.len() * 4);
+        for index in data {
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+        let buffer_view = self.push_view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+
+        self.push_accessor(json!({
+            "bufferView": buffer_view,
+            "componentType": UNSIGNED_INT,
+            "count": data.len(),
+            "type": "SCALAR"
+        }))
+    }
+
+    pub fn push_vertices(&mut self, vertices: &GlbVertices) -> Value {
+        let mut attributes = Map::new();
+
+        let position = self.push_f32(&vertices.positions, Some(ARRAY_BUFFER), true);
+        attributes.insert("POSITION".to_string(), json!(position));
+
+        if !vertices.normals.is_empty() {
+            let normal = self.push_f32(&vertices.normals, Some(ARRAY_BUFFER), false);
+            attributes.insert("NORMAL".to_string(), json!(normal));
+        }
+
+        if !vertices.tangents.is_empty() {
+            let tangent = self.push_f32(&vertices.tangents, Some(ARRAY_BUFFER), false);
+            attributes.insert("TANGENT".to_string(), json!(tangent));
+        }
+
+        if !vertices.uvs.is_empty() {
+            let uv = self.push_f32(&vertices.uvs, Some(ARRAY_BUFFER), false);
+            attributes.insert("TEXCOORD_0".to_string(), json!(uv));
+        }
+
+        if !vertices.lightmap_uvs.is_empty() {
+            let lightmap_uv = self.push_f32(&vertices.lightmap_uvs, Some(ARRAY_BUFFER), false);
+            attributes.insert("TEXCOORD_1".to_string(), json!(lightmap_uv));
+        }
+
+        if !vertices.joints.is_empty() {
+            let joints = self.push_joints(&vertices.joints);
+            attributes.insert("JOINTS_0".to_string(), json!(joints));
+            let weights = self.push_f32(&vertices.weights, Some(ARRAY_BUFFER), false);
+            attributes.insert("WEIGHTS_0".to_string(), json!(weights));
+        }
+
+        Value::Object(attributes)
+    }
+
+    pub fn primitive(
+        attributes: &Value,
+        indices: Option<usize>,
+        material: Option<usize>,
+        mode: u32,
+    ) -> Value {
+        let mut primitive = json!({
+            "attributes": attributes,
+            "mode": mode
+        });
+        if let Some(indices) = indices {
+            primitive["indices"] = json!(indices);
+        }
+        if let Some(material) = material {
+            primitive["material"] = json!(material);
+        }
+        primitive
+    }
+
+    // Named from names when it has the crc32, which import reads back from the extras
+    pub fn material(&mut self, crc32: u32, names: &NameTable) -> usize {
+        if let Some(index) = self.material_indices.get(&crc32) {
+            return *index;
+        }
+
+        self.materials.push(json!({
+            "name": names.name_or_crc32(crc32),
+            "pbrMetallicRoughness": {
+                "metallicFactor": 0.0
+            },
+            "extras": {
+                "crc32": crc32
+            }
+        }));
+        self.material_indices
+            .insert(crc32, self.materials.len() - 1);
+        self.materials.len() - 1
+    }
+
+    pub fn add_mesh(&mut self, name: &str, primitives: Vec<Value>) -> usize {
+        self.meshes.push(json!({
+            "name": name,
+            "primitives": primitives
+        }));
+        self.meshes.len() - 1
+    }
+
+    pub fn add_node(&mut self, node: Value) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    pub fn add_skin(&mut self, name: &str, joints: &[GlbJoint]) -> usize {
+        let base = self.nodes.len();
+        for joint in joints {
+            let scale = if joint.scale.iter().all(|x| *x == 0.0) {
+                [1.0; 3]
+            } else {
+                joint.scale
+            };
+            self.nodes.push(json!({
+                "name": joint.name,
+                "translation": joint.translation,
+                "rotation": joint.rotation,
+                "scale": scale
+            }));
+        }
+
+        let mut roots = Vec::new();
+        for (i, joint) in joints.iter().enumerate() {
+            match joint.parent {
+                Some(parent) if parent < joints.len() && parent != i => {
+                    let children = &mut self.nodes[base + parent]["children"];
+                    if !children.is_array() {
+                        *children = json!([]);
+                    }
+                    children.as_array_mut().unwrap().push(json!(base + i));
+                }
+                _ => roots.push(base + i),
+            }
+        }
+
+        let inverse_bind_matrices: Vec<[f32; 16]> =
+            joints.iter().map(|x| invert(x.model_transform)).collect();
+        let inverse_bind_matrices = self.push_f32(&inverse_bind_matrices, None, false);
+
+        let mut skin = json!({
+            "name": name,
+            "joints": (base..base + joints.len()).collect::<Vec<usize>>(),
+            "inverseBindMatrices": inverse_bind_matrices
+        });
+        if let Some(root) = roots.first() {
+            skin["skeleton"] = json!(root);
+        }
+
+        self.skins.push(skin);
+        self.skins.len() - 1
+    }
+
+    // Channel joints index the joints of skin, in the order they were given to add_skin. Channels
+    // of joints the skin doesn't have are left out
+    pub fn add_animation(&mut self, name: &str, skin: usize, channels: &[GlbChannel]) -> usize {
+        let mut samplers = Vec::new();
+        let mut targets = Vec::new();
+        for channel in channels {
+            let node = match self.skins[skin]["joints"][channel.joint].as_u64() {
+                Some(node) => node,
+                None => continue,
+            };
+
+            let times: Vec<[f32; 1]> = channel.times.iter().map(|x| [*x]).collect();
+            let input = self.push_f32(&times, None, true);
+            let (output, path) = match &channel.keyframes {
+                GlbKeyframes::Translation(values) => {
+                    (self.push_f32(values, None, false), "translation")
+                }
+                GlbKeyframes::Rotation(values) => (self.push_f32(values, None, false), "rotation"),
+                GlbKeyframes::Scale(values) => (self.push_f32(values, None, false), "scale"),
+            };
+
+            targets.push(json!({
+                "sampler": samplers.len(),
+                "target": { "node": node, "path": path }
+            }));
+            samplers.push(json!({
+                "input": input,
+                "output": output,
+                "interpolation": "LINEAR"
+            }));
+        }
+
+        self.animations.push(json!({
+            "name": name,
+            "samplers": samplers,
+            "channels": targets
+        }));
+        self.animations.len() - 1
+    }
+
+    pub fn write<W: Write>(mut self, writer: &mut W) -> Result<(), DpcError> {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+
+        let mut children = HashSet::new();
+        for node in self.nodes.iter() {
+            if let Some(node_children) = node["children"].as_array() {
+                for child in node_children {
+                    if let Some(child) = child.as_u64() {
+                        children.insert(child as usize);
+                    }
+                }
+            }
+        }
+        let roots: Vec<usize> = (0..self.nodes.len())
+            .filter(|x| !children.contains(x))
+            .collect();
+
+        let mut root = Map::new();
+        root.insert(
+            "asset".to_string(),
+            json!({ "version": "2.0", "generator": "dpc" }),
+        );
+        root.insert("scene".to_string(), json!(0));
+        root.insert("scenes".to_string(), json!([{ "nodes": roots }]));
+
+        let arrays = [
+            ("accessors", self.accessors),
+            ("bufferViews", self.buffer_views),
+            ("materials", self.materials),
+            ("meshes", self.meshes),
+            ("nodes", self.nodes),
+            ("skins", self.skins),
+            ("animations", self.animations),
+        ];
+        for (key, array) in arrays {
+            if !array.is_empty() {
+                root.insert(key.to_string(), Value::Array(array));
+            }
+        }
+
+        if !self.bin.is_empty() {
+            root.insert(
+                "buffers".to_string(),
+                json!([{ "byteLength": self.bin.len() }]),
+            );
+        }
+
+        let json = serde_json::to_vec(&Value::Object(root))?;
+
+        let glb = Glb {
+            header: Header {
+                magic: *b"glTF",
+                version: 2,
+                length: 0,
+            },
+            json: Cow::Owned(json),
+            bin: if self.bin.is_empty() {
+                None
+            } else {
+                Some(Cow::Owned(self.bin))
+            },
+        };
+
+        glb.to_writer(writer)?;
+
+        Ok(())
+    }
+}
+
+pub struct ImportedPrimitive {
+    pub vertices: GlbVertices,
+    pub indices: Vec<u32>,
+    // The material crc32, from the extras or a material named by its crc32
+    pub material: Option<u32>,
+}
+
+pub fn find_import(input_path: &Path) -> Option<PathBuf> {
+    ["import.glb", "import.gltf", "import.obj"]
+        .iter()
+        .map(|name| input_path.join(name))
+        .find(|path| path.is_file())
+}
+
+pub fn import_primitives(path: &Path) -> Result<Vec<ImportedPrimitive>, DpcError> {
+    let mut primitives = match path.extension().and_then(|x| x.to_str()) {
+        Some("obj") => import_obj(path)?,
+        _ => import_gltf(path)?,
+    };
+
+    for primitive in primitives.iter_mut() {
+        complete_vertices(&mut primitive.vertices, &primitive.indices);
+    }
+
+    Ok(primitives)
+}
+
+fn import_gltf(path: &Path) -> Result<Vec<ImportedPrimitive>, DpcError> {
+    let (document, buffers, _) = gltf::import(path)?;
+
+    let mut primitives = Vec::new();
+    for mesh in document.meshes() {
+        // The legacy strip geometry is exported for preview only
+        if mesh.name() == Some("strips") {
+            continue;
+        }
+
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            let positions = match reader.read_positions() {
+                Some(positions) => positions.collect(),
+                None => continue,
+            };
+            let mut vertices = GlbVertices {
+                positions,
+                ..Default::default()
+            };
+            if let Some(normals) = reader.read_normals() {
+                vertices.normals = normals.collect();
+            }
+            if let Some(tangents) = reader.read_tangents() {
+                vertices.tangents = tangents.collect();
+            }
+            if let Some(uvs) = reader.read_tex_coords(0) {
+                vertices.uvs = uvs.into_f32().collect();
+            }
+            if let Some(lightmap_uvs) = reader.read_tex_coords(1) {
+                vertices.lightmap_uvs = lightmap_uvs.into_f32().collect();
+            }
+            if let (Some(joints), Some(weights)) = (reader.read_joints(0), reader.read_weights(0)) {
+                vertices.joints = joints.into_u16().collect();
+                vertices.weights = weights.into_f32().map(normalize_weights).collect();
+            }
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..vertices.positions.len() as u32).collect(),
+            };
+            let indices = match primitive.mode() {
+                Mode::Triangles => indices,
+                Mode::TriangleStrip => strip_to_triangles(&indices),
+                mode => {
+                    return Err(DpcError::Format(format!(
+                        "unsupported glTF primitive mode {:?} in {}",
+                        mode,
+                        path.display()
+                    )))
+                }
+            };
+
+            primitives.push(ImportedPrimitive {
+                vertices,
+                indices,
+                material: gltf_material_crc32(&primitive.material()),
+            });
+        }
+    }
+
+    Ok(primitives)
+}
+
+fn import_obj(path: &Path) -> Result<Vec<ImportedPrimitive>, DpcError> {
+    let obj = obj::Obj::load(path)?;
+    let data = &obj.data;
+
+    let mut primitives = Vec::new();
+    for object in data.objects.iter() {
+        for group in object.groups.iter() {
+            let mut vertices = GlbVertices::default();
+            let mut indices = Vec::new();
+            let mut vertex_indices: HashMap<obj::IndexTuple, u32> = HashMap::new();
+            let has_uvs = group
+                .polys
+                .iter()
+                .all(|x| x.0.iter().all(|x| x.1.is_some()));
+            let has_normals = group
+                .polys
+                .iter()
+                .all(|x| x.0.iter().all(|x| x.2.is_some()));
+
+            for polygon in group.polys.iter() {
+                let mut polygon_indices = Vec::with_capacity(polygon.0.len());
+                for tuple in polygon.0.iter() {
+                    let index = match vertex_indices.get(tuple) {
+                        Some(index) => *index,
+                        None => {
+                            let position = *data.position.get(tuple.0).ok_or_else(|| {
+                                DpcError::Format(format!(
+                                    "position index {} out of range in {}",
+                                    tuple.0,
+                                    path.display()
+                                ))
+                            })?;
+                            vertices.positions.push(position);
+                            if has_uvs {
+                                let uv = data.texture.get(tuple.1.unwrap()).copied();
+                                vertices.uvs.push(uv.unwrap_or([0.0; 2]));
+                            }
+                            if has_normals {
+                                let normal = data.normal.get(tuple.2.unwrap()).copied();
+                                vertices.normals.push(normalize(normal.unwrap_or([0.0; 3])));
+                            }
+                            let index = vertex_indices.len() as u32;
+                            vertex_indices.insert(*tuple, index);
+                            index
+                        }
+                    };
+                    polygon_indices.push(index);
+                }
+
+                for i in 1..polygon_indices.len().saturating_sub(1) {
+                    indices.push(polygon_indices[0]);
+                    indices.push(polygon_indices[i]);
+                    indices.push(polygon_indices[i + 1]);
+                }
+            }
+
+            if indices.is_empty() {
+                continue;
+            }
+
+            let material = group.material.as_ref().and_then(|material| match material {
+                obj::ObjMaterial::Ref(name) => name.parse().ok(),
+                obj::ObjMaterial::Mtl(material) => material.name.parse().ok(),
+            });
+
+            primitives.push(ImportedPrimitive {
+                vertices,
+                indices,
+                material,
+            });
+        }
+    }
+
+    Ok(primitives)
+}
+
+fn gltf_material_crc32(material: &gltf::Material) -> Option<u32> {
+    material
+        .extras()
+        .as_ref()
+        .and_then(|extras| serde_json::from_str::<Value>(extras.get()).ok())
+        .and_then(|extras| extras.get("crc32")?.as_u64())
+        .map(|crc32| crc32 as u32)
+        .or_else(|| material.name()?.parse().ok())
+}
+
+fn strip_to_triangles(strip: &[u32]) -> Vec<u32> {
+    let mut indices = Vec::new();
+    for i in 2..strip.len() {
+        let (a, b, c) = if i % 2 == 0 {
+            (strip[i - 2], strip[i - 1], strip[i])
+        } else {
+            (strip[i - 1], strip[i - 2], strip[i])
+        };
+        if a != b && b != c && a != c {
+            indices.extend_from_slice(&[a, b, c]);
+        }
+    }
+    indices
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn complete_vertices(vertices: &mut GlbVertices, indices: &[u32]) {
+    let count = vertices.positions.len();
+
+    if vertices.normals.len() != count {
+        let mut normals = vec![[0.0f32; 3]; count];
+        for triangle in indices.chunks_exact(3) {
+            let (a, b, c) = (
+                triangle[0] as usize,
+                triangle[1] as usize,
+                triangle[2] as usize,
+            );
+            if a >= count || b >= count || c >= count {
+                continue;
+            }
+            let p = &vertices.positions;
+            let normal = cross(sub(p[b], p[a]), sub(p[c], p[a]));
+            for index in [a, b, c] {
+                for i in 0..3 {
+                    normals[index][i] += normal[i];
+                }
+            }
+        }
+        vertices.normals = normals.into_iter().map(normalize).collect();
+    }
+
+    if vertices.tangents.len() != count {
+        vertices.tangents = vertices
+            .normals
+            .iter()
+            .map(|normal| {
+                let axis = if normal[0].abs() < 0.9 {
+                    [1.0, 0.0, 0.0]
+                } else {
+                    [0.0, 1.0, 0.0]
+                };
+                let tangent = normalize(cross(axis, *normal));
+                [tangent[0], tangent[1], tangent[2], 1.0]
+            })
+            .collect();
+    }
+
+    if vertices.uvs.len() != count {
+        vertices.uvs = vec![[0.0; 2]; count];
+    }
+
+    if vertices.lightmap_uvs.len() != count {
+        vertices.lightmap_uvs = vertices.uvs.clone();
+    }
+
+    if vertices.joints.len() != count || vertices.weights.len() != count {
+        vertices.joints = vec![[0; 4]; count];
+        vertices.weights = vec![[1.0, 0.0, 0.0, 0.0]; count];
+    }
+}
+
+pub fn bounding_sphere(positions: &[[f32; 3]]) -> ([f32; 3], f32) {
+    if positions.is_empty() {
+        return ([0.0; 3], 0.0);
+    }
+
+    let (min, max) = bounds(positions);
+    let center = [
+        (min[0] + max[0]) / 2.0,
+        (min[1] + max[1]) / 2.0,
+        (min[2] + max[2]) / 2.0,
+    ];
+    let radius = positions
+        .iter()
+        .map(|x| {
+            let d = sub(*x, center);
+            (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt()
+        })
+        .fold(0.0, f32::max);
+
+    (center, radius)
+}
+
+pub fn bounds(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for position in positions {
+        for i in 0..3 {
+            min[i] = min[i].min(position[i]);
+            max[i] = max[i].max(position[i]);
+        }
+    }
+    (min, max)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs::File;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn quad() -> GlbVertices {
+        GlbVertices {
+            positions: vec![
+                [-1.0, -1.0, 0.0],
+                [1.0, -1.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [-1.0, 1.0, 0.0],
+            ],
+            uvs: vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_glb_roundtrip() {
+        let vertices = quad();
+        let mut builder = GlbBuilder::new();
+        let attributes = builder.push_vertices(&vertices);
+        let triangles = builder.push_indices(&[0, 1, 2, 0, 2, 3]);
+        let strip = builder.push_indices(&[0, 1, 3, 2]);
+        let mut names = NameTable::new();
+        names.insert(1234, "paint");
+        let material = builder.material(1234, &names);
+        let primitives = vec![
+            GlbBuilder::primitive(&attributes, Some(triangles), Some(material), TRIANGLES),
+            GlbBuilder::primitive(&attributes, Some(strip), None, TRIANGLE_STRIP),
+        ];
+        let mesh = builder.add_mesh("mesh", primitives);
+        let preview = GlbBuilder::primitive(&attributes, Some(strip), None, TRIANGLE_STRIP);
+        let strips = builder.add_mesh("strips", vec![preview]);
+        builder.add_node(json!({ "mesh": mesh }));
+        builder.add_node(json!({ "mesh": strips }));
+
+        let dir = TempDir::new("dpc-glb").unwrap();
+        builder
+            .write(&mut File::create(dir.path().join("import.glb")).unwrap())
+            .unwrap();
+        let path = find_import(dir.path()).unwrap();
+        let primitives = import_primitives(&path).unwrap();
+
+        assert_eq!(primitives.len(), 2);
+        assert_eq!(primitives[0].indices, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(primitives[0].material, Some(1234));
+        let (document, _, _) = gltf::import(&path).unwrap();
+        assert_eq!(document.materials().next().unwrap().name(), Some("paint"));
+        assert_eq!(primitives[1].indices, vec![0, 1, 3, 3, 1, 2]);
+        assert_eq!(primitives[1].material, None);
+
+        let imported = &primitives[0].vertices;
+        assert_eq!(imported.positions, vertices.positions);
+        assert_eq!(imported.uvs, vertices.uvs);
+        assert_eq!(imported.lightmap_uvs, vertices.uvs);
+        assert_eq!(imported.normals, vec![[0.0, 0.0, 1.0]; 4]);
+        assert_eq!(imported.tangents.len(), 4);
+        assert_eq!(imported.joints, vec![[0; 4]; 4]);
+        assert_eq!(imported.weights, vec![[1.0, 0.0, 0.0, 0.0]; 4]);
+    }
+
+    #[test]
+    fn test_obj_import() {
+        let dir = TempDir::new("dpc-obj").unwrap();
+        let path = dir.path().join("import.obj");
+        std::fs::write(
+            &path,
+            "v -1 -1 0\nv 1 -1 0\nv 1 1 0\nv -1 1 0\nusemtl 1234\nf 1 2 3 4\n",
+        )
+        .unwrap();
+        let primitives = import_primitives(&find_import(dir.path()).unwrap()).unwrap();
+
+        assert_eq!(primitives.len(), 1);
+        assert_eq!(primitives[0].indices, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(primitives[0].material, Some(1234));
+        assert_eq!(primitives[0].vertices.positions, quad().positions);
+        assert_eq!(primitives[0].vertices.normals, vec![[0.0, 0.0, 1.0]; 4]);
+    }
+
+    #[test]
+    fn test_bounding_sphere() {
+        let (center, radius) = bounding_sphere(&quad().positions);
+        assert_eq!(center, [0.0; 3]);
+        assert_eq!(radius, 2.0f32.sqrt());
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DpcError, Result};
+use crate::names::NameTable;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Hard,
+    Soft,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub crc32: u32,
+    pub class_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: u32,
+    pub to: u32,
+    pub kind: LinkKind,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+    #[serde(skip)]
+    index: HashMap<u32, usize>,
+    #[serde(skip)]
+    edge_set: HashSet<GraphEdge>,
+}
+
+impl DependencyGraph {
+    pub fn new() -> DependencyGraph {
+        DependencyGraph::default()
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: &P) -> Result<DependencyGraph> {
+        let mut graph: DependencyGraph = serde_json::from_reader(File::open(path)?)?;
+        graph.index = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.crc32, i))
+            .collect();
+        graph.edge_set = graph.edges.iter().copied().collect();
+        Ok(graph)
+    }
+
+    // Objects referenced but not present in the DPC keep a class_name of None
+    pub fn add_node(&mut self, crc32: u32, class_name: Option<&str>, name: Option<&str>) {
+        match self.index.get(&crc32) {
+            Some(i) => {
+                let node = &mut self.nodes[*i];
+                if class_name.is_some() {
+                    node.class_name = class_name.map(String::from);
+                }
+                if name.is_some() {
+                    node.name = name.map(String::from);
+                }
+            }
+            None => {
+                self.index.insert(crc32, self.nodes.len());
+                self.nodes.push(GraphNode {
+                    crc32,
+                    class_name: class_name.map(String::from),
+                    name: name.map(String::from),
+                });
+            }
+        }
+    }
+
+    pub fn add_edge(&mut self, from: u32, to: u32, kind: LinkKind) {
+        if !self.index.contains_key(&from) {
+            self.add_node(from, None, None);
+        }
+        if !self.index.contains_key(&to) {
+            self.add_node(to, None, None);
+        }
+        let edge = GraphEdge { from, to, kind };
+        if self.edge_set.insert(edge) {
+            self.edges.push(edge);
+        }
+    }
+
+    pub fn add_links(&mut self, from: u32, hard_links: &[u32], soft_links: &[u32]) {
+        for to in hard_links {
+            self.add_edge(from, *to, LinkKind::Hard);
+        }
+        for to in soft_links {
+            self.add_edge(from, *to, LinkKind::Soft);
+        }
+    }
+
+    pub fn name_nodes(&mut self, names: &NameTable) {
+        for node in self.nodes.iter_mut() {
+            if let Some(name) = names.get(node.crc32) {
+                node.name = Some(name.to_string());
+            }
+        }
+    }
+
+    pub fn node(&self, crc32: u32) -> Option<&GraphNode> {
+        self.index.get(&crc32).map(|i| &self.nodes[*i])
+    }
+
+    pub fn nodes(&self) -> &[GraphNode] {
+        &self.nodes[..]
+    }
+
+    pub fn edges(&self) -> &[GraphEdge] {
+        &self.edges[..]
+    }
+
+    // What crc32 needs, following edges of the given kind (or all kinds)
+    pub fn dependencies(
+        &self,
+        crc32: u32,
+        kind: Option<LinkKind>,
+        transitive: bool,
+    ) -> BTreeSet<u32> {
+        self.walk(crc32, kind, transitive, |edge| (edge.from, edge.to))
+    }
+
+    // Who references crc32, following edges of the given kind (or all kinds)
+    pub fn dependents(
+        &self,
+        crc32: u32,
+        kind: Option<LinkKind>,
+        transitive: bool,
+    ) -> BTreeSet<u32> {
+        self.walk(crc32, kind, transitive, |edge| (edge.to, edge.from))
+    }
+
+    fn walk<F: Fn(&GraphEdge) -> (u32, u32)>(
+        &self,
+        crc32: u32,
+        kind: Option<LinkKind>,
+        transitive: bool,
+        direction: F,
+    ) -> BTreeSet<u32> {
+        let mut adjacency: HashMap<u32, Vec<u32>> = HashMap::new();
+        for edge in self.edges.iter() {
+            if kind.is_none_or(|kind| kind == edge.kind) {
+                let (from, to) = direction(edge);
+                adjacency.entry(from).or_default().push(to);
+            }
+        }
+
+        let mut visited: HashSet<u32> = HashSet::new();
+        let mut result: BTreeSet<u32> = BTreeSet::new();
+        let mut queue: VecDeque<u32> = VecDeque::new();
+        queue.push_back(crc32);
+        visited.insert(crc32);
+        while let Some(current) = queue.pop_front() {
+            for next in adjacency.get(&current).into_iter().flatten() {
+                if *next != crc32 {
+                    result.insert(*next);
+                }
+                if transitive && visited.insert(*next) {
+                    queue.push_back(*next);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn describe(&self, crc32: u32) -> String {
+        match self.node(crc32) {
+            Some(node) => {
+                let mut description = crc32.to_string();
+                if let Some(name) = &node.name {
+                    description.push_str(format!(" \"{}\"", name).as_str());
+                }
+                if let Some(class_name) = &node.class_name {
+                    description.push_str(format!(" {}", class_name).as_str());
+                }
+                description
+            }
+            None => crc32.to_string(),
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dpc {\n");
+        for node in self.nodes.iter() {
+            let mut label = node.crc32.to_string();
+            if let Some(name) = &node.name {
+                label.push_str(format!("\\n{}", name.replace('"', "\\\"")).as_str());
+            }
+            label.push_str(
+                format!("\\n{}", node.class_name.as_deref().unwrap_or("<missing>")).as_str(),
+            );
+            let style = if node.class_name.is_some() {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            dot.push_str(
+                format!("    \"{}\" [label=\"{}\"{}];\n", node.crc32, label, style).as_str(),
+            );
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                LinkKind::Hard => "solid",
+                LinkKind::Soft => "dashed",
+            };
+            dot.push_str(
+                format!(
+                    "    \"{}\" -> \"{}\" [style={}];\n",
+                    edge.from, edge.to, style
+                )
+                .as_str(),
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+#[derive(Default)]
+pub struct GraphSubCommand {}
+
+impl GraphSubCommand {
+    pub fn new() -> GraphSubCommand {
+        GraphSubCommand {}
+    }
+
+    pub fn subcommand(&self) -> App<'_, '_> {
+        SubCommand::with_name("graph")
+            .about("Query the graph.json written by extract")
+            .arg(
+                Arg::with_name("DEPENDENCIES")
+                    .short("d")
+                    .long("dependencies")
+                    .takes_value(true)
+                    .conflicts_with("DEPENDENTS")
+                    .help("List the objects this crc32 references"),
+            )
+            .arg(
+                Arg::with_name("DEPENDENTS")
+                    .short("r")
+                    .long("dependents")
+                    .takes_value(true)
+                    .help("List the objects that reference this crc32"),
+            )
+            .arg(
+                Arg::with_name("TRANSITIVE")
+                    .short("t")
+                    .long("transitive")
+                    .help("Follow references transitively"),
+            )
+            .arg(
+                Arg::with_name("KIND")
+                    .short("k")
+                    .long("kind")
+                    .takes_value(true)
+                    .possible_values(&["hard", "soft"])
+                    .help("Only follow one kind of link"),
+            )
+            .arg(
+                Arg::with_name("FORMAT")
+                    .short("f")
+                    .long("format")
+                    .takes_value(true)
+                    .possible_values(&["dot", "json"])
+                    .conflicts_with_all(&["DEPENDENCIES", "DEPENDENTS"])
+                    .help("Export the whole graph"),
+            )
+            .after_help("EXAMPLES:\n    graph -td 3213513545 -i BIGFILE_EXTRACTED\n    graph -f dot -i BIGFILE_EXTRACTED -o graph.dot")
+    }
+
+    pub fn execute(&self, matches: &ArgMatches, subcommand_matches: &ArgMatches) -> Result<()> {
+        let input_path = PathBuf::from(
+            matches
+                .value_of_os("INPUT")
+                .ok_or_else(|| DpcError::Format("graph needs an INPUT path".to_string()))?,
+        );
+        let graph_path = if input_path.is_dir() {
+            input_path.join("graph.json")
+        } else {
+            input_path
+        };
+        let graph = DependencyGraph::from_file(&graph_path)?;
+
+        let mut output: Box<dyn Write> = match matches.value_of_os("OUTPUT") {
+            Some(output_path_string) => Box::new(File::create(PathBuf::from(output_path_string))?),
+            None => Box::new(io::stdout()),
+        };
+
+        let kind = match subcommand_matches.value_of("KIND") {
+            Some("hard") => Some(LinkKind::Hard),
+            Some("soft") => Some(LinkKind::Soft),
+            _ => None,
+        };
+        let transitive = subcommand_matches.is_present("TRANSITIVE");
+
+        let crc32s = if let Some(crc32) = subcommand_matches.value_of("DEPENDENCIES") {
+            graph.dependencies(parse_crc32(crc32)?, kind, transitive)
+        } else if let Some(crc32) = subcommand_matches.value_of("DEPENDENTS") {
+            graph.dependents(parse_crc32(crc32)?, kind, transitive)
+        } else {
+            match subcommand_matches.value_of("FORMAT") {
+                Some("json") => output.write_all(graph.to_json()?.as_bytes())?,
+                _ => output.write_all(graph.to_dot().as_bytes())?,
+            };
+            return Ok(());
+        };
+
+        for crc32 in crc32s {
+            output.write_all(format!("{}\n", graph.describe(crc32)).as_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_crc32(crc32: &str) -> Result<u32> {
+    crc32
+        .parse::<i64>()
+        .map(|x| x as u32)
+        .map_err(|_| DpcError::Format(format!("invalid crc32 {}", crc32)))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    // 1 -> 2 -> 3 -> 1 hard, 2 -> 4 soft, 5 -> 3 hard
+    fn graph() -> DependencyGraph {
+        let mut graph = DependencyGraph::new();
+        graph.add_node(1, Some("Node_Z"), Some("root"));
+        graph.add_links(1, &[2], &[]);
+        graph.add_links(2, &[3], &[4]);
+        graph.add_links(3, &[1], &[]);
+        graph.add_links(5, &[3], &[]);
+        graph
+    }
+
+    fn set(crc32s: &[u32]) -> BTreeSet<u32> {
+        crc32s.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let graph = graph();
+        assert_eq!(graph.dependencies(2, None, false), set(&[3, 4]));
+        assert_eq!(
+            graph.dependencies(2, Some(LinkKind::Hard), false),
+            set(&[3])
+        );
+        assert_eq!(
+            graph.dependencies(2, Some(LinkKind::Soft), false),
+            set(&[4])
+        );
+        // The cycle leads back to 1 but never to the start
+        assert_eq!(graph.dependencies(1, None, true), set(&[2, 3, 4]));
+        assert_eq!(
+            graph.dependencies(1, Some(LinkKind::Hard), true),
+            set(&[2, 3])
+        );
+        assert_eq!(graph.dependencies(4, None, true), set(&[]));
+        assert_eq!(graph.dependencies(6, None, true), set(&[]));
+    }
+
+    #[test]
+    fn test_dependents() {
+        let graph = graph();
+        assert_eq!(graph.dependents(3, None, false), set(&[2, 5]));
+        assert_eq!(graph.dependents(3, None, true), set(&[1, 2, 5]));
+        assert_eq!(graph.dependents(4, None, true), set(&[1, 2, 3, 5]));
+        assert_eq!(graph.dependents(4, Some(LinkKind::Hard), true), set(&[]));
+        assert_eq!(
+            graph.dependents(1, Some(LinkKind::Hard), true),
+            set(&[2, 3, 5])
+        );
+    }
+
+    #[test]
+    fn test_from_file() {
+        let dir = TempDir::new("dpc-graph").unwrap();
+        let path = dir.path().join("graph.json");
+        let mut graph = graph();
+        graph.add_edge(1, 2, LinkKind::Hard);
+        assert_eq!(graph.edges().len(), 5);
+        fs::write(&path, graph.to_json().unwrap()).unwrap();
+
+        let mut loaded = DependencyGraph::from_file(&path).unwrap();
+        assert_eq!(loaded.nodes().len(), 5);
+        assert_eq!(loaded.edges(), graph.edges());
+        let node = loaded.node(1).unwrap();
+        assert_eq!(node.class_name.as_deref(), Some("Node_Z"));
+        assert_eq!(node.name.as_deref(), Some("root"));
+        assert_eq!(loaded.dependencies(1, None, true), set(&[2, 3, 4]));
+
+        // The index and edge set are rebuilt, so nothing is added twice
+        loaded.add_edge(3, 1, LinkKind::Hard);
+        loaded.add_node(5, Some("Warp_Z"), None);
+        assert_eq!(loaded.edges().len(), 5);
+        assert_eq!(loaded.nodes().len(), 5);
+        assert_eq!(
+            loaded.node(5).unwrap().class_name.as_deref(),
+            Some("Warp_Z")
+        );
+    }
+}
@@ -1,7 +1,23 @@
+pub mod asobo_fmt;
 pub mod base_dpc;
+pub mod bigfile;
 pub mod crc32;
+pub mod detect;
+pub mod diff;
+pub mod error;
 pub mod fuel_dpc;
 pub mod fuel_fmt;
+pub mod game_dpc;
+pub mod game_profile;
+pub(crate) mod glb;
+pub mod graph;
 pub mod lz;
+pub mod manifest;
+pub mod names;
+pub mod object_database;
+pub mod object_store;
+pub(crate) mod parallel;
+pub mod patch;
+pub mod verify;
 pub mod walle_dpc;
 pub mod walle_fmt;
@@ -3,7 +3,6 @@ use std::fs::File;
 use std::io;
 use std::io::prelude::*;
 use std::io::Cursor;
-use std::io::SeekFrom;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -14,13 +13,12 @@ use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use itertools::Itertools;
 use lz4::{Decoder, EncoderBuilder};
-use std::cmp::{max, min};
-use std::convert::TryInto;
-use std::ptr::null_mut;
+use std::cmp::min;
+
+use crate::error::DpcError;
 
 pub fn lzrs_decompress(
     compressed_buffer: &[u8],
-    _compressed_buffer_size: usize,
     decompressed_buffer: &mut [u8],
     decompressed_buffer_size: usize,
     is_in_place: bool,
@@ -33,16 +31,25 @@ pub fn lzrs_decompress(
     let mut decompressed_buffer_cursor = Cursor::new(decompressed_buffer);
 
     loop {
-        let mut flags: u32 = compressed_buffer_cursor.read_u32::<BigEndian>().unwrap(); // read as big endian
+        let mut flags: u32 = compressed_buffer_cursor.read_u32::<BigEndian>()?; // read as big endian
         let len: u32 = flags & 0x3; // 0b11
         let temp_shift: u32 = WINDOW_LOG - len;
         let temp_mask: u32 = WINDOW_MASK >> len;
 
         for _ in 0..30 {
             if (flags & 0x80000000) != 0 {
-                let temp: u32 = compressed_buffer_cursor.read_u16::<BigEndian>().unwrap() as u32; // read as big endian
-                let start: usize = decompressed_buffer_cursor.position() as usize
-                    - ((temp & temp_mask) as usize + 1);
+                let temp: u32 = compressed_buffer_cursor.read_u16::<BigEndian>()? as u32; // read as big endian
+                let position = decompressed_buffer_cursor.position() as usize;
+                let distance = (temp & temp_mask) as usize + 1;
+                let start: usize = position.checked_sub(distance).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "lzrs match {} bytes back at output position {}",
+                            distance, position
+                        ),
+                    )
+                })?;
                 let end: usize = start + (temp >> temp_shift) as usize + 3;
 
                 for i in start..end {
@@ -50,7 +57,7 @@ pub fn lzrs_decompress(
                     decompressed_buffer_cursor.write_u8(byte)?;
                 }
             } else {
-                let byte = compressed_buffer_cursor.read_u8().unwrap();
+                let byte = compressed_buffer_cursor.read_u8()?;
                 decompressed_buffer_cursor.write_u8(byte)?;
             }
 
@@ -80,229 +87,196 @@ struct Packet {
     matches: Vec<PacketMatch>,
 }
 
-#[derive(Clone)]
+const WINDOW_SIZE: usize = 0x8000;
+
+#[derive(Clone, Copy)]
 struct Match {
-    pos: u64,
-    prev: *mut Match,
-    next: *mut Match,
+    pos: usize,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+// Nodes 0..WINDOW_SIZE hold window positions, the 0x10000 nodes after them head the lists
+// of positions sharing the same two leading bytes
+struct MatchWindow {
+    nodes: Vec<Match>,
 }
 
-impl Match {
-    unsafe fn orphan(&mut self) {
-        self.prev.as_mut().unwrap().next = null_mut();
-        self.prev = null_mut();
+impl MatchWindow {
+    fn new() -> Self {
+        MatchWindow {
+            nodes: vec![
+                Match {
+                    pos: 0,
+                    prev: None,
+                    next: None,
+                };
+                WINDOW_SIZE + 0x10000
+            ],
+        }
     }
 
-    unsafe fn push_back(&mut self, node: *mut Match) {
-        node.as_mut().unwrap().prev = self.prev;
-        if !node.as_mut().unwrap().prev.is_null() {
-            node.as_mut().unwrap().prev.as_mut().unwrap().next = node;
+    fn insert(&mut self, uncompressed_buffer: &[u8], pos: usize) {
+        let slot = pos % WINDOW_SIZE;
+
+        // Slots are reused oldest first, so this only cuts off the position being evicted
+        if let Some(next) = self.nodes[slot].next.take() {
+            self.nodes[next].prev = None;
+        }
+
+        let head = WINDOW_SIZE
+            + u16::from_be_bytes([
+                uncompressed_buffer[pos],
+                uncompressed_buffer.get(pos + 1).copied().unwrap_or(0),
+            ]) as usize;
+        let prev = self.nodes[head].prev;
+        self.nodes[slot] = Match {
+            pos,
+            prev,
+            next: Some(head),
         };
-        node.as_mut().unwrap().next = self;
-        node.as_mut().unwrap().next.as_mut().unwrap().prev = node;
+        if let Some(prev) = prev {
+            self.nodes[prev].next = Some(slot);
+        }
+        self.nodes[head].prev = Some(slot);
     }
 }
 
-unsafe fn encode_packet(
-    mut uncompressed_buffer_ptr: u64,
+fn encode_packet(
+    mut uncompressed_buffer_ptr: usize,
     packet: &mut Packet,
-    mut window_index: u32,
+    mut window_index: usize,
     uncompressed_buffer: &[u8],
-    uncompressed_buffer_size: usize,
-    g_window_buffer: &Vec<Match>,
+    window: &MatchWindow,
 ) -> bool {
-    let mut remaining_length: u32 = (1 << packet.match_length) + 2;
-    let v20: u32 = 0x10000 >> packet.match_length;
+    let mut remaining_length: usize = (1 << packet.match_length) + 2;
+    let v20: usize = 0x10000 >> packet.match_length;
 
     packet.matches.clear();
     for _ in 0..30 {
-        let v5: u64 = max(0 as i64, uncompressed_buffer_ptr as i64 - v20 as i64) as u64;
+        let v5 = uncompressed_buffer_ptr.saturating_sub(v20);
 
         remaining_length = min(
             remaining_length,
-            (uncompressed_buffer_size - uncompressed_buffer_ptr as usize) as u32,
+            uncompressed_buffer.len() - uncompressed_buffer_ptr,
         );
 
-        if remaining_length <= 2 {
-            packet.total_length += 1;
-            packet.matches.push(PacketMatch {
-                length: -1,
-                data: uncompressed_buffer[uncompressed_buffer_ptr as usize] as i32,
-            });
-            uncompressed_buffer_ptr += 1;
-            window_index += 1;
-        } else {
-            let mut ptr: u64 = 0;
+        let mut ptr: usize = 0;
+        let mut match_length: usize = 2;
 
-            let mut match_length: i32 = 2;
-            let mut cur: *const Match = g_window_buffer[window_index as usize].prev;
-            while !cur.is_null() && cur.as_ref().unwrap().pos >= v5 {
-                if uncompressed_buffer[uncompressed_buffer_ptr as usize + 2]
-                    == uncompressed_buffer[cur.as_ref().unwrap().pos as usize + 2]
+        if remaining_length > 2 {
+            let mut cur = window.nodes[window_index].prev;
+            while let Some(node) = cur.map(|index| &window.nodes[index]) {
+                if node.pos < v5 {
+                    break;
+                }
+
+                if uncompressed_buffer[uncompressed_buffer_ptr + 2]
+                    == uncompressed_buffer[node.pos + 2]
                 {
-                    let mut j: i32 = 3;
-                    while uncompressed_buffer[cur.as_ref().unwrap().pos as usize + j as usize]
-                        == uncompressed_buffer[uncompressed_buffer_ptr as usize + j as usize]
-                        && remaining_length != j as u32
+                    let mut j: usize = 3;
+                    while j != remaining_length
+                        && uncompressed_buffer[node.pos + j]
+                            == uncompressed_buffer[uncompressed_buffer_ptr + j]
                     {
                         j += 1;
                     }
 
                     if match_length < j {
-                        if remaining_length == j as u32 {
-                            ptr = cur.as_ref().unwrap().pos;
-                            match_length = remaining_length as i32;
+                        match_length = j;
+                        ptr = node.pos;
+                        if remaining_length == j {
                             break;
                         }
-                        match_length = j;
-                        ptr = cur.as_ref().unwrap().pos;
                     }
                 }
-                cur = cur.as_ref().unwrap().prev;
+                cur = node.prev;
             }
+        }
 
-            if match_length == 2 {
-                packet.total_length += 1;
-                packet.matches.push(PacketMatch {
-                    length: -1,
-                    data: uncompressed_buffer[uncompressed_buffer_ptr as usize] as i32,
-                });
-                uncompressed_buffer_ptr += 1;
-                window_index += 1;
-            } else {
-                packet.total_length += match_length;
-                packet.matches.push(PacketMatch {
-                    length: match_length - 3,
-                    data: uncompressed_buffer_ptr as i32 - ptr as i32,
-                });
-                uncompressed_buffer_ptr += match_length as u64;
-                window_index += match_length as u32;
-            }
+        if match_length == 2 {
+            packet.total_length += 1;
+            packet.matches.push(PacketMatch {
+                length: -1,
+                data: uncompressed_buffer[uncompressed_buffer_ptr] as i32,
+            });
+            uncompressed_buffer_ptr += 1;
+            window_index += 1;
+        } else {
+            packet.total_length += match_length as i32;
+            packet.matches.push(PacketMatch {
+                length: match_length as i32 - 3,
+                data: (uncompressed_buffer_ptr - ptr) as i32,
+            });
+            uncompressed_buffer_ptr += match_length;
+            window_index += match_length;
         }
 
-        window_index = window_index % 0x8000;
+        window_index %= WINDOW_SIZE;
 
-        if uncompressed_buffer_ptr >= uncompressed_buffer_size as u64 {
+        if uncompressed_buffer_ptr >= uncompressed_buffer.len() {
             return false;
         }
     }
 
-    return true;
+    true
 }
 
-pub(crate) unsafe fn lzrs_compress(
-    uncompressed_buffer: &[u8],
-    uncompressed_buffer_size: usize,
-    compressed_buffer: &mut [u8],
-    _compressed_buffer_size: usize,
-) -> Result<usize, io::Error> {
-    assert_eq!(uncompressed_buffer.len(), uncompressed_buffer_size + 2);
-
-    let mut g_window_buffer: Vec<Match> = std::iter::repeat(Match {
-        pos: 0,
-        prev: null_mut(),
-        next: null_mut(),
-    })
-    .take(0x8000)
-    .collect::<Vec<_>>();
-
-    let mut short_lookup: Vec<Match> = std::iter::repeat(Match {
-        pos: 0,
-        prev: null_mut(),
-        next: null_mut(),
-    })
-    .take(0x10000)
-    .collect::<Vec<_>>();
-
-    let mut compressed_buffer_cursor = Cursor::new(compressed_buffer);
+pub fn lzrs_compress(uncompressed_buffer: &[u8]) -> Vec<u8> {
+    let uncompressed_buffer_size = uncompressed_buffer.len();
+    let mut compressed_buffer: Vec<u8> = Vec::with_capacity(uncompressed_buffer_size / 2);
 
-    let window_size: u32 = min(uncompressed_buffer_size as u32, 0x8000 as u32);
-
-    let mut packets: Vec<Packet> = std::iter::repeat(Packet {
-        match_length: 0,
-        total_length: 0,
-        matches: vec![],
-    })
-    .take(4)
-    .collect::<Vec<_>>();
-    packets[0].match_length = 2;
-    packets[1].match_length = 3;
-    packets[2].match_length = 4;
-    packets[3].match_length = 5;
-
-    let mut window_index: u32 = 0;
-
-    for i in 0..window_size {
-        let ptr: u32 = i;
-        let match_index: u16 = u16::from_be_bytes(
-            uncompressed_buffer[ptr as usize..ptr as usize + 2]
-                .try_into()
-                .unwrap(),
-        );
-        let current: *mut Match = &mut g_window_buffer[i as usize];
-        let next: *mut Match = &mut short_lookup[match_index as usize];
-        current.as_mut().unwrap().pos = ptr as u64;
-        next.as_mut().unwrap().push_back(current);
+    let mut window = MatchWindow::new();
+    for pos in 0..min(uncompressed_buffer_size, WINDOW_SIZE) {
+        window.insert(uncompressed_buffer, pos);
     }
 
-    let mut uncompressed_buffer_cursor = Cursor::new(uncompressed_buffer);
-
-    let mut buffer_size_2: u32 = 0x8000 as u32;
+    let mut packets: Vec<Packet> = (2..6)
+        .map(|match_length| Packet {
+            match_length,
+            total_length: 0,
+            matches: vec![],
+        })
+        .collect();
+
+    let mut uncompressed_buffer_ptr: usize = 0;
+    let mut window_index: usize = 0;
+    let mut buffer_size_2: usize = WINDOW_SIZE;
     let mut k: i32 = 0x7000;
 
-    while uncompressed_buffer_cursor.position() < uncompressed_buffer_size as u64 {
-        let mut len: u8;
-
-        packets[3].total_length = 0;
-        packets[2].total_length = 0;
-        packets[1].total_length = 0;
-        packets[0].total_length = 0;
-
-        if encode_packet(
-            uncompressed_buffer_cursor.position(),
-            &mut packets[3],
-            window_index,
-            uncompressed_buffer,
-            uncompressed_buffer_size,
-            &g_window_buffer,
-        ) && packets[3].total_length <= 540
-        {
-            if encode_packet(
-                uncompressed_buffer_cursor.position(),
-                &mut packets[2],
+    while uncompressed_buffer_ptr < uncompressed_buffer_size {
+        let mut len: usize;
+
+        for packet in packets.iter_mut() {
+            packet.total_length = 0;
+        }
+
+        let encode = |packet: &mut Packet| {
+            encode_packet(
+                uncompressed_buffer_ptr,
+                packet,
                 window_index,
                 uncompressed_buffer,
-                uncompressed_buffer_size,
-                &g_window_buffer,
-            ) {
+                &window,
+            )
+        };
+
+        if encode(&mut packets[3]) && packets[3].total_length <= 540 {
+            if encode(&mut packets[2]) {
                 len = if packets[2].total_length <= packets[3].total_length {
-                    1
+                    3
                 } else {
-                    0
-                } + 2;
-                if packets[len as usize].total_length <= 300 {
-                    if encode_packet(
-                        uncompressed_buffer_cursor.position(),
-                        &mut packets[1],
-                        window_index,
-                        uncompressed_buffer,
-                        uncompressed_buffer_size,
-                        &g_window_buffer,
-                    ) {
-                        if packets[1].total_length > packets[len as usize].total_length {
+                    2
+                };
+                if packets[len].total_length <= 300 {
+                    if encode(&mut packets[1]) {
+                        if packets[1].total_length > packets[len].total_length {
                             len = 1;
                         }
 
-                        if packets[len as usize].total_length <= 180 {
-                            encode_packet(
-                                uncompressed_buffer_cursor.position(),
-                                &mut packets[0],
-                                window_index,
-                                uncompressed_buffer,
-                                uncompressed_buffer_size,
-                                &g_window_buffer,
-                            );
-                            if packets[0].total_length >= packets[len as usize].total_length {
+                        if packets[len].total_length <= 180 {
+                            encode(&mut packets[0]);
+                            if packets[0].total_length >= packets[len].total_length {
                                 len = 0;
                             }
                         }
@@ -317,61 +291,103 @@ pub(crate) unsafe fn lzrs_compress(
             len = 3;
         }
 
-        let current_packet: &Packet = &packets[len as usize];
+        let current_packet: &Packet = &packets[len];
 
         let mut flag: u32 = 0;
-        for i in 0..current_packet.matches.len() {
-            if current_packet.matches[i].length >= 0 {
+        for (i, m) in current_packet.matches.iter().enumerate() {
+            if m.length >= 0 {
                 flag |= 0x80000000u32 >> i;
             }
         }
 
-        compressed_buffer_cursor.write_u32::<BigEndian>(flag | len as u32)?;
+        compressed_buffer.extend_from_slice(&(flag | len as u32).to_be_bytes());
 
         for m in current_packet.matches.iter() {
             if m.length == -1 {
-                compressed_buffer_cursor.write_u8(m.data as u8)?;
+                compressed_buffer.push(m.data as u8);
             } else {
-                compressed_buffer_cursor
-                    .write_u16::<BigEndian>((m.data + (m.length << (0xE - len)) - 1) as u16)?;
+                compressed_buffer.extend_from_slice(
+                    &((m.data + (m.length << (0xE - len)) - 1) as u16).to_be_bytes(),
+                );
             }
         }
 
-        uncompressed_buffer_cursor.seek(SeekFrom::Current(current_packet.total_length as i64))?;
+        uncompressed_buffer_ptr += current_packet.total_length as usize;
 
-        window_index = (window_index + current_packet.total_length as u32) % 0x8000 as u32;
+        window_index = (window_index + current_packet.total_length as usize) % WINDOW_SIZE;
 
         k -= current_packet.total_length;
         if k < 0 {
-            let window_size_1: u32 =
-                min(uncompressed_buffer_size as u32, buffer_size_2 + 0x1000u32);
-            for i in buffer_size_2..window_size_1 {
-                let ptr: u32 = i;
-                let match_index: u16 = u16::from_be_bytes(
-                    uncompressed_buffer[ptr as usize..ptr as usize + 2]
-                        .try_into()
-                        .unwrap(),
-                );
-                let current: *mut Match = &mut g_window_buffer[i as usize % 0x8000 as usize];
-                let next: *mut Match = &mut short_lookup[match_index as usize];
-                current.as_mut().unwrap().next.as_mut().unwrap().orphan();
-                current.as_mut().unwrap().pos = ptr as u64;
-                next.as_mut().unwrap().push_back(current);
+            let window_size_1 = min(uncompressed_buffer_size, buffer_size_2 + 0x1000);
+            for pos in buffer_size_2..window_size_1 {
+                window.insert(uncompressed_buffer, pos);
             }
-            k += 0x1000i32;
+            k += 0x1000;
             buffer_size_2 = window_size_1;
         }
     }
 
-    return Ok(compressed_buffer_cursor.position() as usize);
+    compressed_buffer
 }
 
-pub fn lzrs_compress_optimized(
-    decompressed_buffer: &[u8],
-    decompressed_buffer_size: usize,
-    compressed_buffer: &mut [u8],
-    _compressed_buffer_size: usize,
-) -> Result<usize, io::Error> {
+// Search parameters for the exhaustive compressor. The format fixes the 30 items per flag group
+// and the 14-bit window, so only how far back and how many window splits are searched can change
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchParameters {
+    // log2 of the furthest distance searched, at most 14
+    pub window_log: u32,
+    // How many of the 4 length/distance splits are tried for each flag group
+    pub match_iter: u32,
+}
+
+// Fast is the default everywhere: the encoders, BigFileWriter, BigFilePatch and the command line
+// without OPTIMIZATION. Max has to be asked for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompressionLevel {
+    // Greedy hash chain search, for iteration builds
+    #[default]
+    Fast,
+    Balanced,
+    // Exhaustive search over the whole window, for releases
+    Max,
+    Custom(MatchParameters),
+}
+
+impl CompressionLevel {
+    pub fn from_name(name: &str) -> Option<CompressionLevel> {
+        match name {
+            "fast" => Some(CompressionLevel::Fast),
+            "balanced" => Some(CompressionLevel::Balanced),
+            "max" => Some(CompressionLevel::Max),
+            _ => None,
+        }
+    }
+
+    // None means the greedy lzrs_compress is used instead of the exhaustive search
+    pub fn match_parameters(&self) -> Option<MatchParameters> {
+        match self {
+            CompressionLevel::Fast => None,
+            CompressionLevel::Balanced => Some(MatchParameters {
+                window_log: 13,
+                match_iter: 4,
+            }),
+            CompressionLevel::Max => Some(MatchParameters {
+                window_log: 14,
+                match_iter: 4,
+            }),
+            CompressionLevel::Custom(parameters) => Some(*parameters),
+        }
+    }
+}
+
+pub fn lzrs_compress_optimized(decompressed_buffer: &[u8]) -> Vec<u8> {
+    lzrs_compress_search(
+        decompressed_buffer,
+        CompressionLevel::Max.match_parameters().unwrap(),
+    )
+}
+
+pub fn lzrs_compress_search(decompressed_buffer: &[u8], parameters: MatchParameters) -> Vec<u8> {
     const WINDOW_LOG: u32 = 14;
     const WINDOW_MASK: u32 = (1 << WINDOW_LOG) - 1;
     const MATCH_NUM: u32 = 30;
@@ -379,35 +395,28 @@ pub fn lzrs_compress_optimized(
     const MIN_MATCH_LEN: u32 = 3;
     const MIN_DISTANCE: u32 = 1;
 
+    let match_iter = parameters.match_iter.clamp(1, MATCH_ITER);
+    let search_mask: u32 = (1 << parameters.window_log.min(WINDOW_LOG)) - 1;
+
     let mut distances_table = [[0u32; MATCH_ITER as usize]; MATCH_NUM as usize];
     let mut lengths_table = [[0u32; MATCH_ITER as usize]; MATCH_NUM as usize];
 
-    let mut decompressed_buffer_cursor = Cursor::new(decompressed_buffer);
-    let mut compressed_buffer_cursor = Cursor::new(compressed_buffer);
-
-    // let mut next: u64 = 0;
+    let decompressed_buffer_size = decompressed_buffer.len();
+    let mut compressed_buffer: Vec<u8> = Vec::with_capacity(decompressed_buffer_size / 2);
+    let mut position: usize = 0;
 
-    while (decompressed_buffer_cursor.position() as usize) < decompressed_buffer_size {
-        // if decompressed_buffer_cursor.position() >= next {
-        // 	println!("inp={}/{} out={}\r", decompressed_buffer_cursor.position() as u32, decompressed_buffer_size as u32, compressed_buffer_cursor.position() as u32);
-        // 	next = decompressed_buffer_cursor.position() + 0x10000;
-        // 	if next > decompressed_buffer_size as u64 {
-        // 		next = decompressed_buffer_size as u64;
-        // 	}
-        // }
-
-        let decompressed_buffer_cursor_position_backup = decompressed_buffer_cursor.position();
-        let flag_position: usize = compressed_buffer_cursor.position() as usize;
-        compressed_buffer_cursor.seek(SeekFrom::Current(4))?;
+    while position < decompressed_buffer_size {
+        let position_backup = position;
+        let flag_position: usize = compressed_buffer.len();
+        compressed_buffer.extend_from_slice(&[0; 4]);
         let mut opt_flag: u32 = 0;
         let mut opt_rate: f64 = 0.0;
 
-        for t in 0..MATCH_ITER {
+        for t in 0..match_iter {
             let mut flag: u32 = 0;
             let mut ulen: u32 = 0;
             let mut clen: u32 = 0;
-            decompressed_buffer_cursor
-                .seek(SeekFrom::Start(decompressed_buffer_cursor_position_backup))?;
+            position = position_backup;
             let temp_wlog: u32 = WINDOW_LOG - t;
             let temp_mlen: u32 = (1 << (16 - temp_wlog)) - 1 + MIN_MATCH_LEN;
             let temp_mask: u32 = WINDOW_MASK >> t;
@@ -418,18 +427,17 @@ pub fn lzrs_compress_optimized(
             }
 
             for i in 0..MATCH_NUM {
-                if decompressed_buffer_cursor.position() as usize >= decompressed_buffer_size {
+                if position >= decompressed_buffer_size {
                     break;
                 }
 
-                let pos: u32 = decompressed_buffer_cursor.position() as u32;
-                let mut k: u32 = (pos as i32 - (temp_mask + MIN_DISTANCE) as i32) as u32;
+                let pos: u32 = position as u32;
+                let mut k: u32 =
+                    (pos as i32 - (temp_mask.min(search_mask) + MIN_DISTANCE) as i32) as u32;
                 if (k & 0x80000000) != 0 {
                     k = 0;
                 }
-                let mut l: u32 = (decompressed_buffer_size as u32
-                    - decompressed_buffer_cursor.position() as u32)
-                    as u32;
+                let mut l: u32 = (decompressed_buffer_size - position) as u32;
                 if l > temp_mlen {
                     l = temp_mlen;
                 }
@@ -441,8 +449,7 @@ pub fn lzrs_compress_optimized(
                 while j != end {
                     let mut rr: u32 = l;
                     for r in 0..l {
-                        if decompressed_buffer
-                            [(decompressed_buffer_cursor.position() as u32 + r) as usize]
+                        if decompressed_buffer[position + r as usize]
                             != decompressed_buffer[(j + r) as usize]
                         {
                             rr = r;
@@ -452,6 +459,10 @@ pub fn lzrs_compress_optimized(
                     if rr > ml {
                         ml = rr;
                         mj = pos - j;
+                        // Nothing further back can be longer
+                        if ml == l {
+                            break;
+                        }
                     }
 
                     j = (j as i32 - 1) as u32;
@@ -460,7 +471,7 @@ pub fn lzrs_compress_optimized(
                 if ml < MIN_MATCH_LEN {
                     // literal
                     ulen += 1;
-                    decompressed_buffer_cursor.seek(SeekFrom::Current(1))?;
+                    position += 1;
                     clen += 1;
                 } else {
                     // match
@@ -468,7 +479,7 @@ pub fn lzrs_compress_optimized(
                     lengths_table[i as usize][t as usize] = ml;
                     flag |= 1 << (31 - i);
                     ulen += ml;
-                    decompressed_buffer_cursor.seek(SeekFrom::Current(ml as i64))?;
+                    position += ml as usize;
                     clen += 2;
                 }
             } // for
@@ -481,17 +492,16 @@ pub fn lzrs_compress_optimized(
             }
         }
 
-        (&mut compressed_buffer_cursor.get_mut()[flag_position..flag_position + 4])
-            .write_u32::<BigEndian>(opt_flag)?;
+        compressed_buffer[flag_position..flag_position + 4]
+            .copy_from_slice(&opt_flag.to_be_bytes());
 
         let t: u32 = opt_flag & 3;
-        decompressed_buffer_cursor
-            .seek(SeekFrom::Start(decompressed_buffer_cursor_position_backup))?;
+        position = position_backup;
         let temp_wlog: u32 = WINDOW_LOG - t;
         let temp_mask: u32 = WINDOW_MASK >> t;
 
         for i in 0..MATCH_NUM {
-            if decompressed_buffer_cursor.position() as usize >= decompressed_buffer_size {
+            if position >= decompressed_buffer_size {
                 break;
             }
 
@@ -501,32 +511,196 @@ pub fn lzrs_compress_optimized(
                 let mj: u32 = distances_table[i as usize][t as usize];
                 let c: u16 = ((ml - MIN_MATCH_LEN) << temp_wlog) as u16
                     + ((mj - MIN_DISTANCE) & temp_mask) as u16;
-                compressed_buffer_cursor.write_u16::<BigEndian>(c)?;
-                decompressed_buffer_cursor.seek(SeekFrom::Current(ml as i64))?;
+                compressed_buffer.extend_from_slice(&c.to_be_bytes());
+                position += ml as usize;
             } else {
                 // literal
-                let byte = decompressed_buffer_cursor.read_u8()?;
-                compressed_buffer_cursor.write_u8(byte)?;
+                compressed_buffer.push(decompressed_buffer[position]);
+                position += 1;
             }
         }
     }
 
-    Ok(compressed_buffer_cursor.position() as usize)
+    compressed_buffer
+}
+
+// lzrs matches reach back over the whole object and its header holds the total sizes, so this
+// does not stream: the encoder buffers the whole object written to it and emits the size header
+// and the compressed stream when finished
+pub struct LzrsEncoder<W: Write> {
+    writer: W,
+    buffer: Vec<u8>,
+    compression_level: CompressionLevel,
+}
+
+impl<W: Write> LzrsEncoder<W> {
+    pub fn new(writer: W) -> Self {
+        LzrsEncoder {
+            writer,
+            buffer: vec![],
+            compression_level: CompressionLevel::default(),
+        }
+    }
+
+    pub fn compression_level(mut self, compression_level: CompressionLevel) -> Self {
+        self.compression_level = compression_level;
+        self
+    }
+
+    pub fn finish(mut self) -> Result<W, io::Error> {
+        let compressed_buffer = match self.compression_level.match_parameters() {
+            Some(parameters) => lzrs_compress_search(&self.buffer[..], parameters),
+            None => lzrs_compress(&self.buffer[..]),
+        };
+
+        self.writer
+            .write_u32::<LittleEndian>(self.buffer.len() as u32)?;
+        self.writer
+            .write_u32::<LittleEndian>(compressed_buffer.len() as u32 + 8)?;
+        self.writer.write_all(&compressed_buffer)?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for LzrsEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// compressed_buffer holds at least compressed_len bytes of the stream after the size header
+fn lzrs_decompress_sized(
+    compressed_buffer: &[u8],
+    decompressed_size: u32,
+    compressed_len: u64,
+) -> Result<Vec<u8>, io::Error> {
+    if (compressed_buffer.len() as u64) < compressed_len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "lzrs stream ends after {} of {} compressed bytes",
+                compressed_buffer.len(),
+                compressed_len
+            ),
+        ));
+    }
+
+    // A two byte match copies at most 34 bytes, so the size in the header can't be trusted past
+    // that before allocating it
+    if decompressed_size as u64 > (compressed_len + 2) * 17 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "lzrs decompressed size {} is too large for {} compressed bytes",
+                decompressed_size, compressed_len
+            ),
+        ));
+    }
+
+    let mut decompressed_buffer = vec![0; decompressed_size as usize];
+    if !decompressed_buffer.is_empty() {
+        lzrs_decompress(
+            &compressed_buffer[..compressed_len as usize],
+            &mut decompressed_buffer[..],
+            decompressed_size as usize,
+            false,
+        )?;
+    }
+    Ok(decompressed_buffer)
+}
+
+// Decompresses a whole object, size header included, straight from a borrowed slice such as a
+// mapped BigFile, without copying the compressed data first
+pub fn lzrs_decompress_object(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+    let decoder = LzrsDecoder::new(data)?;
+    lzrs_decompress_sized(
+        &data[8..],
+        decoder.decompressed_size(),
+        decoder.compressed_size() as u64 - 8,
+    )
+}
+
+// Reads the size header up front and decompresses the whole object on the first read, holding
+// both the compressed and the decompressed object in memory
+pub struct LzrsDecoder<R: Read> {
+    reader: R,
+    decompressed_size: u32,
+    compressed_size: u32,
+    decompressed_buffer: Option<Cursor<Vec<u8>>>,
+}
+
+impl<R: Read> LzrsDecoder<R> {
+    pub fn new(mut reader: R) -> Result<Self, io::Error> {
+        let decompressed_size = reader.read_u32::<LittleEndian>()?;
+        let compressed_size = reader.read_u32::<LittleEndian>()?;
+        if compressed_size < 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "lzrs compressed size {} is smaller than its header",
+                    compressed_size
+                ),
+            ));
+        }
+
+        Ok(LzrsDecoder {
+            reader,
+            decompressed_size,
+            compressed_size,
+            decompressed_buffer: None,
+        })
+    }
+
+    pub fn decompressed_size(&self) -> u32 {
+        self.decompressed_size
+    }
+
+    // Includes the 8-byte size header, like ObjectHeader::compressed_size
+    pub fn compressed_size(&self) -> u32 {
+        self.compressed_size
+    }
+
+    fn decompressed_buffer(&mut self) -> Result<&mut Cursor<Vec<u8>>, io::Error> {
+        if self.decompressed_buffer.is_none() {
+            // Grows with what is actually read rather than the size in the header
+            let compressed_len = self.compressed_size as u64 - 8;
+            let mut compressed_buffer = vec![];
+            (&mut self.reader)
+                .take(compressed_len)
+                .read_to_end(&mut compressed_buffer)?;
+            let decompressed_buffer =
+                lzrs_decompress_sized(&compressed_buffer, self.decompressed_size, compressed_len)?;
+            self.decompressed_buffer = Some(Cursor::new(decompressed_buffer));
+        }
+
+        Ok(self.decompressed_buffer.as_mut().unwrap())
+    }
+}
+
+impl<R: Read> Read for LzrsDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decompressed_buffer()?.read(buf)
+    }
 }
 
 pub trait LZ {
     fn decompress_internal(
-        self: &Self,
+        &self,
         compressed_buffer: &Vec<u8>,
         decompressed_buffer: &mut Vec<u8>,
     ) -> Result<(), io::Error>;
     fn compress_internal(
-        self: &Self,
+        &self,
         decompressed_buffer: &mut Vec<u8>,
         compressed_buffer: &mut Vec<u8>,
     ) -> Result<(), io::Error>;
     fn decompress(
-        self: &Self,
+        &self,
         compressed_path: &Path,
         decompressed_path: &Path,
     ) -> Result<(), io::Error> {
@@ -548,11 +722,7 @@ pub trait LZ {
         Ok(())
     }
 
-    fn compress(
-        self: &Self,
-        decompressed_path: &Path,
-        compressed_path: &Path,
-    ) -> Result<(), io::Error> {
+    fn compress(&self, decompressed_path: &Path, compressed_path: &Path) -> Result<(), io::Error> {
         let mut decompressed_file = File::open(decompressed_path)?;
         let mut compressed_file = File::create(compressed_path)?;
 
@@ -577,14 +747,13 @@ pub struct LZLZRS {}
 
 impl LZ for LZLZRS {
     fn decompress_internal(
-        self: &Self,
+        &self,
         compressed_buffer: &Vec<u8>,
         decompressed_buffer: &mut Vec<u8>,
     ) -> Result<(), io::Error> {
         let decompressed_buffer_len = decompressed_buffer.len();
         match lzrs_decompress(
             &compressed_buffer[..],
-            compressed_buffer.len(),
             &mut decompressed_buffer[..],
             decompressed_buffer_len,
             false,
@@ -595,28 +764,29 @@ impl LZ for LZLZRS {
     }
 
     fn compress_internal(
-        self: &Self,
+        &self,
         decompressed_buffer: &mut Vec<u8>,
         compressed_buffer: &mut Vec<u8>,
     ) -> Result<(), io::Error> {
-        unsafe {
-            let compressed_buffer_len = compressed_buffer.len();
-            let decompressed_buffer_len = decompressed_buffer.len();
-            decompressed_buffer.resize(decompressed_buffer_len + 2, 0);
-            match lzrs_compress(
-                &decompressed_buffer[..],
-                decompressed_buffer_len,
-                &mut compressed_buffer[..],
-                compressed_buffer_len,
-            ) {
-                Ok(len) => {
-                    decompressed_buffer.resize(decompressed_buffer_len, 0);
-                    compressed_buffer.resize(len, 0);
-                    Ok(())
-                }
-                Err(err) => Err(err),
-            }
-        }
+        *compressed_buffer = lzrs_compress(&decompressed_buffer[..]);
+        Ok(())
+    }
+
+    fn decompress(
+        &self,
+        compressed_path: &Path,
+        decompressed_path: &Path,
+    ) -> Result<(), io::Error> {
+        let mut decoder = LzrsDecoder::new(File::open(compressed_path)?)?;
+        io::copy(&mut decoder, &mut File::create(decompressed_path)?)?;
+        Ok(())
+    }
+
+    fn compress(&self, decompressed_path: &Path, compressed_path: &Path) -> Result<(), io::Error> {
+        let mut encoder = LzrsEncoder::new(File::create(compressed_path)?);
+        io::copy(&mut File::open(decompressed_path)?, &mut encoder)?;
+        encoder.finish()?;
+        Ok(())
     }
 }
 
@@ -624,7 +794,7 @@ pub struct ARITH {}
 
 impl LZ for ARITH {
     fn decompress_internal(
-        self: &Self,
+        &self,
         compressed_buffer: &Vec<u8>,
         decompressed_buffer: &mut Vec<u8>,
     ) -> Result<(), io::Error> {
@@ -648,7 +818,7 @@ impl LZ for ARITH {
     }
 
     fn compress_internal(
-        self: &Self,
+        &self,
         decompressed_buffer: &mut Vec<u8>,
         compressed_buffer: &mut Vec<u8>,
     ) -> Result<(), io::Error> {
@@ -672,7 +842,7 @@ impl LZ for ARITH {
     }
 
     fn decompress(
-        self: &Self,
+        &self,
         compressed_path: &Path,
         decompressed_path: &Path,
     ) -> Result<(), io::Error> {
@@ -694,11 +864,7 @@ impl LZ for ARITH {
         Ok(())
     }
 
-    fn compress(
-        self: &Self,
-        decompressed_path: &Path,
-        compressed_path: &Path,
-    ) -> Result<(), io::Error> {
+    fn compress(&self, decompressed_path: &Path, compressed_path: &Path) -> Result<(), io::Error> {
         let mut decompressed_file = File::open(decompressed_path)?;
         let mut compressed_file = File::create(compressed_path)?;
 
@@ -723,7 +889,7 @@ pub struct LZLZ4 {}
 
 impl LZ for LZLZ4 {
     fn decompress_internal(
-        self: &Self,
+        &self,
         compressed_buffer: &Vec<u8>,
         decompressed_buffer: &mut Vec<u8>,
     ) -> Result<(), io::Error> {
@@ -737,7 +903,7 @@ impl LZ for LZLZ4 {
     }
 
     fn compress_internal(
-        self: &Self,
+        &self,
         decompressed_buffer: &mut Vec<u8>,
         compressed_buffer: &mut Vec<u8>,
     ) -> Result<(), io::Error> {
@@ -768,7 +934,7 @@ impl LZSubCommand<'_> {
         LZSubCommand { algorithms }
     }
 
-    pub fn subcommand(self: &Self) -> App {
+    pub fn subcommand(&self) -> App<'_, '_> {
         SubCommand::with_name("lz")
             .about("Used to compress raw files")
             .arg(
@@ -808,10 +974,10 @@ impl LZSubCommand<'_> {
     }
 
     pub fn execute(
-        self: &Self,
+        &self,
         matches: &ArgMatches,
         subcommand_matches: &ArgMatches,
-    ) -> Result<(), io::Error> {
+    ) -> crate::error::Result<()> {
         let input_path_string = matches.value_of_os("INPUT").unwrap();
         let input_path = Path::new(input_path_string);
 
@@ -824,21 +990,177 @@ impl LZSubCommand<'_> {
             }),
         };
 
-        match subcommand_matches.value_of("ALGORITHM") {
-            None => panic!("Algorithm is required"),
-            Some(algorithm) => {
-                if let Some(lz_implementation) = self.algorithms.get(algorithm) {
-                    if subcommand_matches.is_present("COMPRESS") {
-                        lz_implementation.compress(&input_path, &output_path.as_path())?;
-                    } else {
-                        lz_implementation.decompress(&input_path, &output_path.as_path())?;
-                    }
-                } else {
-                    panic!("bad algorithm")
-                }
+        let algorithm = subcommand_matches
+            .value_of("ALGORITHM")
+            .ok_or_else(|| DpcError::Format("an algorithm is required".to_string()))?;
+        let lz_implementation = self.algorithms.get(algorithm).ok_or_else(|| {
+            DpcError::Format(format!(
+                "unknown algorithm {}, expected one of {}",
+                algorithm,
+                self.algorithms.keys().sorted().join(", ")
+            ))
+        })?;
+        if subcommand_matches.is_present("COMPRESS") {
+            lz_implementation.compress(input_path, output_path.as_path())?;
+        } else {
+            lz_implementation.decompress(input_path, output_path.as_path())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // xorshift so failures are reproducible without pulling in a rng crate
+    fn fuzz_input(seed: u64, size: usize, alphabet: u64) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(0x9E3779B97F4A7C15) | 1;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let mut data: Vec<u8> = Vec::with_capacity(size);
+        while data.len() < size {
+            let r = next();
+            if r % 4 == 0 && data.len() > 4 {
+                // copy an earlier run so there is something to match against
+                let start = next() as usize % data.len();
+                let len = min(next() as usize % 80, data.len() - start);
+                data.extend_from_within(start..start + len);
+            } else {
+                data.push((r % alphabet) as u8);
+            }
+        }
+        data.truncate(size);
+        data
+    }
+
+    fn roundtrip(data: &[u8]) {
+        let compressed = lzrs_compress(data);
+        let mut decompressed = vec![0; data.len()];
+        let len = lzrs_decompress(&compressed, &mut decompressed, data.len(), false).unwrap();
+        assert_eq!(len, data.len());
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn test_lzrs_decompress_corrupt() {
+        let mut decompressed = vec![0; 2];
+
+        // A match before the start of the output
+        let data = [0x80, 0, 0, 0, 0, 0];
+        assert!(lzrs_decompress(&data, &mut decompressed, 2, false).is_err());
+
+        // More literals than the output holds
+        let data = [0, 0, 0, 0, 1, 2, 3, 4];
+        assert!(lzrs_decompress(&data, &mut decompressed, 4, false).is_err());
+
+        // A stream that ends early
+        let data = [0, 0, 0, 0, 1];
+        assert!(lzrs_decompress(&data, &mut decompressed, 2, false).is_err());
+    }
+
+    #[test]
+    fn test_lzrs_compress_empty() {
+        assert!(lzrs_compress(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_lzrs_compress_fuzz() {
+        for size in 1..512 {
+            let alphabet = [1, 2, 4, 16, 256][size % 5];
+            roundtrip(&fuzz_input(size as u64, size, alphabet));
+        }
+    }
+
+    #[test]
+    fn test_lzrs_compress_fuzz_window() {
+        // sizes around the window and its refill steps
+        for &size in &[0x7FFF, 0x8000, 0x8001, 0x9001, 0x12345, 0x30000] {
+            for &alphabet in &[2, 256] {
+                roundtrip(&fuzz_input(size as u64, size, alphabet));
             }
+        }
+    }
+
+    #[test]
+    fn test_lzrs_encoder_decoder() {
+        let levels = [
+            CompressionLevel::Fast,
+            CompressionLevel::Balanced,
+            CompressionLevel::Max,
+            CompressionLevel::Custom(MatchParameters {
+                window_log: 4,
+                match_iter: 1,
+            }),
+        ];
+        for &compression_level in &levels {
+            for &size in &[0, 1, 31, 4096] {
+                let data = fuzz_input(size as u64, size, 16);
+
+                let mut encoder = LzrsEncoder::new(vec![]).compression_level(compression_level);
+                encoder.write_all(&data).unwrap();
+                let compressed = encoder.finish().unwrap();
+
+                let mut decoder = LzrsDecoder::new(&compressed[..]).unwrap();
+                assert_eq!(decoder.decompressed_size() as usize, size);
+                assert_eq!(decoder.compressed_size() as usize, compressed.len());
+
+                let mut decompressed = vec![];
+                decoder.read_to_end(&mut decompressed).unwrap();
+                assert_eq!(decompressed, data);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lzrs_decoder_sizes() {
+        // The best ratio lzrs gets still fits under the decompressed size bound
+        let data = vec![0; 1 << 20];
+        for &compression_level in &[CompressionLevel::Fast, CompressionLevel::Max] {
+            let mut encoder = LzrsEncoder::new(vec![]).compression_level(compression_level);
+            encoder.write_all(&data).unwrap();
+            let compressed = encoder.finish().unwrap();
+            let mut decompressed = vec![];
+            LzrsDecoder::new(&compressed[..])
+                .unwrap()
+                .read_to_end(&mut decompressed)
+                .unwrap();
+            assert_eq!(decompressed, data);
+            assert_eq!(lzrs_decompress_object(&compressed).unwrap(), data);
+        }
+
+        let header = |decompressed_size: u32, compressed_size: u32| {
+            let mut data = decompressed_size.to_le_bytes().to_vec();
+            data.extend_from_slice(&compressed_size.to_le_bytes());
+            data
+        };
+        let read = |data: Vec<u8>| {
+            LzrsDecoder::new(&data[..])
+                .unwrap()
+                .read_to_end(&mut vec![])
+                .unwrap_err()
+                .kind()
         };
 
-        Ok(())
+        let mut data = header(u32::MAX, 8 + 4);
+        data.extend_from_slice(&[0; 4]);
+        assert_eq!(
+            lzrs_decompress_object(&data).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(read(data), io::ErrorKind::InvalidData);
+        assert_eq!(
+            lzrs_decompress_object(&header(1, u32::MAX))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        assert_eq!(read(header(1, u32::MAX)), io::ErrorKind::UnexpectedEof);
     }
 }
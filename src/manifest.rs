@@ -0,0 +1,332 @@
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::error::{DpcError, Result};
+use crate::game_profile::GameProfile;
+
+pub const MANIFEST_VERSION: u64 = 2;
+
+// JSON Schema of the current manifest.json
+pub const SCHEMA: &str = include_str!("../manifest.schema.json");
+
+pub fn schema() -> Value {
+    serde_json::from_str(SCHEMA).expect("manifest.schema.json is valid json")
+}
+
+pub fn manifest_version(manifest: &Value) -> Result<u64> {
+    match manifest.get("manifest_version") {
+        None => Ok(1),
+        Some(version) => version.as_u64().ok_or_else(|| {
+            DpcError::Format(format!("manifest_version {} is not a number", version))
+        }),
+    }
+}
+
+// Version 1 only wrote the version numbers and block type for versions missing from the profile,
+// and create took them from the profile otherwise. An empty IncrediBuilder string meant none
+fn migrate_v1(manifest: &mut Map<String, Value>, profile: &GameProfile) -> Result<()> {
+    if let Some(Value::Object(header)) = manifest.get_mut("header") {
+        let version_string = header
+            .get("version_string")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let lookup = version_string
+            .as_deref()
+            .and_then(|version_string| profile.lookup_version(version_string));
+        let fields = ["version_patch", "version_minor", "block_type"];
+        for (i, field) in fields.iter().enumerate() {
+            let value = match lookup {
+                Some((version_patch, version_minor, block_type)) => {
+                    [version_patch, version_minor, block_type][i]
+                }
+                None => header
+                    .get(*field)
+                    .and_then(Value::as_u64)
+                    .and_then(|value| u32::try_from(value).ok())
+                    .ok_or_else(|| {
+                        DpcError::Format(format!(
+                            "header.{} is not set and the {} profile has no version {:?}",
+                            field,
+                            profile.name,
+                            version_string.as_deref().unwrap_or_default()
+                        ))
+                    })?,
+            };
+            header.insert(field.to_string(), Value::from(value));
+        }
+
+        if header.get("incredi_builder_string") == Some(&Value::from("")) {
+            header.insert("incredi_builder_string".to_string(), Value::Null);
+        }
+    }
+    Ok(())
+}
+
+/// Brings an older manifest up to MANIFEST_VERSION. Since version 2 the version numbers and block
+/// type are always written from the BigFile header and used as is by create
+pub fn migrate(mut manifest: Value, profile: &GameProfile) -> Result<Value> {
+    let mut version = manifest_version(&manifest)?;
+    if version > MANIFEST_VERSION {
+        return Err(DpcError::UnsupportedManifestVersion(version));
+    }
+
+    if let Value::Object(object) = &mut manifest {
+        while version < MANIFEST_VERSION {
+            if version == 1 {
+                migrate_v1(object, profile)?;
+            }
+            version += 1;
+        }
+        object.insert("manifest_version".to_string(), Value::from(version));
+    }
+    Ok(manifest)
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(x) if x.is_u64() || x.is_i64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn is_type(value: &Value, name: &str) -> bool {
+    match name {
+        "number" => value.is_number(),
+        name => describe(value) == name,
+    }
+}
+
+// The JSON Schema subset validate_at understands, which is what manifest.schema.json uses:
+// $ref (only into #/$defs), const, type, minimum, maximum, properties, required,
+// additionalProperties and items. The rest are annotations. Any other keyword in the schema is
+// reported as an error rather than silently ignored
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "$ref",
+    "const",
+    "type",
+    "minimum",
+    "maximum",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "$schema",
+    "$defs",
+    "title",
+    "description",
+];
+
+fn validate_at(root: &Value, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let location = if path.is_empty() { "/" } else { path };
+
+    if let Value::Object(schema) = schema {
+        for keyword in schema.keys() {
+            if !SCHEMA_KEYWORDS.contains(&keyword.as_str()) {
+                errors.push(format!(
+                    "{}: unsupported schema keyword `{}`",
+                    location, keyword
+                ));
+            }
+        }
+    }
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match reference
+            .strip_prefix("#/$defs/")
+            .and_then(|name| root.get("$defs")?.get(name))
+        {
+            Some(schema) => validate_at(root, schema, value, path, errors),
+            None => errors.push(format!("{}: unresolved $ref {}", location, reference)),
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            errors.push(format!(
+                "{}: expected {}, found {}",
+                location, expected, value
+            ));
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            types => types.as_str().into_iter().collect(),
+        };
+        if !types.iter().any(|name| is_type(value, name)) {
+            errors.push(format!(
+                "{}: expected {}, found {}",
+                location,
+                types.join(" or "),
+                describe(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(x) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if x < minimum {
+                errors.push(format!("{}: {} is less than {}", location, value, minimum));
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if x > maximum {
+                errors.push(format!("{}: {} is more than {}", location, value, maximum));
+            }
+        }
+    }
+
+    if let Value::Object(object) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    errors.push(format!("{}: missing field `{}`", location, name));
+                }
+            }
+        }
+
+        let properties = schema.get("properties");
+        for (key, property) in object.iter() {
+            let property_path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(property_schema) => {
+                    validate_at(root, property_schema, property, &property_path, errors)
+                }
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        errors.push(format!("{}: unknown field `{}`", location, key))
+                    }
+                    Some(Value::Bool(true)) | None => (),
+                    Some(additional_schema) => {
+                        validate_at(root, additional_schema, property, &property_path, errors)
+                    }
+                },
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(items_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(root, items_schema, item, &format!("{}/{}", path, i), errors);
+        }
+    }
+}
+
+/// Checks a manifest against SCHEMA and returns one line per problem, located by JSON pointer
+pub fn validate(manifest: &Value) -> Vec<String> {
+    let schema = schema();
+    let mut errors = vec![];
+    validate_at(&schema, &schema, manifest, "", &mut errors);
+    errors
+}
+
+/// Reads, migrates and validates a manifest.json
+pub fn load<P: AsRef<Path>>(path: &P, profile: &GameProfile) -> Result<Value> {
+    let manifest: Value = serde_json::from_slice(&fs::read(path.as_ref())?)?;
+    let manifest = migrate(manifest, profile)?;
+    let errors = validate(&manifest);
+    if !errors.is_empty() {
+        return Err(DpcError::InvalidManifest {
+            path: path.as_ref().to_path_buf(),
+            errors,
+        });
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::game_profile::FUEL;
+
+    fn v1_manifest() -> Value {
+        json!({
+            "header": {
+                "version_string": "v1.381.67.09 - Asobo Studio - Internal Cross Technology",
+                "version_minor": null,
+                "version_patch": null,
+                "block_type": null,
+                "is_rtc": false,
+                "pool_manifest_unused": 0,
+                "incredi_builder_string": ""
+            },
+            "blocks": [{ "offset": 0, "objects": [{ "crc32": 1, "compress": true }] }]
+        })
+    }
+
+    #[test]
+    fn test_migrate() {
+        let manifest = migrate(v1_manifest(), &FUEL).unwrap();
+        assert_eq!(validate(&manifest), Vec::<String>::new());
+        assert_eq!(manifest["manifest_version"], json!(MANIFEST_VERSION));
+        assert_eq!(manifest["header"]["version_patch"], json!(272));
+        assert_eq!(manifest["header"]["version_minor"], json!(380));
+        assert_eq!(manifest["header"]["incredi_builder_string"], Value::Null);
+
+        // Already current manifests are left alone
+        assert_eq!(migrate(manifest.clone(), &FUEL).unwrap(), manifest);
+
+        // Without a profile version the manifest has to carry the numbers itself
+        let mut unknown = v1_manifest();
+        unknown["header"]["version_string"] = json!("unknown");
+        match migrate(unknown.clone(), &FUEL) {
+            Err(DpcError::Format(message)) => assert!(message.contains("header.version_patch")),
+            result => panic!("unexpected {:?}", result),
+        }
+        unknown["header"]["version_patch"] = json!(1);
+        unknown["header"]["version_minor"] = json!(2);
+        unknown["header"]["block_type"] = json!(3);
+        let migrated = migrate(unknown, &FUEL).unwrap();
+        assert_eq!(migrated["header"]["version_patch"], json!(1));
+        assert_eq!(migrated["header"]["block_type"], json!(3));
+
+        let mut newer = manifest;
+        newer["manifest_version"] = json!(MANIFEST_VERSION + 1);
+        assert!(matches!(
+            migrate(newer, &FUEL),
+            Err(DpcError::UnsupportedManifestVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut manifest = migrate(v1_manifest(), &FUEL).unwrap();
+        manifest["header"]["is_rtc"] = json!(1);
+        manifest["blocks"][0]["objects"][0]["compresss"] = json!(true);
+        manifest["blocks"][0]["offset"] = json!(-1);
+        manifest["header"]
+            .as_object_mut()
+            .unwrap()
+            .remove("block_type");
+
+        assert_eq!(
+            validate(&manifest),
+            vec![
+                "/blocks/0/objects/0: unknown field `compresss`",
+                "/blocks/0/offset: -1 is less than 0",
+                "/header: missing field `block_type`",
+                "/header/is_rtc: expected boolean, found integer",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_unsupported_keyword() {
+        let schema = json!({ "type": "string", "pattern": "^a" });
+        let mut errors = vec![];
+        validate_at(&schema, &schema, &json!("b"), "", &mut errors);
+        assert_eq!(errors, vec!["/: unsupported schema keyword `pattern`"]);
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::error::{DpcError, Result};
+
+#[derive(Clone, Debug, Default)]
+pub struct NameTable {
+    names: HashMap<u32, String>,
+    crc32s: HashMap<String, u32>,
+}
+
+impl NameTable {
+    pub fn new() -> NameTable {
+        NameTable::default()
+    }
+
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> Result<NameTable> {
+        let mut names = NameTable::new();
+        for path in paths {
+            names.load(path)?;
+        }
+        Ok(names)
+    }
+
+    // Reads the `hash "name"` lines written by CRC32::generate_names, signed or unsigned
+    pub fn load<P: AsRef<Path>>(&mut self, path: &P) -> Result<()> {
+        let file = BufReader::new(File::open(path)?);
+        for (i, line) in file.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid_line = || {
+                DpcError::Format(format!(
+                    "{}:{}: expected `hash \"name\"`",
+                    path.as_ref().display(),
+                    i + 1
+                ))
+            };
+
+            let (hash, name) = line.split_at(line.find(' ').ok_or_else(invalid_line)?);
+            let hash = hash.parse::<i64>().map_err(|_| invalid_line())?;
+            let name = name.trim();
+            if name.len() < 2 || !name.starts_with('"') || !name.ends_with('"') {
+                return Err(invalid_line());
+            }
+
+            self.insert(hash as u32, &name[1..name.len() - 1]);
+        }
+        Ok(())
+    }
+
+    pub fn insert(&mut self, crc32: u32, name: &str) {
+        self.names.insert(crc32, name.to_string());
+        self.crc32s.insert(name.to_string(), crc32);
+    }
+
+    pub fn get(&self, crc32: u32) -> Option<&str> {
+        self.names.get(&crc32).map(|name| name.as_str())
+    }
+
+    pub fn crc32(&self, name: &str) -> Option<u32> {
+        self.crc32s.get(name).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn object_file_name(&self, crc32: u32, class_name: &str) -> String {
+        match self.get(crc32) {
+            Some(name) => format!("{}_{}.{}", crc32, sanitize(name), class_name),
+            None => format!("{}.{}", crc32, class_name),
+        }
+    }
+
+    // Parses an objects/ file stem: `{crc32}`, `{crc32}_{name}` or a bare known name
+    pub fn parse_stem(&self, stem: &str) -> Option<u32> {
+        match stem.split('_').next().map(|crc32| crc32.parse::<u32>()) {
+            Some(Ok(crc32)) => Some(crc32),
+            _ => self.crc32(stem),
+        }
+    }
+
+    // The name of crc32, or crc32 itself when it has none
+    pub fn name_or_crc32(&self, crc32: u32) -> String {
+        match self.get(crc32) {
+            Some(name) => name.to_string(),
+            None => crc32.to_string(),
+        }
+    }
+
+    pub fn describe(&self, crc32: u32) -> String {
+        match self.get(crc32) {
+            Some(name) => format!("{} \"{}\"", crc32, name),
+            None => crc32.to_string(),
+        }
+    }
+
+    pub fn describe_all(&self, crc32s: &[u32]) -> String {
+        format!(
+            "[{}]",
+            crc32s
+                .iter()
+                .map(|crc32| self.describe(*crc32))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+
+    // Adds a `{key}_name` or `{key}_names` sibling next to every crc32 field with a known name
+    pub fn annotate(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                let mut annotations = Vec::new();
+                for (key, value) in map.iter_mut() {
+                    if key.contains("crc32") {
+                        match value {
+                            Value::Number(number) => {
+                                if let Some(name) = number.as_u64().and_then(|x| self.get(x as u32))
+                                {
+                                    annotations.push((format!("{}_name", key), Value::from(name)));
+                                }
+                            }
+                            Value::Array(array)
+                                if array.iter().all(|x| x.is_u64())
+                                    && array.iter().any(|x| {
+                                        self.get(x.as_u64().unwrap() as u32).is_some()
+                                    }) =>
+                            {
+                                let names = array
+                                    .iter()
+                                    .map(|x| match self.get(x.as_u64().unwrap() as u32) {
+                                        Some(name) => Value::from(name),
+                                        None => Value::Null,
+                                    })
+                                    .collect::<Vec<Value>>();
+                                annotations.push((format!("{}_names", key), Value::from(names)));
+                            }
+                            _ => self.annotate(value),
+                        }
+                    } else {
+                        self.annotate(value);
+                    }
+                }
+                for (key, value) in annotations {
+                    if !map.contains_key(&key) {
+                        map.insert(key, value);
+                    }
+                }
+            }
+            Value::Array(array) => {
+                for value in array.iter_mut() {
+                    self.annotate(value);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    pub fn annotate_file<P: AsRef<Path>>(&self, path: &P) -> Result<()> {
+        if self.is_empty() || !path.as_ref().is_file() {
+            return Ok(());
+        }
+
+        let mut value: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        self.annotate(&mut value);
+        fs::write(path, serde_json::to_string_pretty(&value)?)?;
+        Ok(())
+    }
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn names() -> NameTable {
+        let mut names = NameTable::new();
+        names.insert(123, "road.tga");
+        names.insert(7, "car/wheel");
+        names
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = TempDir::new("dpc-names").unwrap();
+        let path = dir.path().join("names.txt");
+        fs::write(&path, "# generated\n\n123 \"road.tga\"\n-1 \"signed\"\n").unwrap();
+        let names = NameTable::from_files(&[&path]).unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.get(123), Some("road.tga"));
+        assert_eq!(names.get(u32::MAX), Some("signed"));
+        assert_eq!(names.crc32("road.tga"), Some(123));
+
+        for line in ["123", "road \"road.tga\"", "123 road.tga", "123 \""] {
+            fs::write(&path, line).unwrap();
+            assert!(NameTable::new().load(&path).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_stems() {
+        let names = names();
+        assert_eq!(
+            names.object_file_name(123, "Bitmap_Z"),
+            "123_road.tga.Bitmap_Z"
+        );
+        assert_eq!(names.object_file_name(7, "Mesh_Z"), "7_car_wheel.Mesh_Z");
+        assert_eq!(names.object_file_name(5, "Mesh_Z"), "5.Mesh_Z");
+
+        for (crc32, class_name) in [(123, "Bitmap_Z"), (7, "Mesh_Z"), (5, "Mesh_Z")] {
+            let file_name = names.object_file_name(crc32, class_name);
+            let stem = Path::new(&file_name).file_stem().unwrap().to_str().unwrap();
+            assert_eq!(names.parse_stem(stem), Some(crc32));
+        }
+
+        assert_eq!(names.parse_stem("road.tga"), Some(123));
+        assert_eq!(names.parse_stem("unknown"), None);
+        assert_eq!(names.name_or_crc32(5), "5");
+        assert_eq!(names.describe_all(&[123, 5]), "[123 \"road.tga\", 5]");
+    }
+
+    #[test]
+    fn test_annotate() {
+        let dir = TempDir::new("dpc-names").unwrap();
+        let path = dir.path().join("object.json");
+        let object = json!({
+            "data_crc32": 123,
+            "unknown_crc32": 5,
+            "material_crc32s": [123, 5],
+            "unknown_crc32s": [5],
+            "link_name": 123,
+            "parent_crc32": 7,
+            "parent_crc32_name": "kept",
+            "nodes": [{ "crc32": 7 }],
+            "child": { "mesh_crc32": 123 }
+        });
+        fs::write(&path, object.to_string()).unwrap();
+
+        NameTable::new().annotate_file(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), object.to_string());
+
+        names().annotate_file(&path).unwrap();
+        let annotated: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            annotated,
+            json!({
+                "data_crc32": 123,
+                "data_crc32_name": "road.tga",
+                "unknown_crc32": 5,
+                "material_crc32s": [123, 5],
+                "material_crc32s_names": ["road.tga", null],
+                "unknown_crc32s": [5],
+                "link_name": 123,
+                "parent_crc32": 7,
+                "parent_crc32_name": "kept",
+                "nodes": [{ "crc32": 7, "crc32_name": "car/wheel" }],
+                "child": { "mesh_crc32": 123, "mesh_crc32_name": "road.tga" }
+            })
+        );
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use glob::{glob_with, MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+
+use crate::bigfile::{read_object_header, read_pool_manifest, read_primary_header};
+use crate::error::Result;
+use crate::parallel::parallel_map;
+
+// Bumped when the cache layout changes, older caches are rescanned
+const CACHE_VERSION: u32 = 2;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ObjectRecord {
+    pub crc32: u32,
+    pub class_crc32: u32,
+    pub blocks: Vec<usize>,
+    pub is_pooled: bool,
+    // placeholder_dpc_index of the reference record holding the pooled object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder_dpc_index: Option<u16>,
+    pub class_object_size: u32,
+    pub decompressed_size: u32,
+    pub compressed_size: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DpcRecord {
+    // Relative to the scanned directory
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: u64,
+    pub version_string: String,
+    pub objects: Vec<ObjectRecord>,
+    // Why the DPC could not be read, it is scanned again once modified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DpcRecord {
+    // A DPC that fails to read is recorded with its error instead of failing the whole scan
+    fn scan<P: AsRef<Path>>(root: &P, path: &Path) -> Result<DpcRecord> {
+        let full_path = root.as_ref().join(path);
+        let (size, modified) = file_stamp(&full_path)?;
+        let (version_string, objects, error) = match DpcRecord::read(&full_path) {
+            Ok((version_string, objects)) => (version_string, objects, None),
+            Err(error) => (String::new(), vec![], Some(error.to_string())),
+        };
+        Ok(DpcRecord {
+            path: path.to_path_buf(),
+            size,
+            modified,
+            version_string,
+            objects,
+            error,
+        })
+    }
+
+    fn read(path: &Path) -> Result<(String, Vec<ObjectRecord>)> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let header = read_primary_header(&mut reader)?;
+
+        let mut objects: Vec<ObjectRecord> = vec![];
+        let mut object_indices: HashMap<u32, usize> = HashMap::new();
+        let mut block_start: u64 = 2048;
+        for (block_index, block_description) in header.block_descriptions.iter().enumerate() {
+            reader.seek(SeekFrom::Start(block_start))?;
+
+            for _ in 0..block_description.object_count {
+                let oh =
+                    read_object_header(&mut reader).map_err(|error| error.in_block(block_index))?;
+                reader.seek(SeekFrom::Current(oh.data_size as i64))?;
+
+                let object_index = *object_indices.entry(oh.crc32).or_insert_with(|| {
+                    objects.push(ObjectRecord {
+                        crc32: oh.crc32,
+                        class_crc32: oh.class_crc32,
+                        blocks: vec![],
+                        is_pooled: false,
+                        placeholder_dpc_index: None,
+                        class_object_size: oh.class_object_size,
+                        decompressed_size: oh.decompressed_size,
+                        compressed_size: oh.compressed_size,
+                    });
+                    objects.len() - 1
+                });
+                let object = &mut objects[object_index];
+                if !object.blocks.contains(&block_index) {
+                    object.blocks.push(block_index);
+                }
+            }
+
+            block_start += block_description.padded_size as u64;
+        }
+
+        if header.pool_manifest_offset != 0 {
+            let manifest = read_pool_manifest(&mut reader, &header)?;
+            for (crc32, reference_record_index) in manifest
+                .crc32s
+                .iter()
+                .zip(manifest.reference_records_indices.iter())
+            {
+                if let Some(object_index) = object_indices.get(crc32) {
+                    let object = &mut objects[*object_index];
+                    object.is_pooled = true;
+                    // Reference record indices start at 1
+                    object.placeholder_dpc_index = manifest
+                        .reference_records
+                        .get((*reference_record_index as usize).wrapping_sub(1))
+                        .map(|record| record.placeholder_dpc_index);
+                }
+            }
+
+            // Pooled objects only keep their class object in the blocks, the data sizes are in
+            // the headers of the pool objects that follow the manifest
+            for object_entry_index in manifest.objects_crc32s.iter() {
+                let oh = read_object_header(&mut reader)?;
+                let is_listed =
+                    manifest.crc32s.get(*object_entry_index as usize) == Some(&oh.crc32);
+                if let Some(object_index) = object_indices.get(&oh.crc32).filter(|_| is_listed) {
+                    let object = &mut objects[*object_index];
+                    object.decompressed_size = oh.decompressed_size;
+                    object.compressed_size = oh.compressed_size;
+                }
+
+                // u64 so a corrupt size seeks past the end instead of overflowing
+                let padded_size = (24 + oh.data_size as u64 + 0x7ff) & !0x7ff;
+                reader.seek(SeekFrom::Current(padded_size as i64 - 24))?;
+            }
+        }
+
+        Ok((header.version_string, objects))
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+fn file_stamp(path: &Path) -> Result<(u64, u64)> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    Ok((metadata.len(), modified))
+}
+
+// An object found in one of the DPCs
+#[derive(Clone, Copy, Debug)]
+pub struct Occurrence<'a> {
+    pub dpc: &'a DpcRecord,
+    pub object: &'a ObjectRecord,
+}
+
+/// Every object of every DPC under a game directory: for each crc32 the DPCs and blocks holding
+/// it, whether it is pooled, its class and its sizes. A DPC that cannot be read does not stop the
+/// scan, it is listed by `failed_dpcs` with its error
+#[derive(Serialize, Deserialize, Default)]
+pub struct ObjectDatabase {
+    version: u32,
+    dpcs: Vec<DpcRecord>,
+    // crc32 -> (dpc index, object index)
+    #[serde(skip)]
+    index: HashMap<u32, Vec<(usize, usize)>>,
+}
+
+impl ObjectDatabase {
+    pub fn new() -> ObjectDatabase {
+        ObjectDatabase {
+            version: CACHE_VERSION,
+            ..Default::default()
+        }
+    }
+
+    pub fn scan<P: AsRef<Path>>(root: &P, threads: usize) -> Result<ObjectDatabase> {
+        let mut database = ObjectDatabase::new();
+        database.update(root, threads)?;
+        Ok(database)
+    }
+
+    /// Loads cache_path if it exists, rescans the DPCs whose size or modification time changed
+    /// since and saves it back
+    pub fn open<P: AsRef<Path>>(
+        root: &P,
+        cache_path: &P,
+        threads: usize,
+    ) -> Result<ObjectDatabase> {
+        let mut database = if cache_path.as_ref().is_file() {
+            ObjectDatabase::load(cache_path)?
+        } else {
+            ObjectDatabase::new()
+        };
+        if database.update(root, threads)? {
+            database.save(cache_path)?;
+        }
+        Ok(database)
+    }
+
+    // A cache from another version or one that doesn't parse comes back empty, so the next
+    // update rescans everything
+    pub fn load<P: AsRef<Path>>(cache_path: &P) -> Result<ObjectDatabase> {
+        let reader = BufReader::new(File::open(cache_path.as_ref())?);
+        let mut database: ObjectDatabase = match serde_json::from_reader(reader) {
+            Ok(database) => database,
+            Err(_) => return Ok(ObjectDatabase::new()),
+        };
+        if database.version != CACHE_VERSION {
+            return Ok(ObjectDatabase::new());
+        }
+        database.build_index();
+        Ok(database)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, cache_path: &P) -> Result<()> {
+        let writer = BufWriter::new(File::create(cache_path.as_ref())?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+
+    // Rescans new and modified DPCs under root and forgets deleted ones. Returns whether anything
+    // changed
+    pub fn update<P: AsRef<Path>>(&mut self, root: &P, threads: usize) -> Result<bool> {
+        let root = root.as_ref();
+        let pattern = Path::new(&Pattern::escape(&root.to_string_lossy()))
+            .join("**")
+            .join("*.dpc");
+        let options = MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        let mut paths = glob_with(pattern.to_string_lossy().as_ref(), options)?
+            .collect::<std::result::Result<Vec<PathBuf>, _>>()?
+            .into_iter()
+            .filter_map(|path| path.strip_prefix(root).ok().map(Path::to_path_buf))
+            .collect::<Vec<PathBuf>>();
+        paths.sort();
+
+        let mut cached: HashMap<PathBuf, DpcRecord> = self
+            .dpcs
+            .drain(..)
+            .map(|dpc| (dpc.path.clone(), dpc))
+            .collect();
+        let cached_count = cached.len();
+
+        let mut stale_paths = vec![];
+        for path in paths.iter() {
+            let is_fresh = match cached.get(path) {
+                Some(dpc) => file_stamp(&root.join(path))? == (dpc.size, dpc.modified),
+                None => false,
+            };
+            if !is_fresh {
+                cached.remove(path);
+                stale_paths.push(path.clone());
+            }
+        }
+
+        let scanned = parallel_map(threads, &stale_paths, |path| DpcRecord::scan(&root, path))?;
+        for dpc in scanned {
+            cached.insert(dpc.path.clone(), dpc);
+        }
+
+        self.dpcs = paths
+            .iter()
+            .filter_map(|path| cached.remove(path))
+            .collect();
+        self.build_index();
+        Ok(!stale_paths.is_empty() || self.dpcs.len() != cached_count)
+    }
+
+    fn build_index(&mut self) {
+        self.index.clear();
+        for (dpc_index, dpc) in self.dpcs.iter().enumerate() {
+            for (object_index, object) in dpc.objects.iter().enumerate() {
+                self.index
+                    .entry(object.crc32)
+                    .or_default()
+                    .push((dpc_index, object_index));
+            }
+        }
+    }
+
+    pub fn dpcs(&self) -> &[DpcRecord] {
+        &self.dpcs
+    }
+
+    pub fn failed_dpcs(&self) -> Vec<&DpcRecord> {
+        self.dpcs.iter().filter(|dpc| dpc.is_failed()).collect()
+    }
+
+    pub fn find(&self, crc32: u32) -> Vec<Occurrence<'_>> {
+        self.index
+            .get(&crc32)
+            .map(|indices| {
+                indices
+                    .iter()
+                    .map(|(dpc_index, object_index)| {
+                        let dpc = &self.dpcs[*dpc_index];
+                        Occurrence {
+                            dpc,
+                            object: &dpc.objects[*object_index],
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Paths of the DPCs holding crc32 as an object of class_crc32, such as
+    /// `dpcs_containing(FUEL.class_crc32("Bitmap_Z").unwrap(), 123456)` for a bitmap
+    pub fn dpcs_containing(&self, class_crc32: u32, crc32: u32) -> Vec<&Path> {
+        self.find(crc32)
+            .into_iter()
+            .filter(|occurrence| occurrence.object.class_crc32 == class_crc32)
+            .map(|occurrence| occurrence.dpc.path.as_path())
+            .collect()
+    }
+
+    pub fn find_class(&self, class_crc32: u32) -> Vec<Occurrence<'_>> {
+        self.dpcs
+            .iter()
+            .flat_map(|dpc| {
+                dpc.objects
+                    .iter()
+                    .filter(move |object| object.class_crc32 == class_crc32)
+                    .map(move |object| Occurrence { dpc, object })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempdir::TempDir;
+
+    use super::*;
+    use crate::bigfile::{BigFileObject, BigFileWriter};
+
+    fn write_dpc(path: &Path, objects: &[(usize, u32, u32)], pooled: &[u32]) {
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        writer.add_block(0);
+        writer.add_block(0);
+        for (block_index, crc32, class_crc32) in objects {
+            if writer.contains(*crc32) {
+                writer.push_existing(*block_index, *crc32, false).unwrap();
+            } else {
+                let object = BigFileObject::new(*crc32, *class_crc32, vec![1], vec![2, 3]);
+                writer.push(*block_index, object, false).unwrap();
+            }
+        }
+        for crc32 in pooled {
+            writer.pool(*crc32);
+        }
+        writer.finish(&mut File::create(path).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_object_database() {
+        let root = TempDir::new("dpc-database").unwrap();
+        fs::create_dir(root.path().join("LEVEL")).unwrap();
+        write_dpc(
+            &root.path().join("A.DPC"),
+            &[(0, 1, 100), (0, 2, 200), (1, 1, 100)],
+            &[1],
+        );
+        write_dpc(&root.path().join("LEVEL/B.dpc"), &[(1, 1, 100)], &[]);
+        fs::write(root.path().join("LEVEL/C.dpc"), [0; 16]).unwrap();
+
+        let cache_path = root.path().join("objects.json");
+        let database = ObjectDatabase::open(&root.path().to_path_buf(), &cache_path, 2).unwrap();
+        assert!(cache_path.is_file());
+
+        assert_eq!(
+            database.dpcs_containing(100, 1),
+            vec![Path::new("A.DPC"), Path::new("LEVEL/B.dpc")]
+        );
+        assert!(database.dpcs_containing(200, 1).is_empty());
+
+        let occurrences = database.find(1);
+        assert_eq!(occurrences[0].object.blocks, vec![0, 1]);
+        assert!(occurrences[0].object.is_pooled);
+        assert_eq!(occurrences[0].object.placeholder_dpc_index, Some(0));
+        // Sizes of pooled objects come from the pool, the same as the unpooled copy in B.dpc
+        let sizes = |object: &ObjectRecord| {
+            (
+                object.class_object_size,
+                object.decompressed_size,
+                object.compressed_size,
+            )
+        };
+        assert_eq!(sizes(occurrences[0].object), (1, 2, 0));
+        assert_eq!(sizes(occurrences[1].object), (1, 2, 0));
+        assert_eq!(occurrences[1].object.blocks, vec![1]);
+        assert!(!occurrences[1].object.is_pooled);
+        assert_eq!(database.find_class(200).len(), 1);
+
+        let failed = database.failed_dpcs();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].path, Path::new("LEVEL/C.dpc"));
+        assert!(failed[0].objects.is_empty() && failed[0].error.is_some());
+
+        let mut database = ObjectDatabase::load(&cache_path).unwrap();
+        assert_eq!(database.find(2).len(), 1);
+        assert!(!database.update(&root.path(), 1).unwrap());
+
+        fs::remove_file(root.path().join("LEVEL/B.dpc")).unwrap();
+        assert!(database.update(&root.path(), 1).unwrap());
+        assert_eq!(database.dpcs().len(), 2);
+        assert_eq!(database.find(1).len(), 1);
+        assert_eq!(database.failed_dpcs().len(), 1);
+    }
+
+    #[test]
+    fn test_object_database_corrupt_cache() {
+        let dir = TempDir::new("dpc-database").unwrap();
+        // Glob metacharacters in the root are matched literally
+        let root = dir.path().join("[GAME] *");
+        fs::create_dir(&root).unwrap();
+        write_dpc(&root.join("A.DPC"), &[(0, 1, 100)], &[]);
+
+        let cache_path = dir.path().join("objects.json");
+        let database = ObjectDatabase::open(&root, &cache_path, 1).unwrap();
+        assert_eq!(database.dpcs().len(), 1);
+
+        let cache = fs::read(&cache_path).unwrap();
+        fs::write(&cache_path, &cache[..cache.len() / 2]).unwrap();
+        assert!(ObjectDatabase::load(&cache_path).unwrap().dpcs().is_empty());
+
+        let database = ObjectDatabase::open(&root, &cache_path, 1).unwrap();
+        assert_eq!(database.find(1).len(), 1);
+        assert_eq!(fs::read(&cache_path).unwrap(), cache);
+    }
+
+    #[test]
+    fn test_object_database_corrupt_pool() {
+        let root = TempDir::new("dpc-database").unwrap();
+        let path = root.path().join("A.DPC");
+        write_dpc(&path, &[(0, 1, 100), (0, 2, 200)], &[1, 2]);
+        write_dpc(&root.path().join("B.DPC"), &[(0, 1, 100)], &[]);
+
+        // data_size of the first pool object
+        let mut data = fs::read(&path).unwrap();
+        let header = read_primary_header(&mut std::io::Cursor::new(&data)).unwrap();
+        let offset = (header.pool_manifest_offset + header.pool_manifest_padded_size) as usize;
+        data[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        fs::write(&path, data).unwrap();
+
+        let cache_path = root.path().join("objects.json");
+        let database = ObjectDatabase::open(&root.path().to_path_buf(), &cache_path, 2).unwrap();
+        let failed = database.failed_dpcs();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].path, Path::new("A.DPC"));
+        assert_eq!(database.find(1).len(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use checksums::{hash_reader, Algorithm};
+
+use crate::bigfile::BigFileObject;
+use crate::error::{DpcError, Result};
+
+// Object files shared by many extracted BigFiles, named `{crc32}_{hash}.{Class}` after the crc32
+// and a hash of the decompressed class object and data
+pub struct ObjectStore {
+    path: PathBuf,
+}
+
+impl ObjectStore {
+    pub fn open<P: AsRef<Path>>(path: &P) -> Result<ObjectStore> {
+        fs::create_dir_all(path.as_ref())?;
+        Ok(ObjectStore {
+            path: path.as_ref().canonicalize()?,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn object_path(&self, file_name: &str) -> PathBuf {
+        self.path.join(file_name)
+    }
+
+    // Objects that are still compressed get their own key so create writes them back as they were
+    pub fn key(object: &BigFileObject) -> Result<String> {
+        let data = object.decompressed_data()?;
+        let hash = hash_reader(
+            &mut object.class_object.as_slice().chain(data.as_slice()),
+            Algorithm::SHA2256,
+        );
+        let suffix = if object.is_compressed() { "_lz" } else { "" };
+        Ok(format!(
+            "{}_{}{}",
+            object.header.crc32,
+            hash.to_lowercase(),
+            suffix
+        ))
+    }
+
+    // Moves an extracted object file into the store, or deletes it when the store already has
+    // it. Returns its file name in the store
+    pub fn insert_file<P: AsRef<Path>>(&self, object_file_path: &P) -> Result<String> {
+        let object_file_path = object_file_path.as_ref();
+        let object = BigFileObject::read(&mut File::open(object_file_path)?)?;
+        let mut file_name = ObjectStore::key(&object)?;
+        if let Some(extension) = object_file_path.extension() {
+            file_name.push('.');
+            file_name.push_str(&extension.to_string_lossy());
+        }
+
+        let store_path = self.object_path(&file_name);
+        if store_path.is_file() {
+            fs::remove_file(object_file_path)?;
+        } else if fs::rename(object_file_path, &store_path).is_err() {
+            // The store is on another file system, copy through a temporary file so other
+            // extractions never see a partial object
+            let temporary_path = store_path.with_extension(format!("{}.tmp", process::id()));
+            fs::copy(object_file_path, &temporary_path)?;
+            fs::rename(&temporary_path, &store_path)?;
+            fs::remove_file(object_file_path)?;
+        }
+        Ok(file_name)
+    }
+
+    pub fn read(&self, crc32: u32, file_name: &str) -> Result<BigFileObject> {
+        let store_path = self.object_path(file_name);
+        if !store_path.is_file() {
+            return Err(DpcError::MissingObject(crc32));
+        }
+        BigFileObject::read(&mut File::open(store_path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempdir::TempDir;
+
+    use super::*;
+    use crate::lz::CompressionLevel;
+
+    fn write_object(path: &Path, object: &BigFileObject) {
+        object.write(&mut File::create(path).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_object_store() {
+        let dir = TempDir::new("dpc-store").unwrap();
+        let store = ObjectStore::open(&dir.path().join("store")).unwrap();
+
+        let object = BigFileObject::new(1, 100, vec![1, 2], vec![3; 100]);
+        let compressed = object.compressed(CompressionLevel::Fast).unwrap();
+        let other = BigFileObject::new(1, 100, vec![1, 2], vec![4; 100]);
+
+        let first_path = dir.path().join("1.Bitmap_Z");
+        let second_path = dir.path().join("1_copy.Bitmap_Z");
+        write_object(&first_path, &object);
+        write_object(&second_path, &object);
+        let first = store.insert_file(&first_path).unwrap();
+        let second = store.insert_file(&second_path).unwrap();
+        assert_eq!(first, second);
+        assert!(first.starts_with("1_") && first.ends_with(".Bitmap_Z"));
+        assert!(!first_path.exists() && !second_path.exists());
+        assert_eq!(store.read(1, &first).unwrap().data, object.data);
+
+        write_object(&first_path, &compressed);
+        let compressed_name = store.insert_file(&first_path).unwrap();
+        assert_ne!(compressed_name, first);
+        assert!(store.read(1, &compressed_name).unwrap().is_compressed());
+
+        write_object(&first_path, &other);
+        assert_ne!(store.insert_file(&first_path).unwrap(), first);
+
+        assert!(store.read(2, "2_missing.Bitmap_Z").is_err());
+    }
+}
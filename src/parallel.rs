@@ -0,0 +1,79 @@
+use std::cmp::min;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use crate::error::Result;
+
+// Maps items on up to `threads` threads, returning results in item order
+pub fn parallel_map<T, R, F>(threads: usize, items: &[T], f: F) -> Result<Vec<R>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> Result<R> + Sync,
+{
+    let threads = min(threads, items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    let mut results: Vec<(usize, Result<R>)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    while !failed.load(Ordering::Relaxed) {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= items.len() {
+                            break;
+                        }
+                        let result = f(&items[i]);
+                        if result.is_err() {
+                            failed.store(true, Ordering::Relaxed);
+                        }
+                        results.push((i, result));
+                    }
+                    results
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|error| panic::resume_unwind(error))
+            })
+            .collect()
+    });
+
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::DpcError;
+
+    #[test]
+    fn test_parallel_map_order() {
+        let items: Vec<u32> = (0..1000).collect();
+        let results = parallel_map(8, &items, |x| Ok(x * 2)).unwrap();
+        assert_eq!(results, items.iter().map(|x| x * 2).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_parallel_map_error() {
+        let items: Vec<u32> = (0..100).collect();
+        let result = parallel_map(4, &items, |x| match x {
+            50 => Err(DpcError::MissingObject(*x)),
+            _ => Ok(*x),
+        });
+        assert!(matches!(result, Err(DpcError::MissingObject(50))));
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek, Write};
+
+use crate::bigfile::{BigFileIndex, BigFileObject, BigFileWriter, PoolLayout, PrimaryHeader};
+use crate::error::{DpcError, Result};
+use crate::lz::CompressionLevel;
+
+/// Replaces and adds objects in an existing BigFile. Replaced objects are compressed the way the
+/// originals were. Blocks that reference none of them are copied as is, the others and the pool
+/// are laid out again
+pub struct BigFilePatch {
+    replacements: HashMap<u32, BigFileObject>,
+    // (block index, object, compress)
+    additions: Vec<(usize, BigFileObject, bool)>,
+    compression_level: CompressionLevel,
+    threads: usize,
+}
+
+impl Default for BigFilePatch {
+    fn default() -> Self {
+        BigFilePatch::new()
+    }
+}
+
+impl BigFilePatch {
+    pub fn new() -> BigFilePatch {
+        BigFilePatch {
+            replacements: HashMap::new(),
+            additions: Vec::new(),
+            compression_level: CompressionLevel::default(),
+            threads: 1,
+        }
+    }
+
+    pub fn compression_level(mut self, compression_level: CompressionLevel) -> BigFilePatch {
+        self.compression_level = compression_level;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> BigFilePatch {
+        self.threads = threads;
+        self
+    }
+
+    // Replacements are compressed when the object they replace was
+    pub fn replace(&mut self, object: BigFileObject) {
+        self.replacements.insert(object.header.crc32, object);
+    }
+
+    pub fn add(&mut self, block_index: usize, object: BigFileObject, compress: bool) {
+        self.additions.push((block_index, object, compress));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty() && self.additions.is_empty()
+    }
+
+    // Only the objects of the patched blocks and the pool are held in memory, the other blocks
+    // are streamed from reader into writer
+    pub fn apply<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<PrimaryHeader> {
+        let mut index = BigFileIndex::new(&mut *reader)?;
+
+        for crc32 in self.replacements.keys() {
+            if !index.contains(*crc32) {
+                return Err(DpcError::MissingObject(*crc32));
+            }
+        }
+        for (block_index, object, _) in self.additions.iter() {
+            if *block_index >= index.blocks.len() {
+                return Err(DpcError::Format(format!(
+                    "block {} does not exist",
+                    block_index
+                )));
+            }
+            if index.contains(object.header.crc32) {
+                return Err(DpcError::Format(format!(
+                    "{} is already in the BigFile, replace it instead",
+                    object.header.crc32
+                )));
+            }
+        }
+
+        let header = &index.header;
+        let mut bigfile_writer = BigFileWriter::new(
+            &header.version_string,
+            header.version_patch,
+            header.version_minor,
+            header
+                .block_descriptions
+                .first()
+                .map_or(0, |description| description.block_type),
+        )
+        .rtc(header.is_not_rtc == 0)
+        .pool_manifest_unused(header.pool_manifest_unused0)
+        .incredi_builder_string(&header.incredi_builder_string)
+        .compression_level(self.compression_level)
+        .threads(self.threads);
+
+        if let Some(pool_manifest) = &index.pool_manifest {
+            bigfile_writer.set_pool_layout(PoolLayout::from_manifest(pool_manifest));
+        }
+
+        let blocks = index.blocks.clone();
+        let mut is_compressed: HashMap<u32, bool> = HashMap::new();
+        let mut block_start: u64 = 2048;
+        for (block_index, block) in blocks.iter().enumerate() {
+            let description = block.description;
+            let is_patched = block
+                .object_headers
+                .iter()
+                .any(|oh| self.replacements.contains_key(&oh.crc32))
+                || self.additions.iter().any(|(i, _, _)| *i == block_index);
+
+            let writer_block_index = if is_patched {
+                let writer_block_index =
+                    bigfile_writer.add_block(description.working_buffer_offset);
+                bigfile_writer.set_block_type(writer_block_index, description.block_type)?;
+                writer_block_index
+            } else {
+                bigfile_writer.add_copied_block(description, block_start)
+            };
+
+            for oh in block.object_headers.iter() {
+                let location = index
+                    .location(oh.crc32)
+                    .ok_or(DpcError::MissingObject(oh.crc32))?;
+                let is_pooled = location.pool_offset.is_some();
+                // Objects of copied blocks are only needed to lay out the pool
+                if !is_patched && !is_pooled {
+                    continue;
+                }
+
+                if !bigfile_writer.contains(oh.crc32) {
+                    let object = index.read_object(oh.crc32)?;
+                    is_compressed.insert(oh.crc32, object.is_compressed());
+                    let object = match self.replacements.get(&oh.crc32) {
+                        Some(replacement) => replacement.clone(),
+                        None => object,
+                    };
+                    bigfile_writer.push(writer_block_index, object, is_compressed[&oh.crc32])?;
+                } else {
+                    bigfile_writer.push_existing(
+                        writer_block_index,
+                        oh.crc32,
+                        is_compressed[&oh.crc32],
+                    )?;
+                }
+            }
+
+            for (_, object, compress) in self.additions.iter().filter(|(i, _, _)| *i == block_index)
+            {
+                bigfile_writer.push(writer_block_index, object.clone(), *compress)?;
+            }
+
+            block_start += description.padded_size as u64;
+        }
+
+        bigfile_writer.finish_copying(index.into_inner(), writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::bigfile::BigFile;
+    use crate::verify::verify_layout;
+
+    #[test]
+    fn test_patch() {
+        let data: Vec<u8> = (0..4096).map(|x| (x % 7) as u8).collect();
+
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        let first = writer.add_block(0);
+        let second = writer.add_block(0);
+        writer
+            .push(first, BigFileObject::new(1, 100, vec![1, 2, 3], data), true)
+            .unwrap();
+        writer
+            .push(
+                second,
+                BigFileObject::new(2, 200, vec![4], vec![5, 6]),
+                false,
+            )
+            .unwrap();
+        let mut input = Cursor::new(Vec::new());
+        writer.finish(&mut input).unwrap();
+
+        let mut patch = BigFilePatch::new();
+        patch.replace(BigFileObject::new(2, 200, vec![4], vec![7, 8, 9]));
+        patch.add(1, BigFileObject::new(3, 300, vec![], vec![10]), false);
+        let mut output = Cursor::new(Vec::new());
+        let header = patch.apply(&mut input, &mut output).unwrap();
+
+        // The first block is untouched and copied as is
+        let first_size = header.block_descriptions[0].padded_size as usize;
+        assert_eq!(
+            input.get_ref()[2048..2048 + first_size],
+            output.get_ref()[2048..2048 + first_size]
+        );
+        assert_eq!(verify_layout(&mut output).unwrap(), vec![]);
+
+        let bigfile = BigFile::read(&mut output).unwrap();
+        assert_eq!(bigfile.object(2).unwrap().data, vec![7, 8, 9]);
+        assert_eq!(bigfile.object(3).unwrap().data, vec![10]);
+
+        let mut patch = BigFilePatch::new();
+        patch.replace(BigFileObject::new(4, 400, vec![], vec![]));
+        assert!(patch
+            .apply(&mut input, &mut Cursor::new(Vec::new()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_patch_pooled() {
+        let data: Vec<u8> = (0..4096).map(|x| (x % 5) as u8).collect();
+
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        let first = writer.add_block(0);
+        let second = writer.add_block(0);
+        writer
+            .push(
+                first,
+                BigFileObject::new(1, 100, vec![1], data.clone()),
+                true,
+            )
+            .unwrap();
+        writer
+            .push(first, BigFileObject::new(2, 200, vec![2], vec![3]), false)
+            .unwrap();
+        writer.push_existing(second, 1, true).unwrap();
+        writer
+            .push(second, BigFileObject::new(4, 400, vec![], vec![5]), false)
+            .unwrap();
+        writer.pool(1);
+        let mut input = Cursor::new(Vec::new());
+        writer.finish(&mut input).unwrap();
+
+        let mut patch = BigFilePatch::new();
+        patch.replace(BigFileObject::new(4, 400, vec![], vec![6, 7]));
+        let mut output = Cursor::new(Vec::new());
+        let header = patch.apply(&mut input, &mut output).unwrap();
+
+        let first_size = header.block_descriptions[0].padded_size as usize;
+        assert_eq!(
+            input.get_ref()[2048..2048 + first_size],
+            output.get_ref()[2048..2048 + first_size]
+        );
+        assert_eq!(verify_layout(&mut output).unwrap(), vec![]);
+
+        let bigfile = BigFile::read(&mut output).unwrap();
+        assert!(bigfile.is_pooled(1));
+        assert_eq!(
+            bigfile.object(1).unwrap().decompressed_data().unwrap(),
+            data
+        );
+        assert_eq!(bigfile.object(2).unwrap().data, vec![3]);
+        assert_eq!(bigfile.object(4).unwrap().data, vec![6, 7]);
+    }
+}
@@ -0,0 +1,421 @@
+use std::cmp::max;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use crate::bigfile::{
+    read_object_header, read_pool_manifest, read_primary_header, BigFile, BigFileObject,
+    ObjectHeader,
+};
+use crate::error::{DpcError, Result};
+use crate::lz::LzrsDecoder;
+use crate::parallel::parallel_map;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub crc32: Option<u32>,
+    pub message: String,
+}
+
+impl Mismatch {
+    fn new(crc32: Option<u32>, message: String) -> Mismatch {
+        Mismatch { crc32, message }
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.crc32 {
+            Some(crc32) => write!(f, "{}: {}", crc32, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Sizes read back from the file are summed as u64 so corrupt ones can't overflow
+fn calculate_padded_size(size: u64) -> u64 {
+    (size + 0x7ff) & !0x7ff
+}
+
+fn verify_object_header(oh: &ObjectHeader, mismatches: &mut Vec<Mismatch>) {
+    let size = match oh.compressed_size {
+        0 => oh.decompressed_size,
+        compressed_size => compressed_size,
+    };
+    let expected_data_size = oh.class_object_size as u64 + size as u64;
+    if oh.data_size as u64 != expected_data_size {
+        mismatches.push(Mismatch::new(
+            Some(oh.crc32),
+            format!(
+                "data_size is {} but class_object_size + data is {}",
+                oh.data_size, expected_data_size
+            ),
+        ));
+    }
+}
+
+/// Recomputes the block sizes, working buffer capacities, pool decompression buffer capacity and
+/// pool reference record chunk ranges from what is on disk and lists those the headers disagree on
+pub fn verify_layout<R: Read + Seek>(reader: &mut R) -> Result<Vec<Mismatch>> {
+    let header = read_primary_header(reader)?;
+    let mut mismatches = vec![];
+
+    let mut block_start: u64 = 2048;
+    let mut block_working_buffer_capacity_even = 0;
+    let mut block_working_buffer_capacity_odd = 0;
+    for (block_index, block_description) in header.block_descriptions.iter().enumerate() {
+        reader.seek(SeekFrom::Start(block_start))?;
+
+        let mut data_size: u64 = 0;
+        for _ in 0..block_description.object_count {
+            let oh = read_object_header(reader).map_err(|error| error.in_block(block_index))?;
+            verify_object_header(&oh, &mut mismatches);
+            reader.seek(SeekFrom::Current(oh.data_size as i64))?;
+            data_size += 24 + oh.data_size as u64;
+        }
+
+        if block_description.data_size as u64 != data_size {
+            mismatches.push(Mismatch::new(
+                None,
+                format!(
+                    "block {}: data_size is {} but its objects take {}",
+                    block_index, block_description.data_size, data_size
+                ),
+            ));
+        }
+        if block_description.padded_size as u64 != calculate_padded_size(data_size) {
+            mismatches.push(Mismatch::new(
+                None,
+                format!(
+                    "block {}: padded_size is {} but should be {}",
+                    block_index,
+                    block_description.padded_size,
+                    calculate_padded_size(data_size)
+                ),
+            ));
+        }
+
+        let block_working_buffer_capacity =
+            block_description.padded_size as u64 + block_description.working_buffer_offset as u64;
+        if block_index % 2 == 0 {
+            block_working_buffer_capacity_even = max(
+                block_working_buffer_capacity_even,
+                block_working_buffer_capacity,
+            );
+        } else {
+            block_working_buffer_capacity_odd = max(
+                block_working_buffer_capacity_odd,
+                block_working_buffer_capacity,
+            );
+        }
+
+        block_start += block_description.padded_size as u64;
+    }
+
+    if header.padded_size as u64 != block_start - 2048 {
+        mismatches.push(Mismatch::new(
+            None,
+            format!(
+                "padded_size is {} but the blocks take {}",
+                header.padded_size,
+                block_start - 2048
+            ),
+        ));
+    }
+    if header.block_working_buffer_capacity_even as u64 != block_working_buffer_capacity_even {
+        mismatches.push(Mismatch::new(
+            None,
+            format!(
+                "block_working_buffer_capacity_even is {} but the even blocks need {}",
+                header.block_working_buffer_capacity_even, block_working_buffer_capacity_even
+            ),
+        ));
+    }
+    if header.block_working_buffer_capacity_odd as u64 != block_working_buffer_capacity_odd {
+        mismatches.push(Mismatch::new(
+            None,
+            format!(
+                "block_working_buffer_capacity_odd is {} but the odd blocks need {}",
+                header.block_working_buffer_capacity_odd, block_working_buffer_capacity_odd
+            ),
+        ));
+    }
+
+    if header.pool_manifest_offset == 0 {
+        return Ok(mismatches);
+    }
+
+    if header.pool_manifest_offset as u64 != block_start {
+        mismatches.push(Mismatch::new(
+            None,
+            format!(
+                "pool manifest starts at {} but the blocks end at {}",
+                header.pool_manifest_offset, block_start
+            ),
+        ));
+    }
+
+    let manifest = read_pool_manifest(reader, &header)?;
+
+    // Chunk index of every pool object, and of the end of the last one
+    let mut chunk_indices: Vec<u64> = vec![];
+    let mut chunk_index = reader.stream_position()? / 2048;
+    let mut pool_object_decompression_buffer_capacity = 0;
+    for object_entry_index in manifest.objects_crc32s.iter() {
+        chunk_indices.push(chunk_index);
+
+        let oh = read_object_header(reader)?;
+        verify_object_header(&oh, &mut mismatches);
+
+        let padded_size = calculate_padded_size(24 + oh.data_size as u64) >> 11;
+        let index = *object_entry_index as usize;
+        match manifest.crc32s.get(index) {
+            Some(crc32) if *crc32 != oh.crc32 => mismatches.push(Mismatch::new(
+                Some(oh.crc32),
+                format!(
+                    "pool object at chunk {} is listed as {}",
+                    chunk_index, crc32
+                ),
+            )),
+            Some(_) => (),
+            None => mismatches.push(Mismatch::new(
+                Some(oh.crc32),
+                format!("pool entry {} does not exist", index),
+            )),
+        }
+        match manifest.object_padded_size.get(index) {
+            Some(x) if *x as u64 != padded_size => mismatches.push(Mismatch::new(
+                Some(oh.crc32),
+                format!(
+                    "object_padded_size is {} but the pool object takes {} chunks",
+                    x, padded_size
+                ),
+            )),
+            _ => (),
+        }
+
+        pool_object_decompression_buffer_capacity = max(
+            pool_object_decompression_buffer_capacity,
+            calculate_padded_size(oh.decompressed_size as u64) >> 11,
+        );
+
+        chunk_index += padded_size;
+        reader.seek(SeekFrom::Current((padded_size as i64) * 2048 - 24))?;
+    }
+    chunk_indices.push(chunk_index);
+
+    if header.pool_object_decompression_buffer_capacity as u64
+        != pool_object_decompression_buffer_capacity
+    {
+        mismatches.push(Mismatch::new(
+            None,
+            format!(
+                "pool_object_decompression_buffer_capacity is {} but the pool objects need {}",
+                header.pool_object_decompression_buffer_capacity,
+                pool_object_decompression_buffer_capacity
+            ),
+        ));
+    }
+
+    for (record_index, record) in manifest.reference_records.iter().enumerate() {
+        let start = record.objects_crc32_starting_index as usize;
+        let end = start + record.objects_crc32_count as usize;
+        if end > manifest.objects_crc32s.len() {
+            mismatches.push(Mismatch::new(
+                None,
+                format!(
+                    "reference record {} covers pool objects {}..{} of {}",
+                    record_index,
+                    start,
+                    end,
+                    manifest.objects_crc32s.len()
+                ),
+            ));
+            continue;
+        }
+
+        if record.start_chunk_index as u64 != chunk_indices[start]
+            || record.end_chunk_index as u64 != chunk_indices[end]
+        {
+            mismatches.push(Mismatch::new(
+                None,
+                format!(
+                    "reference record {} spans chunks {}..{} but its objects are at {}..{}",
+                    record_index,
+                    record.start_chunk_index,
+                    record.end_chunk_index,
+                    chunk_indices[start],
+                    chunk_indices[end]
+                ),
+            ));
+        }
+    }
+
+    Ok(mismatches)
+}
+
+fn decompressed_data(object: &BigFileObject) -> std::result::Result<Vec<u8>, String> {
+    if !object.is_compressed() {
+        return Ok(object.data.clone());
+    }
+
+    let mut decoder = LzrsDecoder::new(&object.data[..]).map_err(|error| error.to_string())?;
+    if decoder.decompressed_size() != object.header.decompressed_size
+        || decoder.compressed_size() != object.header.compressed_size
+    {
+        return Err(format!(
+            "the lz header says {} -> {} bytes but the object header says {} -> {}",
+            decoder.decompressed_size(),
+            decoder.compressed_size(),
+            object.header.decompressed_size,
+            object.header.compressed_size
+        ));
+    }
+
+    let mut data = Vec::with_capacity(decoder.decompressed_size() as usize);
+    decoder
+        .read_to_end(&mut data)
+        .map_err(|error| error.to_string())?;
+    Ok(data)
+}
+
+fn verify_object(object: &BigFileObject, source_path: Option<&PathBuf>) -> Result<Option<String>> {
+    let data = match decompressed_data(object) {
+        Ok(data) => data,
+        Err(error) => return Ok(Some(format!("failed to decompress: {}", error))),
+    };
+
+    let source_path = match source_path {
+        Some(source_path) => source_path,
+        None => return Ok(Some("no source object".to_string())),
+    };
+    let source = BigFileObject::read(&mut File::open(source_path)?)?;
+    let source_data = match decompressed_data(&source) {
+        Ok(data) => data,
+        Err(error) => {
+            return Ok(Some(format!(
+                "failed to decompress {}: {}",
+                source_path.display(),
+                error
+            )))
+        }
+    };
+
+    let message = if object.header.class_crc32 != source.header.class_crc32 {
+        format!(
+            "class crc32 is {} but {} has {}",
+            object.header.class_crc32,
+            source_path.display(),
+            source.header.class_crc32
+        )
+    } else if object.class_object != source.class_object {
+        format!("class object differs from {}", source_path.display())
+    } else if data != source_data {
+        format!("data differs from {}", source_path.display())
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(message))
+}
+
+/// Decompresses every object and compares it with its source object file
+pub fn verify_objects(
+    bigfile: &BigFile,
+    source_paths: &HashMap<u32, PathBuf>,
+    threads: usize,
+) -> Result<Vec<Mismatch>> {
+    let mut crc32s: Vec<u32> = bigfile.crc32s().copied().collect();
+    crc32s.sort_unstable();
+
+    let messages = parallel_map(threads, &crc32s, |crc32| {
+        let object = bigfile
+            .object(*crc32)
+            .ok_or(DpcError::MissingObject(*crc32))?;
+        verify_object(object, source_paths.get(crc32))
+    })?;
+
+    Ok(crc32s
+        .into_iter()
+        .zip(messages)
+        .filter_map(|(crc32, message)| message.map(|message| Mismatch::new(Some(crc32), message)))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Cursor, Write};
+
+    use super::*;
+    use crate::bigfile::BigFileWriter;
+
+    #[test]
+    fn test_verify_layout() {
+        let data: Vec<u8> = (0..4096).map(|x| (x % 7) as u8).collect();
+
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        let first = writer.add_block(0);
+        let second = writer.add_block(0);
+        writer
+            .push(first, BigFileObject::new(1, 100, vec![1, 2, 3], data), true)
+            .unwrap();
+        writer
+            .push(
+                second,
+                BigFileObject::new(2, 200, vec![4], vec![5, 6]),
+                false,
+            )
+            .unwrap();
+        writer.push_existing(second, 1, true).unwrap();
+        writer.pool(1);
+
+        let mut cursor = Cursor::new(Vec::new());
+        writer.finish(&mut cursor).unwrap();
+        assert_eq!(verify_layout(&mut cursor).unwrap(), vec![]);
+
+        let bigfile = BigFile::read(&mut cursor).unwrap();
+        let mismatches = verify_objects(&bigfile, &HashMap::new(), 2).unwrap();
+        assert_eq!(
+            mismatches,
+            vec![
+                Mismatch::new(Some(1), "no source object".to_string()),
+                Mismatch::new(Some(2), "no source object".to_string()),
+            ]
+        );
+
+        // pool_object_decompression_buffer_capacity
+        cursor.seek(SeekFrom::Start(0x730)).unwrap();
+        cursor.write_all(&7u32.to_le_bytes()).unwrap();
+        let mismatches = verify_layout(&mut cursor).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].crc32, None);
+    }
+
+    #[test]
+    fn test_verify_layout_overflow() {
+        let mut writer = BigFileWriter::new("v1.381.67.09", 272, 380, 253);
+        let block = writer.add_block(0);
+        writer
+            .push(block, BigFileObject::new(1, 100, vec![1], vec![2]), false)
+            .unwrap();
+        let mut cursor = Cursor::new(Vec::new());
+        writer.finish(&mut cursor).unwrap();
+
+        // data_size of the only object, 24 + data_size no longer fits in a u32
+        cursor.seek(SeekFrom::Start(2048)).unwrap();
+        cursor.write_all(&(u32::MAX - 8).to_le_bytes()).unwrap();
+        cursor.write_all(&(u32::MAX - 8).to_le_bytes()).unwrap();
+        cursor.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        let mismatches = verify_layout(&mut cursor).unwrap();
+        assert!(mismatches.contains(&Mismatch::new(
+            Some(1),
+            format!(
+                "data_size is {} but class_object_size + data is {}",
+                u32::MAX - 8,
+                (u32::MAX - 8) as u64 + u32::MAX as u64
+            )
+        )));
+        assert!(mismatches.iter().any(|x| x.crc32.is_none()));
+    }
+}
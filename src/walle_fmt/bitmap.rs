@@ -1,13 +1,15 @@
 use std::fs::File;
-use std::io::{Error, ErrorKind, Write};
+use std::io::Write;
 use std::path::Path;
 use std::vec;
 
 use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::walle_fmt::common::{FixedVec, HasReferences, WALLEObjectFormatTrait};
+use crate::asobo_fmt::common::{FixedVec, HasReferences, ObjectFormatTrait};
+use crate::error::DpcError;
 use ddsfile::{D3DFormat, Dds};
 
 #[derive(BinWrite)]
@@ -86,13 +88,13 @@ impl BitmapObjectFormat {
     }
 }
 
-impl WALLEObjectFormatTrait for BitmapObjectFormat {
+impl ObjectFormatTrait for BitmapObjectFormat {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
@@ -103,7 +105,7 @@ impl WALLEObjectFormatTrait for BitmapObjectFormat {
         let dds_path = input_path.join("data.dds");
         let mut dds_file = File::open(dds_path)?;
 
-        let dds = Dds::read(&mut dds_file).unwrap();
+        let dds = Dds::read(&mut dds_file)?;
 
         object.bitmap.width = dds.get_width();
         object.bitmap.height = dds.get_height();
@@ -112,7 +114,7 @@ impl WALLEObjectFormatTrait for BitmapObjectFormat {
         object.bitmap.data.clear();
         object.bitmap.write(body)?;
 
-        dds.data.write(body).unwrap();
+        dds.data.write(body)?;
 
         Ok((
             object.bitmap_header.hard_links(),
@@ -121,22 +123,22 @@ impl WALLEObjectFormatTrait for BitmapObjectFormat {
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
         let bitmap_header = match BitmapZHeader::parse(&header) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
         };
 
         let bitmap = match BitmapZ::parse(body) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
         };
 
         let dds_path = output_path.join("data.dds");
@@ -153,12 +155,11 @@ impl WALLEObjectFormatTrait for BitmapObjectFormat {
             },
             Some(0),
             None,
-        )
-        .unwrap();
+        )?;
 
         dds.data = bitmap.data[128..].to_vec();
 
-        dds.write(&mut output_dds_file).unwrap();
+        dds.write(&mut output_dds_file)?;
 
         let object = BitmapObject {
             bitmap_header,
@@ -172,4 +173,21 @@ impl WALLEObjectFormatTrait for BitmapObjectFormat {
             object.bitmap_header.soft_links(),
         ))
     }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        let bitmap_header = match BitmapZHeader::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        let bitmap = match BitmapZ::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
+
+        Ok(serde_json::to_value(BitmapObject {
+            bitmap_header,
+            bitmap,
+        })?)
+    }
 }
@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::walle_fmt::common::{HasReferences, PascalArray, ResourceObjectZ, WALLEObjectFormat};
+use crate::asobo_fmt::common::{HasReferences, ObjectFormat, PascalArray, ResourceObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -26,4 +26,4 @@ impl HasReferences for GameObjZ {
     }
 }
 
-pub type GameObjObjectFormat = WALLEObjectFormat<ResourceObjectZ, GameObjZ>;
+pub type GameObjObjectFormat = ObjectFormat<ResourceObjectZ, GameObjZ>;
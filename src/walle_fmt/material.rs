@@ -2,8 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::walle_fmt::common::{
-    write_option, FixedVec, HasReferences, ResourceObjectZ, Vec3f, Vec4f, WALLEObjectFormat,
+use crate::asobo_fmt::common::{
+    write_option, FixedVec, HasReferences, ObjectFormat, ResourceObjectZ, Vec3f, Vec4f,
 };
 
 #[derive(BinWrite)]
@@ -55,4 +55,4 @@ impl HasReferences for MaterialZ {
     }
 }
 
-pub type MaterialObjectFormat = WALLEObjectFormat<ResourceObjectZ, MaterialZ>;
+pub type MaterialObjectFormat = ObjectFormat<ResourceObjectZ, MaterialZ>;
@@ -3,50 +3,18 @@ use nom::multi::count;
 use nom::IResult;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
-use std::convert::TryInto;
-// use std::ffi::{OsStr, OsString};
+use serde_json::{json, Value};
 use std::fs::File;
-use std::io::{Error, ErrorKind, Write};
-// use std::iter::Map;
+use std::io::Write;
 use std::path::Path;
-// use std::sync::Arc;
-// use three_d_asset::prelude;
-
-// use crate::base_dpc::DPC;
-// use crate::base_dpc::{Options, DPC};
-// use crate::walle_dpc::WALLEDPC;
-use crate::walle_fmt::common::{
-    FixedVec, HasReferences, Mat4f, PascalArray, Quat, RangeBeginEnd, RangeBeginSize, Vec2f, Vec3,
-    Vec3f, VertexVectorComponent, WALLEObjectFormatTrait,
-};
-// use crate::walle_fmt::material::MaterialObject;
-
-#[derive(BinWrite)]
-#[binwrite(little)]
-#[derive(Serialize, Deserialize, NomLE)]
-struct Strip {
-    strip_vertices_indices: PascalArray<u16>,
-    material_name: u32,
-    tri_order: u32,
-}
-
-#[derive(BinWrite)]
-#[binwrite(little)]
-#[derive(Serialize, Deserialize, NomLE)]
-struct CollisionAABB {
-    min: Vec3f,
-    collision_aabb_range: RangeBeginEnd,
-    max: Vec3f,
-    collision_faces_range: RangeBeginSize,
-}
 
-#[derive(BinWrite)]
-#[binwrite(little)]
-#[derive(Serialize, Deserialize, NomLE)]
-struct CollisionFace {
-    short_vec_weirds_indices: FixedVec<u16, 3>,
-    surface_type: u16,
-}
+use crate::asobo_fmt::common::{
+    FixedVec, HasReferences, Mat4f, ObjectFormatTrait, PascalArray, Quat, Vec2f, Vec3, Vec3f,
+    VertexVectorComponent,
+};
+use crate::error::DpcError;
+use crate::glb::{self, GlbBuilder, GlbVertices};
+use crate::names::NameTable;
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -146,9 +114,10 @@ impl VertexBufferData {
                     VertexBufferData::VertexLayoutPositionUVCase(parse_result.1),
                 ))
             }
-            _ => {
-                panic!("Invalid vertex size {}", vertex_size)
-            }
+            _ => Err(nom::Err::Error(nom::error::Error::new(
+                i,
+                nom::error::ErrorKind::Verify,
+            ))),
         }
     }
 }
@@ -246,14 +215,6 @@ impl From<IndexBufferExtShadow> for IndexBufferExt {
     }
 }
 
-#[derive(BinWrite)]
-#[binwrite(little)]
-#[derive(Serialize, Deserialize, NomLE)]
-struct Quad {
-    vertices: FixedVec<Vec3f, 4>,
-    normal: Vec3f,
-}
-
 #[derive(BinWrite)]
 #[binwrite(little)]
 #[derive(Serialize, Deserialize, NomLE)]
@@ -272,6 +233,8 @@ struct VertexGroup {
     cdcdcdcd: u16,
 }
 
+// Unlike FUEL there are no strips, quads or collision faces, all the geometry is in the vertex
+// and index buffers. Vertex groups have no material index either
 #[derive(BinWrite)]
 #[binwrite(little)]
 #[derive(Serialize, Deserialize, NomLE)]
@@ -317,7 +280,11 @@ impl HasReferences for MeshZHeader {
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        self.crc32s.data.clone()
+        let mut crc32s = self.crc32s.data.clone();
+        if self.mesh_data_crc32 != 0 {
+            crc32s.push(self.mesh_data_crc32);
+        }
+        crc32s
     }
 }
 
@@ -331,7 +298,194 @@ impl HasReferences for MeshZ {
     }
 }
 
-// pub type MeshObjectFormat = WALLEObjectFormat<MeshZHeader, MeshZ>;
+fn vertex_vector(vector: &VertexVector3u8) -> [f32; 3] {
+    glb::normalize([
+        -((vector.data[0].data as f32 / 255.0) * 2.0 - 1.0),
+        -((vector.data[1].data as f32 / 255.0) * 2.0 - 1.0),
+        -((vector.data[2].data as f32 / 255.0) * 2.0 - 1.0),
+    ])
+}
+
+fn vec3f(vector: &Vec3f) -> [f32; 3] {
+    [vector.data[0], vector.data[1], vector.data[2]]
+}
+
+fn vec2f(vector: &Vec2f) -> [f32; 2] {
+    [vector.data[0], vector.data[1]]
+}
+
+impl VertexBufferData {
+    fn glb_vertices(&self) -> GlbVertices {
+        let mut vertices = GlbVertices::default();
+        match self {
+            VertexBufferData::VertexLayout4BlendCase(data) => {
+                for vertex in data {
+                    let tangent = vertex_vector(&vertex.tangent);
+                    vertices.positions.push(vec3f(&vertex.position));
+                    vertices.normals.push(vertex_vector(&vertex.normal));
+                    vertices
+                        .tangents
+                        .push([tangent[0], tangent[1], tangent[2], 1.0]);
+                    vertices.uvs.push(vec2f(&vertex.uv));
+                    vertices.joints.push([
+                        vertex.blend_indices.data[0].index as u16,
+                        vertex.blend_indices.data[1].index as u16,
+                        vertex.blend_indices.data[2].index as u16,
+                        vertex.blend_indices.data[3].index as u16,
+                    ]);
+                    vertices.weights.push(glb::normalize_weights([
+                        vertex.blend_weights.data[0],
+                        vertex.blend_weights.data[1],
+                        vertex.blend_weights.data[2],
+                        vertex.blend_weights.data[3],
+                    ]));
+                }
+            }
+            VertexBufferData::VertexLayout1BlendCase(data) => {
+                for vertex in data {
+                    let tangent = vertex_vector(&vertex.tangent);
+                    vertices.positions.push(vec3f(&vertex.position));
+                    vertices.normals.push(vertex_vector(&vertex.normal));
+                    vertices
+                        .tangents
+                        .push([tangent[0], tangent[1], tangent[2], 1.0]);
+                    vertices.uvs.push(vec2f(&vertex.uv));
+                }
+            }
+            VertexBufferData::VertexLayoutNoBlendCase(data) => {
+                for vertex in data {
+                    let tangent = vertex_vector(&vertex.tangent);
+                    vertices.positions.push(vec3f(&vertex.position));
+                    vertices.normals.push(vertex_vector(&vertex.normal));
+                    vertices
+                        .tangents
+                        .push([tangent[0], tangent[1], tangent[2], 1.0]);
+                    vertices.uvs.push(vec2f(&vertex.uv));
+                    vertices.lightmap_uvs.push(vec2f(&vertex.luv));
+                }
+            }
+            VertexBufferData::VertexLayoutPositionUVCase(data) => {
+                for vertex in data {
+                    vertices.positions.push(vec3f(&vertex.position));
+                    vertices.uvs.push(vec2f(&vertex.uv));
+                }
+            }
+        }
+        vertices
+    }
+
+    fn vertex_size(&self) -> u16 {
+        match self {
+            VertexBufferData::VertexLayout4BlendCase(_) => 60,
+            VertexBufferData::VertexLayout1BlendCase(_) => 48,
+            VertexBufferData::VertexLayoutNoBlendCase(_) => 36,
+            VertexBufferData::VertexLayoutPositionUVCase(_) => 24,
+        }
+    }
+}
+
+impl MeshZ {
+    // Groups line up with the materials when there is one per group, a single material is shared
+    fn material_crc32(&self, group_index: usize) -> Option<u32> {
+        let material_crc32s = &self.material_crc32s.data;
+        match material_crc32s.len() {
+            1 => Some(material_crc32s[0]),
+            len if len == self.vertex_groups.len() => material_crc32s.get(group_index).copied(),
+            _ => None,
+        }
+    }
+
+    fn glb_nodes(&self, builder: &mut GlbBuilder, names: &NameTable) -> Vec<usize> {
+        let vertex_buffers = &self.vertex_buffers.data;
+        let index_buffers = &self.indices.data;
+
+        let attributes: Vec<Option<Value>> = vertex_buffers
+            .iter()
+            .map(|vertex_buffer| {
+                let vertices = vertex_buffer.vertices.glb_vertices();
+                if vertices.positions.is_empty() {
+                    None
+                } else {
+                    Some(builder.push_vertices(&vertices))
+                }
+            })
+            .collect();
+
+        let mut primitives = Vec::new();
+        if !self.vertex_groups.data.is_empty() {
+            for (i, vertex_group) in self.vertex_groups.data.iter().enumerate() {
+                let vertex_buffer_index = match vertex_buffers
+                    .iter()
+                    .position(|x| x.vertices.vertex_size() == vertex_group.vertex_size)
+                {
+                    Some(vertex_buffer_index) => vertex_buffer_index,
+                    None => continue,
+                };
+                let attributes = match &attributes[vertex_buffer_index] {
+                    Some(attributes) => attributes,
+                    None => continue,
+                };
+                let index_buffer = match index_buffers
+                    .get(vertex_buffer_index)
+                    .or(index_buffers.first())
+                {
+                    Some(index_buffer) => index_buffer,
+                    None => continue,
+                };
+                let begin = vertex_group.index_buffer_offset as usize;
+                let end = begin + vertex_group.face_count as usize * 3;
+                if begin == end || end > index_buffer.indices.len() {
+                    continue;
+                }
+
+                let indices: Vec<u32> = index_buffer.indices[begin..end]
+                    .iter()
+                    .map(|x| *x as u16 as u32)
+                    .collect();
+                let indices = builder.push_indices(&indices);
+                let material = self
+                    .material_crc32(i)
+                    .map(|crc32| builder.material(crc32, names));
+                primitives.push(GlbBuilder::primitive(
+                    attributes,
+                    Some(indices),
+                    material,
+                    glb::TRIANGLES,
+                ));
+            }
+        } else {
+            for (attributes, index_buffer) in attributes.iter().zip(index_buffers) {
+                if let Some(attributes) = attributes {
+                    if index_buffer.indices.is_empty() {
+                        continue;
+                    }
+                    let indices: Vec<u32> = index_buffer
+                        .indices
+                        .iter()
+                        .map(|x| *x as u16 as u32)
+                        .collect();
+                    let indices = builder.push_indices(&indices);
+                    let material = self
+                        .material_crc32(primitives.len())
+                        .map(|crc32| builder.material(crc32, names));
+                    primitives.push(GlbBuilder::primitive(
+                        attributes,
+                        Some(indices),
+                        material,
+                        glb::TRIANGLES,
+                    ));
+                }
+            }
+        }
+
+        if primitives.is_empty() {
+            return vec![];
+        }
+
+        let mesh = builder.add_mesh("mesh", primitives);
+        vec![builder.add_node(json!({ "name": "mesh", "mesh": mesh }))]
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 struct MeshObject {
@@ -339,6 +493,24 @@ struct MeshObject {
     mesh: MeshZ,
 }
 
+impl HasReferences for MeshObject {
+    fn hard_links(&self) -> Vec<u32> {
+        [
+            &self.mesh_header.hard_links()[..],
+            &self.mesh.hard_links()[..],
+        ]
+        .concat()
+    }
+
+    fn soft_links(&self) -> Vec<u32> {
+        [
+            &self.mesh_header.soft_links()[..],
+            &self.mesh.soft_links()[..],
+        ]
+        .concat()
+    }
+}
+
 pub struct MeshObjectFormat;
 
 impl MeshObjectFormat {
@@ -347,13 +519,13 @@ impl MeshObjectFormat {
     }
 }
 
-impl WALLEObjectFormatTrait for MeshObjectFormat {
+impl ObjectFormatTrait for MeshObjectFormat {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
@@ -362,186 +534,76 @@ impl WALLEObjectFormatTrait for MeshObjectFormat {
         object.mesh_header.write(header)?;
         object.mesh.write(body)?;
 
-        Ok((
-            object.mesh_header.hard_links(),
-            object.mesh_header.soft_links(),
-        ))
+        Ok((object.hard_links(), object.soft_links()))
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        self.unpack_named(header, body, output_path, &NameTable::new())
+    }
+
+    fn unpack_named(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+        names: &NameTable,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
-        let mesh_header = match MeshZHeader::parse(&header) {
+        let mesh_header = match MeshZHeader::parse(header) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
         };
 
         let mesh = match MeshZ::parse(body) {
             Ok((_, h)) => h,
-            Err(_) => return Err(Error::from(ErrorKind::Other)),
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
         };
 
-        // struct GLTFScene {
-        //     nodes: Vec<usize>,
-        // }
-
-        // struct GLTFNode {
-        //     mesh: usize,
-        // }
-
-        // struct GLTFPrimitive {
-        //     attributes: Map<String, f32>,
-        //     indices: usize,
-        // }
-
-        // struct GLTFMesh {
-        //     primitives: Vec<GLTFPrimitive>,
-        // }
-
-        // struct GLTFAsset {
-        //     version: f32,
-        // }
-
-        // struct GLTFFile {
-        //     scene: usize,
-        //     scenes: Vec<GLTFScene>,
-        //     nodes: Vec<GLTFNode>,
-        //     meshes: Vec<GLTFMesh>,
-        //     asset: GLTFAsset,
-        // }
-
-        // pb.println(format!("Extracting {}", oh.crc32));
-
+        // Index buffer i indexes into vertex buffer i, which starts after the vertices of the
+        // buffers before it in data.obj
         let mut vertex_positions: Vec<[f32; 3]> = Vec::new();
-        // let mut indices: Vec<u16> = Vec::new();
         let mut vertex_uvs: Vec<[f32; 2]> = Vec::new();
         let mut vertex_normals: Vec<[f32; 3]> = Vec::new();
+        let mut vertex_offsets: Vec<usize> = Vec::new();
         let mut polygons: Vec<obj::SimplePolygon> = Vec::new();
 
         for vbuffer in &mesh.vertex_buffers.data {
-            match &vbuffer.vertices {
-                VertexBufferData::VertexLayout4BlendCase(buffer_vertices) => {
-                    for vertex in buffer_vertices {
-                        let position: [f32; 3] = vertex.position.data.clone().try_into().unwrap();
-                        let uv: [f32; 2] = vertex.uv.data.clone().try_into().unwrap();
-                        let mut normal_vec: Vec<f32> = vec![];
-                        for component in &vertex.normal.data {
-                            normal_vec.push(-((component.data.clone() as f32 / 255.0) * 2.0 - 1.0));
-                        }
-                        let normal: [f32; 3] = normal_vec.try_into().unwrap();
-                        vertex_positions.push(position);
-                        vertex_uvs.push(uv);
-                        vertex_normals.push(normal);
-                    }
-                }
-                VertexBufferData::VertexLayout1BlendCase(buffer_vertices) => {
-                    for vertex in buffer_vertices {
-                        let position: [f32; 3] = vertex.position.data.clone().try_into().unwrap();
-                        let uv: [f32; 2] = vertex.uv.data.clone().try_into().unwrap();
-                        let mut normal_vec: Vec<f32> = vec![];
-                        for component in &vertex.normal.data {
-                            normal_vec.push(-((component.data.clone() as f32 / 255.0) * 2.0 - 1.0));
-                        }
-                        let normal: [f32; 3] = normal_vec.try_into().unwrap();
-                        vertex_positions.push(position);
-                        vertex_uvs.push(uv);
-                        vertex_normals.push(normal);
-                    }
-                }
-                VertexBufferData::VertexLayoutNoBlendCase(buffer_vertices) => {
-                    for vertex in buffer_vertices {
-                        let position: [f32; 3] = vertex.position.data.clone().try_into().unwrap();
-                        let uv: [f32; 2] = vertex.uv.data.clone().try_into().unwrap();
-                        let mut normal_vec: Vec<f32> = vec![];
-                        for component in &vertex.normal.data {
-                            normal_vec.push(-((component.data.clone() as f32 / 255.0) * 2.0 - 1.0));
-                        }
-                        let normal: [f32; 3] = normal_vec.try_into().unwrap();
-                        vertex_positions.push(position);
-                        vertex_uvs.push(uv);
-                        vertex_normals.push(normal);
-                    }
-                }
-                VertexBufferData::VertexLayoutPositionUVCase(buffer_vertices) => {
-                    for vertex in buffer_vertices {
-                        let position: [f32; 3] = vertex.position.data.clone().try_into().unwrap();
-                        let uv: [f32; 2] = vertex.uv.data.clone().try_into().unwrap();
-                        vertex_positions.push(position);
-                        vertex_uvs.push(uv);
-                        vertex_normals.push([0.0; 3]);
-                    }
-                }
+            vertex_offsets.push(vertex_positions.len());
+            let vertices = vbuffer.vertices.glb_vertices();
+            let vertex_count = vertices.positions.len();
+            if vertices.normals.len() == vertex_count {
+                vertex_normals.extend(vertices.normals);
+            } else {
+                vertex_normals.extend(vec![[0.0; 3]; vertex_count]);
             }
+            vertex_positions.extend(vertices.positions);
+            vertex_uvs.extend(vertices.uvs);
         }
-        for ibuffer in &mesh.indices.data {
-            for triangle in ibuffer.indices.chunks(3).map(|x| x.to_vec()) {
-                let mut tuple: Vec<obj::IndexTuple> = Vec::new();
-                for index in triangle {
-                    tuple.push(obj::IndexTuple(index as usize, None, Some(index as usize)))
-                }
+        for (i, ibuffer) in mesh.indices.data.iter().enumerate() {
+            let vertex_offset = vertex_offsets.get(i).copied().unwrap_or(0);
+            for triangle in ibuffer.indices.chunks(3) {
+                let tuple = triangle
+                    .iter()
+                    .map(|index| {
+                        let index = vertex_offset + *index as u16 as usize;
+                        obj::IndexTuple(index, None, Some(index))
+                    })
+                    .collect();
                 polygons.push(obj::SimplePolygon(tuple));
-                // indices.push(*index as u16);
             }
         }
 
-        // let mut materials: Vec<obj::Material> = vec![];
-        // for material_crc32 in &mesh.material_crc32s.data {
-        //     let dir = output_path.parent().unwrap();
-        //     let mtl_input_path = dir.join(format!("{}.Material_Z", &material_crc32));
-        //     let mut mtl_output_path = OsString::new();
-        //     mtl_output_path.push(mtl_input_path.as_os_str());
-        //     mtl_output_path.push(".d");
-        //     let mtl_json_path = Path::new(&mtl_output_path).join("object.json");
-        //     if !mtl_json_path.try_exists().unwrap() {
-        //         let dpc = WALLEDPC::new(
-        //             &Options {
-        //                 is_force: true,
-        //                 is_quiet: true,
-        //                 is_lz: true,
-        //                 is_unsafe: true,
-        //                 is_optimization: false,
-        //                 is_recursive: false,
-        //             },
-        //             &Vec::<&OsStr>::new(),
-        //         );
-        //         match dpc.fmt_extract(&mtl_input_path, &PathBuf::from(&mtl_output_path)) {
-        //             Ok(_) => {
-        //                 println!("parsed material from mesh parser");
-        //             }
-        //             Err(ref e) => {
-        //                 if e.kind() != ErrorKind::Other {
-        //                     panic!(
-        //                         "{} {} {}",
-        //                         e,
-        //                         mtl_input_path.to_string_lossy(),
-        //                         mtl_output_path.to_string_lossy()
-        //                     );
-        //                 }
-        //             }
-        //         }
-        //     }
-        //     let mtl_json_file = File::open(mtl_json_path)?;
-        //     let mtl_object: MaterialObject = serde_json::from_reader(mtl_json_file)?;
-
-        //     let color: [f32; 3] = mtl_object.material.color.data[..3].try_into().unwrap();
-
-        //     let mut material = obj::Material::new(String::from("material"));
-        //     material.ka = Some(color);
-        //     materials.push(material);
-        // }
-        // let arc_materials = Arc::new(materials.get(0).unwrap().to_owned());
-
         let group = obj::Group {
             name: String::from("meshgroup"),
             index: 0,
             material: None,
-            // material: Some(obj::ObjMaterial::Mtl(arc_materials.clone())),
             polys: polygons,
         };
         let object = obj::Object {
@@ -558,58 +620,128 @@ impl WALLEObjectFormatTrait for MeshObjectFormat {
             normal: vertex_normals,
             objects: vec![object],
             material_libs: Vec::new(),
-            // material_libs: vec![obj::Mtl {
-            //     filename: String::from("materials.mtl"),
-            //     materials: vec![arc_materials.clone()],
-            // }],
         };
-        obj_data.write_to_buf(&mut output_obj_file).unwrap();
-
-        // GLTF EXPORT ATTEMPT...
-        // let tri_mesh = three_d_asset::geometry::TriMesh {
-        //     positions: three_d_asset::geometry::Positions::F32(vertex_positions),
-        //     indices: three_d_asset::geometry::Indices::U16(indices),
-        //     uvs: Some(vertex_uvs),
-        //     normals: None,
-        //     tangents: None,
-        //     colors: None,
-        // };
-        // let mut transform: [[f32; 4]; 4] = [[0.0; 4]; 4];
-        // for item in mesh_header
-        //     .transform
-        //     .data
-        //     .chunks(4)
-        //     .map(|x| x.to_vec())
-        //     .enumerate()
-        // {
-        //     let (i, x): (usize, Vec<f32>) = item;
-        //     transform[i] = x.try_into().unwrap();
-        // }
-
-        // let save_mesh = three_d_asset::Primitive {
-        //     name: String::from("mesh"),
-        //     transformation: prelude::Matrix4::from(transform),
-        //     geometry: three_d_asset::geometry::Geometry::Triangles(tri_mesh),
-        //     animations: Vec::new(),
-        //     material_index: None,
-        // };
-        // let save_model = three_d_asset::Model {
-        //     name: String::from("model"),
-        //     geometries: vec![save_mesh],
-        //     materials: vec![],
-        // };
-
-        // let mut assets = three_d_asset::io::RawAssets::new();
-        // assets.insert(data_path, save_model);
-        // assets.save();
+        obj_data.write_to_buf(&mut output_obj_file)?;
+
+        let mut builder = GlbBuilder::new();
+        let children = mesh.glb_nodes(&mut builder, names);
+        builder.add_node(json!({
+            "name": "root",
+            "matrix": mesh_header.transform.data,
+            "children": children
+        }));
+
+        let mut glb_file = File::create(output_path.join("data.glb"))?;
+        builder.write(&mut glb_file)?;
 
         let object = MeshObject { mesh_header, mesh };
 
-        output_file.write(serde_json::to_string_pretty(&object)?.as_bytes())?;
+        output_file.write_all(serde_json::to_string_pretty(&object)?.as_bytes())?;
+
+        Ok((object.hard_links(), object.soft_links()))
+    }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        let mesh_header = match MeshZHeader::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        let mesh = match MeshZ::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
 
-        Ok((
-            object.mesh_header.hard_links(),
-            object.mesh_header.soft_links(),
-        ))
+        Ok(serde_json::to_value(MeshObject { mesh_header, mesh })?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn mesh_header() -> Vec<u8> {
+        let mut header: Vec<u8> = Vec::new();
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&1u32.to_le_bytes());
+        header.extend_from_slice(&3517318496u32.to_le_bytes());
+        header.extend_from_slice(&2285438457u32.to_le_bytes());
+        header.extend_from_slice(&[0u8; 16 + 64 + 4 + 4 + 2]);
+        header
+    }
+
+    fn mesh_body() -> Vec<u8> {
+        let mut body: Vec<u8> = Vec::new();
+        body.extend_from_slice(&[0u8; 4 + 4 + 4 + 16]);
+        body.extend_from_slice(&2u32.to_le_bytes());
+        body.extend_from_slice(&1234u32.to_le_bytes());
+        body.extend_from_slice(&5678u32.to_le_bytes());
+        body.extend_from_slice(&[0u8; 24 + 5 * 4 + 4 + 4 + 4 + 4 + 4 + 16]);
+        body
+    }
+
+    #[test]
+    fn test_mesh_links() {
+        let header = mesh_header();
+        let body = mesh_body();
+        let dir = TempDir::new("dpc-walle-mesh").unwrap();
+        let format = MeshObjectFormat::new();
+        let expected = (vec![1234, 5678], vec![3517318496, 2285438457]);
+        assert_eq!(format.unpack(&header, &body, dir.path()).unwrap(), expected);
+
+        let mut packed_header = vec![];
+        let mut packed_body = vec![];
+        assert_eq!(
+            format
+                .pack(dir.path(), &mut packed_header, &mut packed_body)
+                .unwrap(),
+            expected
+        );
+        assert_eq!(packed_header, header);
+        assert_eq!(packed_body, body);
+    }
+
+    #[test]
+    fn test_obj_vertex_offsets() {
+        let mut body: Vec<u8> = vec![0; 84];
+        // Two vertex buffers of three position and uv vertices
+        body.extend_from_slice(&2u32.to_le_bytes());
+        for buffer in 0..2 {
+            for x in [3u32, 24, 0] {
+                body.extend_from_slice(&x.to_le_bytes());
+            }
+            for vertex in 0..3 {
+                body.extend_from_slice(&((buffer * 3 + vertex) as f32).to_le_bytes());
+                body.extend_from_slice(&[0; 20]);
+            }
+        }
+        // An index buffer for each
+        body.extend_from_slice(&2u32.to_le_bytes());
+        for _ in 0..2 {
+            for x in [3u32, 0] {
+                body.extend_from_slice(&x.to_le_bytes());
+            }
+            for index in [0i16, 1, 2] {
+                body.extend_from_slice(&index.to_le_bytes());
+            }
+        }
+        body.extend_from_slice(&[0; 4 + 16]);
+
+        let dir = TempDir::new("dpc-walle-mesh").unwrap();
+        MeshObjectFormat::new()
+            .unpack(&mesh_header(), &body, dir.path())
+            .unwrap();
+
+        let obj_file = File::open(dir.path().join("data.obj")).unwrap();
+        let obj_data = obj::ObjData::load_buf(obj_file).unwrap();
+        let faces: Vec<Vec<usize>> = obj_data.objects[0].groups[0]
+            .polys
+            .iter()
+            .map(|poly| poly.0.iter().map(|tuple| tuple.0).collect())
+            .collect();
+        assert_eq!(faces, vec![vec![0, 1, 2], vec![3, 4, 5]]);
+        assert_eq!(obj_data.position[3][0], 3.0);
     }
 }
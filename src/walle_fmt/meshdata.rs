@@ -2,7 +2,7 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::walle_fmt::common::{Color, HasReferences, ResourceObjectZ, WALLEObjectFormat};
+use crate::asobo_fmt::common::{Color, HasReferences, ObjectFormat, ResourceObjectZ};
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -23,4 +23,4 @@ impl HasReferences for MeshDataZ {
     }
 }
 
-pub type MeshDataObjectFormat = WALLEObjectFormat<ResourceObjectZ, MeshDataZ>;
+pub type MeshDataObjectFormat = ObjectFormat<ResourceObjectZ, MeshDataZ>;
@@ -2,9 +2,8 @@ use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
 
-use crate::walle_fmt::common::{
-    Color, FixedVec, HasReferences, Mat4f, Quat, Rect, ResourceObjectZ, SphereZ, Vec3f,
-    WALLEObjectFormat,
+use crate::asobo_fmt::common::{
+    Color, HasReferences, Mat4f, ObjectFormat, Quat, Rect, ResourceObjectZ, SphereZ, Vec3f,
 };
 
 #[derive(BinWrite)]
@@ -48,30 +47,6 @@ pub struct NodeZ {
     unknown8: u32,
 }
 
-#[derive(BinWrite)]
-#[binwrite(little)]
-#[derive(Serialize, Deserialize, NomLE)]
-#[nom(Exact)]
-pub struct NodeZAlt {
-    parent_crc32: u32,
-    some_node_crc320: u32,
-    some_node_crc321: u32,
-    some_node_crc322: u32,
-    some_crc320: u32,
-    some_crc321: u32,
-    some_crc322: u32,
-    some_crc323: u32,
-    some_crc324: u32,
-    mat0: Mat4f,
-    unknown0s: FixedVec<u8, 208>,
-    mat1: Mat4f,
-    unknown2: u32,
-    unknown3: u32,
-    unknown4: u16,
-    unknown5: u32,
-    unknown6: u32,
-}
-
 impl HasReferences for NodeZ {
     fn hard_links(&self) -> Vec<u32> {
         vec![]
@@ -110,15 +85,24 @@ impl HasReferences for NodeZ {
     }
 }
 
-impl HasReferences for NodeZAlt {
-    fn hard_links(&self) -> Vec<u32> {
-        vec![]
-    }
+pub type NodeObjectFormat = ObjectFormat<ResourceObjectZ, NodeZ>;
 
-    fn soft_links(&self) -> Vec<u32> {
-        vec![]
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_node_links() {
+        // parent, head child, next node, object, user define, bitmap
+        let mut data: Vec<u8> = Vec::new();
+        for crc32 in [1, 2, 0, 4, 5, 6, 0, 8, 0u32] {
+            data.extend_from_slice(&crc32.to_le_bytes());
+        }
+        data.extend_from_slice(
+            &[0u8; 64 + 16 * 3 + 64 + 16 + 16 + 16 + 16 + 16 + 32 + 64 + 4 + 4 + 2 + 4 + 4],
+        );
+        let (_, node) = NodeZ::parse(&data[..]).unwrap();
+        assert_eq!(node.hard_links(), Vec::<u32>::new());
+        assert_eq!(node.soft_links(), vec![1, 2, 4, 5, 6, 8]);
     }
 }
-
-pub type NodeObjectFormat = WALLEObjectFormat<ResourceObjectZ, NodeZ>;
-pub type NodeObjectFormatAlt = WALLEObjectFormat<ResourceObjectZ, NodeZAlt>;
@@ -1,11 +1,18 @@
+use std::fs::File;
+use std::path::Path;
+
 use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::walle_fmt::common::{
-    FixedVec, HasReferences, Mat4f, PascalArray, Quat, ResourceObjectZ, SphereZ, Vec3f, Vec3i32,
-    WALLEObjectFormat,
+use crate::asobo_fmt::common::{
+    FixedVec, HasReferences, Mat4f, ObjectFormat, ObjectFormatTrait, PascalArray, Quat,
+    ResourceObjectZ, SphereZ, Vec3f,
 };
+use crate::error::DpcError;
+use crate::glb::{GlbBuilder, GlbJoint};
+use crate::names::NameTable;
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -94,8 +101,153 @@ impl HasReferences for SkelZ {
         );
         v.append(&mut self.material_crc32s.data.clone());
         v.append(&mut self.mesh_data_crc32s.data.clone());
+        let mut bone_links = Vec::new();
+        for group in self.bone_node_groups.data.iter() {
+            bone_links.append(&mut group.data.clone());
+        }
+        for col_bone in self
+            .sphere_col_bones1
+            .data
+            .iter()
+            .chain(self.sphere_col_bones2.data.iter())
+        {
+            bone_links.push(col_bone.name_crc32);
+            bone_links.push(col_bone.bone_node_crc32);
+        }
+        for col_bone in self.box_col_bones.data.iter() {
+            bone_links.push(col_bone.name_crc32);
+            bone_links.push(col_bone.bone_node_crc32);
+        }
+        bone_links.retain(|x| *x != 0);
+        v.append(&mut bone_links);
         v
     }
 }
 
-pub type SkelObjectFormat = WALLEObjectFormat<ResourceObjectZ, SkelZ>;
+impl SkelZ {
+    fn glb_joints(&self, names: &NameTable) -> Vec<GlbJoint> {
+        self.bones
+            .data
+            .iter()
+            .map(|bone| GlbJoint {
+                name: names.name_or_crc32(bone.bone_name),
+                parent: if bone.parent_index >= 0 {
+                    Some(bone.parent_index as usize)
+                } else {
+                    None
+                },
+                translation: [
+                    bone.local_translation.data[0],
+                    bone.local_translation.data[1],
+                    bone.local_translation.data[2],
+                ],
+                rotation: [
+                    bone.local_rotation.data[0],
+                    bone.local_rotation.data[1],
+                    bone.local_rotation.data[2],
+                    bone.local_rotation.data[3],
+                ],
+                scale: [bone.scale.data[0], bone.scale.data[1], bone.scale.data[2]],
+                model_transform: {
+                    let mut model_transform = [0.0; 16];
+                    model_transform.copy_from_slice(&bone.original_model_transform.data);
+                    model_transform
+                },
+            })
+            .collect()
+    }
+}
+
+pub struct SkelObjectFormat;
+
+impl SkelObjectFormat {
+    pub fn new<'a>() -> &'a Self {
+        &Self {}
+    }
+}
+
+impl ObjectFormatTrait for SkelObjectFormat {
+    fn pack(
+        &self,
+        input_path: &Path,
+        header: &mut Vec<u8>,
+        body: &mut Vec<u8>,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        ObjectFormat::<ResourceObjectZ, SkelZ>::new().pack(input_path, header, body)
+    }
+
+    fn unpack(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        self.unpack_named(header, body, output_path, &NameTable::new())
+    }
+
+    fn unpack_named(
+        &self,
+        header: &[u8],
+        body: &[u8],
+        output_path: &Path,
+        names: &NameTable,
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
+        let references =
+            ObjectFormat::<ResourceObjectZ, SkelZ>::new().unpack(header, body, output_path)?;
+
+        let skel = match SkelZ::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
+
+        let mut builder = GlbBuilder::new();
+        builder.add_skin("skeleton", &skel.glb_joints(names));
+
+        let mut glb_file = File::create(output_path.join("data.glb"))?;
+        builder.write(&mut glb_file)?;
+
+        Ok(references)
+    }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        ObjectFormat::<ResourceObjectZ, SkelZ>::new().to_json(header, body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_skel_links() {
+        let mut data: Vec<u8> = vec![0; 4 + 16];
+        // No bones, one material, one mesh data
+        for x in [0, 1, 10, 1, 11u32] {
+            data.extend_from_slice(&x.to_le_bytes());
+        }
+        // Two bone node groups
+        for x in [2, 2, 20, 21, 1, 22u32] {
+            data.extend_from_slice(&x.to_le_bytes());
+        }
+        // No unknown0s, one sphere in each list
+        data.extend_from_slice(&0u32.to_le_bytes());
+        for (name_crc32, bone_node_crc32) in [(30u32, 31u32), (0, 32)] {
+            data.extend_from_slice(&1u32.to_le_bytes());
+            data.extend_from_slice(&[0; 16 + 4]);
+            data.extend_from_slice(&name_crc32.to_le_bytes());
+            data.extend_from_slice(&bone_node_crc32.to_le_bytes());
+        }
+        // One box
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&[0; 64 + 4]);
+        data.extend_from_slice(&40u32.to_le_bytes());
+        data.extend_from_slice(&41u32.to_le_bytes());
+
+        let (_, skel) = SkelZ::parse(&data[..]).unwrap();
+        assert_eq!(skel.hard_links(), Vec::<u32>::new());
+        assert_eq!(
+            skel.soft_links(),
+            vec![10, 11, 20, 21, 22, 30, 31, 32, 40, 41]
+        );
+    }
+}
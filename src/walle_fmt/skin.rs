@@ -1,20 +1,15 @@
-use std::{
-    io::Error,
-    io::{ErrorKind, Write},
-    path::Path,
-};
+use std::{io::Write, path::Path};
 
 use binwrite::BinWrite;
 use nom_derive::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fs::File;
 
-use crate::walle_fmt::common::{
-    write_option, FixedVec, HasReferences, Mat4f, ObjectZ, PascalArray, SphereZ,
-    WALLEObjectFormatTrait,
+use crate::asobo_fmt::common::{
+    FixedVec, HasReferences, Mat4f, ObjectFormatTrait, PascalArray, SphereZ,
 };
-
-static mut SKIN_DATA_COUNT: u32 = 0;
+use crate::error::DpcError;
 
 #[derive(BinWrite)]
 #[binwrite(little)]
@@ -76,7 +71,7 @@ struct SkinZSkinSubsection {
     // unknown_crc320: u32,
     // unknown_crc321: u32,
     // unknown_crc322: u32,
-    // #[nom(Count(unsafe { SKIN_DATA_COUNT as usize }))]
+    // #[nom(Count(data_count as usize))]
     // data: Vec<u32>,
 }
 
@@ -117,7 +112,15 @@ pub struct SkinZHeader {
 
 impl HasReferences for SkinZ {
     fn hard_links(&self) -> Vec<u32> {
-        vec![]
+        let mut v = Vec::new();
+        for skin_section in self.skin_sections.data.iter() {
+            for skin_subsection in skin_section.data.iter() {
+                if skin_subsection.material_link_crc32 != 0 {
+                    v.push(skin_subsection.material_link_crc32)
+                }
+            }
+        }
+        v
     }
 
     fn soft_links(&self) -> Vec<u32> {
@@ -147,11 +150,19 @@ struct SkinObject {
 
 impl HasReferences for SkinObject {
     fn hard_links(&self) -> Vec<u32> {
-        vec![]
+        [
+            &self.skin_header.hard_links()[..],
+            &self.skin.hard_links()[..],
+        ]
+        .concat()
     }
 
     fn soft_links(&self) -> Vec<u32> {
-        vec![]
+        [
+            &self.skin_header.soft_links()[..],
+            &self.skin.soft_links()[..],
+        ]
+        .concat()
     }
 }
 
@@ -163,59 +174,117 @@ impl SkinObjectFormat {
     }
 }
 
-impl WALLEObjectFormatTrait for SkinObjectFormat {
+impl ObjectFormatTrait for SkinObjectFormat {
     fn pack(
-        self: &Self,
+        &self,
         input_path: &Path,
         header: &mut Vec<u8>,
         body: &mut Vec<u8>,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = input_path.join("object.json");
         let json_file = File::open(json_path)?;
 
-        let mut object: SkinObject = serde_json::from_reader(json_file)?;
+        let object: SkinObject = serde_json::from_reader(json_file)?;
 
         object.skin_header.write(header)?;
         object.skin.write(body)?;
 
-        Ok((
-            object.skin_header.hard_links(),
-            object.skin_header.soft_links(),
-        ))
+        Ok((object.hard_links(), object.soft_links()))
     }
 
     fn unpack(
-        self: &Self,
+        &self,
         header: &[u8],
         body: &[u8],
         output_path: &Path,
-    ) -> Result<(Vec<u32>, Vec<u32>), Error> {
+    ) -> Result<(Vec<u32>, Vec<u32>), DpcError> {
         let json_path = output_path.join("object.json");
         let mut output_file = File::create(json_path)?;
 
         let skin_header = match SkinZHeader::parse(&header) {
             Ok((_, h)) => h,
-            Err(e) => {
-                panic!(e.to_string());
-                return Err(Error::from(ErrorKind::Other));
-            }
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
         };
 
         let skin = match SkinZ::parse(body) {
             Ok((_, h)) => h,
-            Err(e) => {
-                panic!(e.to_string());
-                return Err(Error::from(ErrorKind::Other));
-            }
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
         };
 
         let object = SkinObject { skin_header, skin };
 
         output_file.write(serde_json::to_string_pretty(&object)?.as_bytes())?;
 
-        Ok((
-            object.skin_header.hard_links(),
-            object.skin_header.soft_links(),
-        ))
+        Ok((object.hard_links(), object.soft_links()))
+    }
+
+    fn to_json(&self, header: &[u8], body: &[u8]) -> Result<Value, DpcError> {
+        let skin_header = match SkinZHeader::parse(header) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("class object", 0, header, error)),
+        };
+
+        let skin = match SkinZ::parse(body) {
+            Ok((_, h)) => h,
+            Err(error) => return Err(DpcError::parse("body", 0, body, error)),
+        };
+
+        Ok(serde_json::to_value(SkinObject { skin_header, skin })?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn skin_header() -> Vec<u8> {
+        let mut header: Vec<u8> = Vec::new();
+        header.extend_from_slice(&[0u8; 4 + 4]);
+        header.extend_from_slice(&1920180802u32.to_le_bytes());
+        header.extend_from_slice(&[0u8; 16 + 64 + 4 + 4 + 2]);
+        header
+    }
+
+    fn skin_subsection(material_crc32: u32) -> Vec<u8> {
+        let mut subsection: Vec<u8> = Vec::new();
+        subsection.extend_from_slice(&material_crc32.to_le_bytes());
+        subsection.extend_from_slice(&[0u8; 7 * 4 + 8 + 4]);
+        subsection
+    }
+
+    fn skin_body() -> Vec<u8> {
+        let mut body: Vec<u8> = Vec::new();
+        body.extend_from_slice(&2u32.to_le_bytes());
+        body.extend_from_slice(&3517318496u32.to_le_bytes());
+        body.extend_from_slice(&2285438457u32.to_le_bytes());
+        body.extend_from_slice(&[0u8; 4 + 4 + 1 + 4]);
+        body.extend_from_slice(&1u32.to_le_bytes());
+        body.extend_from_slice(&2u32.to_le_bytes());
+        body.extend(skin_subsection(1234));
+        body.extend(skin_subsection(0));
+        body
+    }
+
+    #[test]
+    fn test_skin_links() {
+        let header = skin_header();
+        let body = skin_body();
+        let dir = TempDir::new("dpc-walle-skin").unwrap();
+        let format = SkinObjectFormat::new();
+        let expected = (vec![1234], vec![1920180802, 3517318496, 2285438457]);
+        assert_eq!(format.unpack(&header, &body, dir.path()).unwrap(), expected);
+
+        let mut packed_header = vec![];
+        let mut packed_body = vec![];
+        assert_eq!(
+            format
+                .pack(dir.path(), &mut packed_header, &mut packed_body)
+                .unwrap(),
+            expected
+        );
+        assert_eq!(packed_header, header);
+        assert_eq!(packed_body, body);
     }
 }